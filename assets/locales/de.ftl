@@ -0,0 +1,89 @@
+popup-download = ↓ DOWNLOAD
+popup-upload = ↑ UPLOAD
+popup-details = Details
+
+menu-view-mode = Ansicht
+menu-mode-all = Alles
+menu-mode-download = Nur Download
+menu-mode-upload = Nur Upload
+menu-mode-overlay = Überlagert
+menu-units = Einheiten
+menu-units-bytes = Bytes (B/s)
+menu-units-bits = Bits (bit/s)
+menu-units-decimal = Dezimal (k = 1000)
+menu-units-binary = Binär (Ki = 1024)
+menu-icon-style = Tray-Symbol
+menu-icon-number = Download-Geschwindigkeit
+menu-icon-sparkline = Verlaufskurve
+menu-icon-bars = Balken (Down/Up)
+menu-icon-split = Zahlen (Down/Up)
+menu-icon-dot = Aktivitätspunkt
+menu-theme = Design
+menu-theme-system = Wie System
+menu-theme-dark = Dunkel
+menu-theme-light = Hell
+menu-theme-high-contrast = Hoher Kontrast
+menu-theme-solarized = Solarized
+menu-graph-log = Logarithmische Diagrammskala
+menu-widget = Mini-Widget
+menu-save-graph = Diagramm speichern unter…
+menu-autostart = Bei Anmeldung starten
+menu-exit = Beenden
+
+error-autostart = Autostart konnte nicht geändert werden: { $error }
+error-theme = Das Design konnte nicht geladen werden: { $error }
+error-settings = Einstellungen konnten nicht gespeichert werden: { $error }
+error-settings-load = Einstellungen konnten nicht gelesen werden, Änderungen werden nicht gespeichert: { $error }
+error-export = Das Diagramm konnte nicht gespeichert werden: { $error }
+error-metrics = Metriken konnten nicht bereitgestellt werden: { $error }
+
+tooltip-speeds = Runter: { $down } | Hoch: { $up }
+tooltip-session = Sitzung: ↓ { $down } ↑ { $up } in { $duration }
+
+duration-days = { $days } T. { $hours } Std.
+duration-hours = { $hours } Std. { $minutes } Min.
+duration-minutes = { $minutes } Min. { $seconds } Sek.
+duration-seconds = { $seconds } Sek.
+duration-whole-minutes = { $minutes } Min.
+duration-under-minute = unter 1 Min.
+
+relative-now = gerade eben
+relative-seconds = vor { $count } Sek.
+relative-minutes = vor { $count } Min.
+relative-hours = vor { $count } Std.
+relative-days = vor { $count } T.
+
+details-interface = Schnittstelle
+details-adapter = Adapter
+details-media = Typ
+details-mac = MAC
+details-ipv4 = IPv4
+details-ipv6 = IPv6
+details-gateway = Gateway
+details-dns = DNS
+details-link-speed = Verbindung
+details-mtu = MTU
+details-uptime = Verbunden seit
+details-unavailable = Keine Details für diese Schnittstelle
+media-ethernet = Ethernet
+media-wifi = WLAN
+media-mobile = Mobilfunk
+media-ppp = PPP
+media-tunnel = Tunnel
+media-other = Sonstige
+export-title = Netzwerkverkehr
+
+tui-waiting = Warte auf die erste Messung…
+tui-keys = m Ansicht · i/Tab Schnittstelle · d Details · u Bytes/Bits · p Präfixe · q Beenden
+tui-interfaces-auto = Schnittstellen · die aktivste
+tui-interfaces-fixed = Schnittstellen · zeige { $name }
+tui-interfaces-down = Schnittstellen · { $name } getrennt, zeige die aktivste
+tui-column-interface = Schnittstelle
+tui-column-down = Download
+tui-column-up = Upload
+tui-column-received = Empfangen
+tui-column-sent = Gesendet
+tui-stat-now = Aktuell
+tui-stat-average = Mittel ({ $seconds } s)
+tui-stat-peak = Spitze ({ $seconds } s)
+tui-stat-session = Sitzung ({ $duration })
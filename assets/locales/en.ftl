@@ -0,0 +1,89 @@
+popup-download = ↓ DOWNLOAD
+popup-upload = ↑ UPLOAD
+popup-details = Details
+
+menu-view-mode = View Mode
+menu-mode-all = All
+menu-mode-download = Download Only
+menu-mode-upload = Upload Only
+menu-mode-overlay = Overlay
+menu-units = Units
+menu-units-bytes = Bytes (B/s)
+menu-units-bits = Bits (bit/s)
+menu-units-decimal = Decimal (k = 1000)
+menu-units-binary = Binary (Ki = 1024)
+menu-icon-style = Tray Icon
+menu-icon-number = Download Speed
+menu-icon-sparkline = Sparkline
+menu-icon-bars = Down/Up Bars
+menu-icon-split = Down/Up Numbers
+menu-icon-dot = Activity Dot
+menu-theme = Theme
+menu-theme-system = Follow System
+menu-theme-dark = Dark
+menu-theme-light = Light
+menu-theme-high-contrast = High Contrast
+menu-theme-solarized = Solarized
+menu-graph-log = Logarithmic Graph Scale
+menu-widget = Mini Widget
+menu-save-graph = Save Graph As…
+menu-autostart = Start on Login
+menu-exit = Exit
+
+error-autostart = Could not change autostart: { $error }
+error-theme = Could not load the theme: { $error }
+error-settings = Could not save settings: { $error }
+error-settings-load = Could not read settings, so changes will not be saved: { $error }
+error-export = Could not save the graph: { $error }
+error-metrics = Could not serve metrics: { $error }
+
+tooltip-speeds = Down: { $down } | Up: { $up }
+tooltip-session = Session: ↓ { $down } ↑ { $up } in { $duration }
+
+duration-days = { $days } d { $hours } h
+duration-hours = { $hours } h { $minutes } min
+duration-minutes = { $minutes } min { $seconds } s
+duration-seconds = { $seconds } s
+duration-whole-minutes = { $minutes } min
+duration-under-minute = under 1 min
+
+relative-now = just now
+relative-seconds = { $count } s ago
+relative-minutes = { $count } min ago
+relative-hours = { $count } h ago
+relative-days = { $count } d ago
+
+details-interface = Interface
+details-adapter = Adapter
+details-media = Type
+details-mac = MAC
+details-ipv4 = IPv4
+details-ipv6 = IPv6
+details-gateway = Gateway
+details-dns = DNS
+details-link-speed = Link speed
+details-mtu = MTU
+details-uptime = Connected for
+details-unavailable = No details for this interface
+media-ethernet = Ethernet
+media-wifi = Wi-Fi
+media-mobile = Mobile broadband
+media-ppp = PPP
+media-tunnel = Tunnel
+media-other = Other
+export-title = Network traffic
+
+tui-waiting = Waiting for the first reading…
+tui-keys = m view · i/Tab interface · d details · u bytes/bits · p prefixes · q quit
+tui-interfaces-auto = Interfaces · following the busiest
+tui-interfaces-fixed = Interfaces · showing { $name }
+tui-interfaces-down = Interfaces · { $name } is down, following the busiest
+tui-column-interface = Interface
+tui-column-down = Down
+tui-column-up = Up
+tui-column-received = Received
+tui-column-sent = Sent
+tui-stat-now = Now
+tui-stat-average = Average ({ $seconds } s)
+tui-stat-peak = Peak ({ $seconds } s)
+tui-stat-session = Session ({ $duration })
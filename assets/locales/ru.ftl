@@ -0,0 +1,89 @@
+popup-download = ↓ ЗАГРУЗКА
+popup-upload = ↑ ОТДАЧА
+popup-details = Подробности
+
+menu-view-mode = Режим просмотра
+menu-mode-all = Всё
+menu-mode-download = Только загрузка
+menu-mode-upload = Только отдача
+menu-mode-overlay = Наложение
+menu-units = Единицы
+menu-units-bytes = Байты (B/s)
+menu-units-bits = Биты (bit/s)
+menu-units-decimal = Десятичные (k = 1000)
+menu-units-binary = Двоичные (Ki = 1024)
+menu-icon-style = Значок в трее
+menu-icon-number = Скорость загрузки
+menu-icon-sparkline = Мини-график
+menu-icon-bars = Полосы (приём/отдача)
+menu-icon-split = Числа (приём/отдача)
+menu-icon-dot = Индикатор активности
+menu-theme = Тема
+menu-theme-system = Как в системе
+menu-theme-dark = Тёмная
+menu-theme-light = Светлая
+menu-theme-high-contrast = Высокий контраст
+menu-theme-solarized = Solarized
+menu-graph-log = Логарифмическая шкала графика
+menu-widget = Мини-виджет
+menu-save-graph = Сохранить график как…
+menu-autostart = Запускать при входе
+menu-exit = Выход
+
+error-autostart = Не удалось изменить автозапуск: { $error }
+error-theme = Не удалось загрузить тему: { $error }
+error-settings = Не удалось сохранить настройки: { $error }
+error-settings-load = Не удалось прочитать настройки, изменения не будут сохранены: { $error }
+error-export = Не удалось сохранить график: { $error }
+error-metrics = Не удалось запустить сервер метрик: { $error }
+
+tooltip-speeds = Загрузка: { $down } | Отдача: { $up }
+tooltip-session = Сеанс: ↓ { $down } ↑ { $up } за { $duration }
+
+duration-days = { $days } д { $hours } ч
+duration-hours = { $hours } ч { $minutes } мин
+duration-minutes = { $minutes } мин { $seconds } с
+duration-seconds = { $seconds } с
+duration-whole-minutes = { $minutes } мин
+duration-under-minute = меньше минуты
+
+relative-now = только что
+relative-seconds = { $count } с назад
+relative-minutes = { $count } мин назад
+relative-hours = { $count } ч назад
+relative-days = { $count } д назад
+
+details-interface = Интерфейс
+details-adapter = Адаптер
+details-media = Тип
+details-mac = MAC
+details-ipv4 = IPv4
+details-ipv6 = IPv6
+details-gateway = Шлюз
+details-dns = DNS
+details-link-speed = Скорость канала
+details-mtu = MTU
+details-uptime = Подключён
+details-unavailable = Нет сведений об интерфейсе
+media-ethernet = Ethernet
+media-wifi = Wi-Fi
+media-mobile = Мобильная сеть
+media-ppp = PPP
+media-tunnel = Туннель
+media-other = Другое
+export-title = Сетевой трафик
+
+tui-waiting = Ожидание первого замера…
+tui-keys = m вид · i/Tab интерфейс · d подробности · u байты/биты · p приставки · q выход
+tui-interfaces-auto = Интерфейсы · самый активный
+tui-interfaces-fixed = Интерфейсы · показан { $name }
+tui-interfaces-down = Интерфейсы · { $name } отключён, показан самый активный
+tui-column-interface = Интерфейс
+tui-column-down = Загрузка
+tui-column-up = Отдача
+tui-column-received = Получено
+tui-column-sent = Отправлено
+tui-stat-now = Сейчас
+tui-stat-average = Среднее ({ $seconds } с)
+tui-stat-peak = Пик ({ $seconds } с)
+tui-stat-session = Сеанс ({ $duration })
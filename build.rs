@@ -1,6 +1,14 @@
 extern crate winres;
 
 fn main() {
+	// The tray app is Windows-only. Elsewhere the `gui` feature changes
+	// nothing, so default builds there are the command-line tool.
+	println!("cargo::rustc-check-cfg=cfg(tray)");
+	let windows = std::env::var("CARGO_CFG_TARGET_OS").is_ok_and(|os| os == "windows");
+	if windows && std::env::var_os("CARGO_FEATURE_GUI").is_some() {
+		println!("cargo::rustc-cfg=tray");
+	}
+
 	if cfg!(target_os = "windows") {
 		let mut res = winres::WindowsResource::new();
 		res.set_icon("assets/icon.ico");
@@ -1,43 +1,240 @@
-use winit::event_loop::EventLoopWindowTarget;
-use tray_icon::{ TrayIcon, TrayIconBuilder, menu::{ Menu, MenuItem, Submenu, CheckMenuItem } };
+use std::time::{ Duration, Instant };
+use winit::dpi::{ PhysicalPosition, PhysicalSize };
+use winit::event::{ ElementState, MouseButton, WindowEvent };
+use winit::event_loop::EventLoop;
+use winit::window::WindowId;
+use fluent_bundle::FluentArgs;
+use tray_icon::{ TrayIcon, TrayIconBuilder, menu::{ Menu, MenuItem, Submenu, CheckMenuItem, PredefinedMenuItem } };
 
+use crate::cli::Activation;
 use crate::core::monitor::NetMonitor;
-use crate::core::state::{ AppState, ViewMode };
+use crate::core::settings::Settings;
+use crate::core::state::{ AppState, GraphScale, IconStyle, ViewMode };
+use crate::sys::{ autostart, dialog, netinfo };
+use crate::ui::export::{ render, ExportFormat, ExportOptions };
+use crate::ui::mini_widget::MiniWidget;
+use crate::ui::panel::Hit;
+use crate::ui::placement::ScreenRect;
 use crate::ui::popup::Popup;
-use crate::ui::tray::TrayIconGenerator;
-use crate::utils::format::format_speed_full;
+use crate::ui::theme::{ self, Theme };
+use crate::ui::tray::{ IconKey, TrayIconGenerator };
+use crate::utils::cache::RenderCache;
+use crate::i18n::{ self, tr, tr_args };
+use crate::metrics;
+use crate::UserEvent;
+use crate::utils::format::{
+	format_bytes,
+	format_duration_minutes,
+	format_speed_full,
+	DataUnit,
+	UnitPrefix,
+	UnitSystem,
+};
 
 pub struct App {
 	monitor: NetMonitor,
+	/// Serves every tick's readings when `metrics_listen` is set.
+	metrics: Option<metrics::Server>,
 	state: AppState,
+	settings: Settings,
 	popup: Popup,
+	widget: MiniWidget,
+	/// When settings changed by dragging or resizing are due to be written.
+	save_due: Option<Instant>,
+	/// `settings.toml` failed to load, so the defaults in use are not saved
+	/// over the user's file.
+	settings_read_only: bool,
 	tray_icon: Option<TrayIcon>,
+	icon_size: u32,
+	/// Display scale the icon size falls back to where the platform does
+	/// not report one.
+	scale: f64,
+	icon_cache: RenderCache<IconKey, tray_icon::Icon>,
+	/// What the tray shows right now, to skip redundant shell updates.
+	shown_icon: Option<IconKey>,
+	shown_tooltip: String,
+	/// When the interface details were last looked up, and for which name.
+	details_fetched: Option<(Instant, String)>,
 
 	menu_quit: MenuItem,
+	menu_autostart: CheckMenuItem,
 	menu_mode_all: CheckMenuItem,
 	menu_mode_down: CheckMenuItem,
 	menu_mode_up: CheckMenuItem,
+	menu_mode_overlay: CheckMenuItem,
+	menu_units_bytes: CheckMenuItem,
+	menu_units_bits: CheckMenuItem,
+	menu_units_decimal: CheckMenuItem,
+	menu_units_binary: CheckMenuItem,
+	menu_icon_styles: Vec<(IconStyle, CheckMenuItem)>,
+	menu_graph_log: CheckMenuItem,
+	menu_save_graph: MenuItem,
+	menu_widget: CheckMenuItem,
+	/// Theme setting names (`system`, presets, user files) and their items.
+	menu_themes: Vec<(String, CheckMenuItem)>,
 }
 
+/// How often open interface details are looked up again, to pick up DHCP
+/// renewals and the like.
+const DETAILS_REFRESH: Duration = Duration::from_secs(10);
+
+/// Dragging or resizing a window reports every step; settings are written
+/// once it has rested this long.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Distinct icons kept around; a minute of varied traffic fits comfortably.
+const ICON_CACHE_SIZE: usize = 64;
+
+const ICON_STYLES: [(IconStyle, &str); 5] = [
+	(IconStyle::Number, "menu-icon-number"),
+	(IconStyle::Sparkline, "menu-icon-sparkline"),
+	(IconStyle::Bars, "menu-icon-bars"),
+	(IconStyle::Split, "menu-icon-split"),
+	(IconStyle::Dot, "menu-icon-dot"),
+];
+
+const THEMES: [(&str, &str); 5] = [
+	(theme::SYSTEM, "menu-theme-system"),
+	("dark", "menu-theme-dark"),
+	("light", "menu-theme-light"),
+	("high-contrast", "menu-theme-high-contrast"),
+	("solarized", "menu-theme-solarized"),
+];
+
 impl App {
-	pub fn new<T>(event_loop: &EventLoopWindowTarget<T>) -> Self {
+	pub fn new(event_loop: &EventLoop<UserEvent>) -> Self {
+		let (settings, load_error) = match Settings::load() {
+			Ok(settings) => (settings, None),
+			Err(e) => (Settings::default(), Some(e)),
+		};
+		i18n::init(settings.language.as_deref());
+		let settings_read_only = load_error.is_some();
+		if let Some(e) = load_error {
+			report("error-settings-load", e);
+		}
+		let metrics = settings.metrics_listen.as_deref().and_then(|listen| {
+			metrics::Server::start(listen).map_err(|e| report("error-metrics", e)).ok()
+		});
+		let units = settings.units;
+		let numbers = i18n::numbers();
+
 		let tray_menu = Menu::new();
 
-		let mode_menu = Submenu::new("View Mode", true);
-		let menu_mode_all = CheckMenuItem::new("All", true, true, None);
-		let menu_mode_down = CheckMenuItem::new("Download Only", true, false, None);
-		let menu_mode_up = CheckMenuItem::new("Upload Only", true, false, None);
+		let mode_menu = Submenu::new(tr("menu-view-mode"), true);
+		let menu_mode_all = CheckMenuItem::new(tr("menu-mode-all"), true, true, None);
+		let menu_mode_down = CheckMenuItem::new(tr("menu-mode-download"), true, false, None);
+		let menu_mode_up = CheckMenuItem::new(tr("menu-mode-upload"), true, false, None);
+		let menu_mode_overlay = CheckMenuItem::new(tr("menu-mode-overlay"), true, false, None);
 
 		mode_menu.append(&menu_mode_all).unwrap();
 		mode_menu.append(&menu_mode_down).unwrap();
 		mode_menu.append(&menu_mode_up).unwrap();
+		mode_menu.append(&menu_mode_overlay).unwrap();
 
 		tray_menu.append(&mode_menu).unwrap();
 
-		let menu_quit = MenuItem::new("Exit", true, None);
+		let units_menu = Submenu::new(tr("menu-units"), true);
+		let menu_units_bytes = CheckMenuItem::new(
+			tr("menu-units-bytes"),
+			true,
+			units.unit == DataUnit::Bytes,
+			None
+		);
+		let menu_units_bits = CheckMenuItem::new(
+			tr("menu-units-bits"),
+			true,
+			units.unit == DataUnit::Bits,
+			None
+		);
+		let menu_units_decimal = CheckMenuItem::new(
+			tr("menu-units-decimal"),
+			true,
+			units.prefix == UnitPrefix::Decimal,
+			None
+		);
+		let menu_units_binary = CheckMenuItem::new(
+			tr("menu-units-binary"),
+			true,
+			units.prefix == UnitPrefix::Binary,
+			None
+		);
+
+		units_menu.append(&menu_units_bytes).unwrap();
+		units_menu.append(&menu_units_bits).unwrap();
+		units_menu.append(&PredefinedMenuItem::separator()).unwrap();
+		units_menu.append(&menu_units_decimal).unwrap();
+		units_menu.append(&menu_units_binary).unwrap();
+
+		tray_menu.append(&units_menu).unwrap();
+
+		let icon_menu = Submenu::new(tr("menu-icon-style"), true);
+		let menu_icon_styles: Vec<(IconStyle, CheckMenuItem)> = ICON_STYLES.iter()
+			.map(|&(style, label)| {
+				let item = CheckMenuItem::new(tr(label), true, style == settings.icon_style, None);
+				icon_menu.append(&item).unwrap();
+				(style, item)
+			})
+			.collect();
+
+		tray_menu.append(&icon_menu).unwrap();
+
+		let theme_menu = Submenu::new(tr("menu-theme"), true);
+		let names = THEMES.iter()
+			.map(|&(name, label)| (name.to_string(), tr(label)))
+			.chain(theme::user_themes().into_iter().map(|name| (name.clone(), name)));
+		let mut menu_themes: Vec<(String, CheckMenuItem)> = Vec::new();
+		for (i, (name, label)) in names.enumerate() {
+			// User theme files go below the built-in ones.
+			if i == THEMES.len() {
+				theme_menu.append(&PredefinedMenuItem::separator()).unwrap();
+			}
+			let item = CheckMenuItem::new(label, true, name == settings.theme, None);
+			theme_menu.append(&item).unwrap();
+			menu_themes.push((name, item));
+		}
+		tray_menu.append(&theme_menu).unwrap();
+
+		let menu_graph_log = CheckMenuItem::new(
+			tr("menu-graph-log"),
+			true,
+			settings.graph_scale == GraphScale::Log,
+			None
+		);
+		tray_menu.append(&menu_graph_log).unwrap();
+
+		let menu_widget = CheckMenuItem::new(tr("menu-widget"), true, settings.widget, None);
+		tray_menu.append(&menu_widget).unwrap();
+
+		let menu_save_graph = MenuItem::new(tr("menu-save-graph"), true, None);
+		tray_menu.append(&menu_save_graph).unwrap();
+
+		let menu_autostart = CheckMenuItem::new(
+			tr("menu-autostart"),
+			true,
+			autostart::is_enabled(),
+			None
+		);
+		tray_menu.append(&menu_autostart).unwrap();
+
+		let menu_quit = MenuItem::new(tr("menu-exit"), true, None);
 		tray_menu.append(&menu_quit).unwrap();
 
-		let icon = TrayIconGenerator::generate(0).unwrap();
+		let mut popup = Popup::new(event_loop, settings.popup_size);
+		popup.set_pinned_position(settings.popup_position);
+		let widget = MiniWidget::new(event_loop, settings.widget_position);
+
+		let mut state = AppState::new();
+		state.set_units(units);
+		state.set_numbers(numbers);
+		state.set_icon_style(settings.icon_style);
+		state.set_graph_scale(settings.graph_scale);
+		state.set_details_expanded(settings.details_expanded);
+		state.set_pinned(settings.popup_pinned);
+		state.set_theme(resolve_theme(&settings.theme, popup.system_dark()));
+
+		let scale = event_loop.primary_monitor().map_or(1.0, |monitor| monitor.scale_factor());
+		let icon_size = TrayIconGenerator::icon_size(None, scale);
+		let icon = TrayIconGenerator::generate(&state, icon_size).unwrap();
 		let tray_icon = Some(
 			TrayIconBuilder::new()
 				.with_menu(Box::new(tray_menu))
@@ -47,67 +244,494 @@ impl App {
 				.unwrap()
 		);
 
-		Self {
+		let mut app = Self {
 			monitor: NetMonitor::new(),
-			state: AppState::new(),
-			popup: Popup::new(event_loop),
+			metrics,
+			state,
+			settings,
+			popup,
+			widget,
+			save_due: None,
+			settings_read_only,
 			tray_icon,
+			icon_size,
+			scale,
+			icon_cache: RenderCache::new(ICON_CACHE_SIZE),
+			shown_icon: None,
+			shown_tooltip: String::new(),
+			details_fetched: None,
 			menu_quit,
+			menu_autostart,
 			menu_mode_all,
 			menu_mode_down,
 			menu_mode_up,
+			menu_mode_overlay,
+			menu_units_bytes,
+			menu_units_bits,
+			menu_units_decimal,
+			menu_units_binary,
+			menu_icon_styles,
+			menu_graph_log,
+			menu_save_graph,
+			menu_widget,
+			menu_themes,
+		};
+		// The tray may sit on another monitor than the primary one.
+		app.refresh_icon_size();
+		// A pinned popup and the widget stay on screen across restarts.
+		app.anchor_popup();
+		if app.settings.widget {
+			app.widget.show(app.popup.anchor());
 		}
+		if app.state.pinned {
+			app.popup.show(&app.state);
+		}
+		app
 	}
 
 	pub fn tick(&mut self) {
-		if let Some(stats) = self.monitor.tick() {
-			self.state.update(stats.down_bps, stats.up_bps, stats.interface_name);
-
-			if let Ok(new_icon) = TrayIconGenerator::generate(self.state.down_bps) {
-				if let Some(tray) = &mut self.tray_icon {
-					let _ = tray.set_icon(Some(new_icon));
-					let tooltip = format!(
-						"Down: {} | Up: {}",
-						format_speed_full(self.state.down_bps),
-						format_speed_full(self.state.up_bps)
-					);
-					let _ = tray.set_tooltip(Some(tooltip));
+		let stats = self.monitor.tick();
+		if let Some(metrics) = &self.metrics {
+			metrics.update(&self.monitor);
+		}
+		if let Some(stats) = stats {
+			self.state.update(stats);
+			self.refresh_icon();
+			self.refresh_details(false);
+			self.popup.update(&self.state);
+			self.widget.update();
+		}
+		if self.save_due.is_some_and(|due| due <= Instant::now()) {
+			self.save_settings();
+		}
+	}
+
+	/// Updates the tray icon and tooltip from the current state, touching the
+	/// shell only for parts that visibly changed.
+	fn refresh_icon(&mut self) {
+		let Some(tray) = &self.tray_icon else {
+			return;
+		};
+
+		let key = TrayIconGenerator::key(&self.state, self.icon_size);
+		if self.shown_icon.as_ref() != Some(&key) {
+			let (state, size) = (&self.state, self.icon_size);
+			let icon = self.icon_cache.get_or_render(key.clone(), || {
+				TrayIconGenerator::generate(state, size)
+			});
+			if let Ok(icon) = icon {
+				if tray.set_icon(Some(icon.clone())).is_ok() {
+					self.shown_icon = Some(key);
 				}
 			}
+			#[cfg(debug_assertions)]
+			self.report_cache();
+		}
 
-			self.popup.update(&self.state);
+		let (units, numbers) = (self.state.units, self.state.numbers);
+		let mut args = FluentArgs::new();
+		args.set("down", format_speed_full(self.state.down_bps, units, numbers));
+		args.set("up", format_speed_full(self.state.up_bps, units, numbers));
+		let speeds = tr_args("tooltip-speeds", &args);
+
+		let mut args = FluentArgs::new();
+		args.set("down", format_bytes(self.state.total_down, units, numbers));
+		args.set("up", format_bytes(self.state.total_up, units, numbers));
+		// Minutes only, or the tooltip would change every tick.
+		let duration = format_duration_minutes(self.state.session_duration(), i18n::current());
+		args.set("duration", duration);
+		let tooltip = format!("{}\n{}", speeds, tr_args("tooltip-session", &args));
+		if tooltip != self.shown_tooltip && tray.set_tooltip(Some(&tooltip)).is_ok() {
+			self.shown_tooltip = tooltip;
+		}
+	}
+
+	/// Looks up the interface details while they are on screen, when the
+	/// interface changed or the last lookup is stale, or always if `force`.
+	fn refresh_details(&mut self, force: bool) {
+		if !self.state.details_expanded || !self.popup.is_visible() {
+			return;
+		}
+		let name = &self.state.interface_name;
+		let fresh = self.details_fetched.as_ref().is_some_and(|(at, fetched)| {
+			fetched == name && at.elapsed() < DETAILS_REFRESH
+		});
+		if fresh && !force {
+			return;
+		}
+		let mut details = netinfo::interface_details(name);
+		if let Some(details) = &mut details {
+			details.uptime = details.uptime.or_else(|| self.monitor.uptime(name));
+		}
+		self.details_fetched = Some((Instant::now(), name.clone()));
+		self.state.set_details(details);
+	}
+
+	#[cfg(debug_assertions)]
+	fn report_cache(&self) {
+		let cache = &self.icon_cache;
+		let lookups = cache.hits() + cache.misses();
+		if lookups.is_multiple_of(60) {
+			eprintln!(
+				"icon cache: {:.0}% hits over {} lookups, {} icons cached",
+				cache.hit_rate() * 100.0,
+				lookups,
+				cache.len()
+			);
 		}
 	}
 
+	pub fn set_scale_factor(&mut self, scale: f64) {
+		self.scale = scale;
+		self.refresh_icon_size();
+	}
+
+	/// Calls `on_change` when the displays change in a way that can resize
+	/// the tray icon; it should lead to `refresh_icon_size`.
+	pub fn watch_displays(&self, on_change: impl Fn() + 'static) {
+		self.popup.watch_displays(on_change);
+	}
+
+	/// Re-renders the icon at the size the tray now wants, e.g. after the
+	/// user changed scaling or moved the taskbar to another monitor.
+	pub fn refresh_icon_size(&mut self) {
+		let size = TrayIconGenerator::icon_size(self.tray_center(), self.scale);
+		if size != self.icon_size {
+			self.icon_size = size;
+			self.refresh_icon();
+		}
+	}
+
+	fn tray_center(&self) -> Option<(i32, i32)> {
+		let rect = self.tray_icon.as_ref()?.rect()?;
+		let x = rect.position.x + (rect.size.width as f64) / 2.0;
+		let y = rect.position.y + (rect.size.height as f64) / 2.0;
+		Some((x.round() as i32, y.round() as i32))
+	}
+
 	pub fn toggle_popup(&mut self) {
+		self.anchor_popup();
 		self.popup.toggle(&self.state);
+		self.refresh_details(true);
+		self.popup.update(&self.state);
+	}
+
+	pub fn activate(&mut self, activations: &[Activation]) {
+		for activation in activations {
+			match activation {
+				Activation::ShowPopup => {
+					self.anchor_popup();
+					self.popup.show(&self.state);
+					self.refresh_details(true);
+					self.popup.update(&self.state);
+				}
+				Activation::SetViewMode(mode) => self.set_view_mode(*mode),
+			}
+		}
+	}
+
+	/// Points the popup at the tray icon, which may have moved to another
+	/// monitor or taskbar edge since it last opened.
+	fn anchor_popup(&mut self) {
+		let anchor = self.tray_icon
+			.as_ref()
+			.and_then(|tray| tray.rect())
+			.map(|rect| {
+				ScreenRect::new(
+					rect.position.x.round() as i32,
+					rect.position.y.round() as i32,
+					rect.size.width as i32,
+					rect.size.height as i32
+				)
+			});
+		self.popup.set_anchor(anchor);
+	}
+
+	pub fn resize_popup(&mut self, size: PhysicalSize<u32>) {
+		if let Some(size) = self.popup.resized(&self.state, size) {
+			self.settings.popup_size = size;
+			self.save_settings_later();
+		}
+	}
+
+	pub fn hover_popup(&mut self, position: Option<PhysicalPosition<f64>>) {
+		self.popup.set_hover(position.map(|p| (p.x as f32, p.y as f32)));
+	}
+
+	pub fn click_popup(&mut self) {
+		match self.popup.hit(&self.state) {
+			Some(Hit::DetailsToggle) => {
+				let expanded = !self.state.details_expanded;
+				self.state.set_details_expanded(expanded);
+				self.settings.details_expanded = expanded;
+				self.save_settings();
+				self.refresh_details(true);
+				self.popup.update(&self.state);
+			}
+			Some(Hit::Pin) => self.toggle_pin(),
+			None => self.popup.drag(&self.state),
+		}
+	}
+
+	fn toggle_pin(&mut self) {
+		let pinned = !self.state.pinned;
+		self.state.set_pinned(pinned);
+		self.settings.popup_pinned = pinned;
+		if pinned {
+			self.settings.popup_position = self.popup.pin_here();
+		}
+		self.save_settings();
+		self.popup.update(&self.state);
+	}
+
+	pub fn popup_focus_lost(&mut self) {
+		self.popup.focus_lost(&self.state);
+	}
+
+	pub fn move_popup(&mut self, position: PhysicalPosition<i32>) {
+		if let Some(position) = self.popup.moved(&self.state, position) {
+			self.settings.popup_position = Some(position);
+			self.save_settings_later();
+		}
 	}
 
 	pub fn redraw_popup(&self) {
 		self.popup.draw(&self.state);
 	}
 
+	pub fn is_widget(&self, window: WindowId) -> bool {
+		window == self.widget.id()
+	}
+
+	/// Handles an event on the mini widget's window, which can only be
+	/// dragged around.
+	pub fn widget_event(&mut self, event: WindowEvent) {
+		match event {
+			WindowEvent::RedrawRequested => self.widget.draw(&self.state),
+			WindowEvent::MouseInput {
+				state: ElementState::Pressed,
+				button: MouseButton::Left,
+				..
+			} => {
+				self.widget.drag();
+			}
+			WindowEvent::Moved(position) => {
+				if let Some(position) = self.widget.moved(position) {
+					self.settings.widget_position = Some(position);
+					self.save_settings_later();
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn set_widget(&mut self, widget: bool) {
+		self.settings.widget = widget;
+		self.save_settings();
+		self.menu_widget.set_checked(widget);
+		if widget {
+			self.anchor_popup();
+			self.widget.show(self.popup.anchor());
+		} else {
+			self.widget.hide();
+		}
+	}
+
 	pub fn handle_menu_event(&mut self, event_id: &str) -> bool {
 		if event_id == self.menu_quit.id().0.as_str() {
+			if self.save_due.is_some() {
+				self.save_settings();
+			}
 			self.tray_icon = None;
 			return true;
+		} else if event_id == self.menu_autostart.id().0.as_str() {
+			self.toggle_autostart();
 		} else if event_id == self.menu_mode_all.id().0.as_str() {
 			self.set_view_mode(ViewMode::All);
 		} else if event_id == self.menu_mode_down.id().0.as_str() {
 			self.set_view_mode(ViewMode::DownloadOnly);
 		} else if event_id == self.menu_mode_up.id().0.as_str() {
 			self.set_view_mode(ViewMode::UploadOnly);
+		} else if event_id == self.menu_mode_overlay.id().0.as_str() {
+			self.set_view_mode(ViewMode::Overlay);
+		} else if event_id == self.menu_graph_log.id().0.as_str() {
+			let log = self.menu_graph_log.is_checked();
+			self.set_graph_scale(if log { GraphScale::Log } else { GraphScale::Linear });
+		} else if event_id == self.menu_widget.id().0.as_str() {
+			self.set_widget(self.menu_widget.is_checked());
+		} else if event_id == self.menu_save_graph.id().0.as_str() {
+			self.save_graph();
+		} else if event_id == self.menu_units_bytes.id().0.as_str() {
+			self.set_units(UnitSystem::new(DataUnit::Bytes, self.state.units.prefix));
+		} else if event_id == self.menu_units_bits.id().0.as_str() {
+			self.set_units(UnitSystem::new(DataUnit::Bits, self.state.units.prefix));
+		} else if event_id == self.menu_units_decimal.id().0.as_str() {
+			self.set_units(UnitSystem::new(self.state.units.unit, UnitPrefix::Decimal));
+		} else if event_id == self.menu_units_binary.id().0.as_str() {
+			self.set_units(UnitSystem::new(self.state.units.unit, UnitPrefix::Binary));
+		} else if
+			let Some(&(style, _)) = self.menu_icon_styles
+				.iter()
+				.find(|(_, item)| event_id == item.id().0.as_str())
+		{
+			self.set_icon_style(style);
+		} else if
+			let Some((name, _)) = self.menu_themes
+				.iter()
+				.find(|(_, item)| event_id == item.id().0.as_str())
+		{
+			self.set_theme(name.clone());
 		}
 		false
 	}
 
+	fn save_graph(&self) {
+		let name = format!("netflux-graph.{}", ExportFormat::Png.extension());
+		let Some(path) = dialog::save_graph_file(&tr("menu-save-graph"), &name) else {
+			return;
+		};
+		if let Err(e) = render::export(&self.state, &ExportOptions::new(path)) {
+			report("error-export", e);
+		}
+	}
+
+	/// Writes the graph for a second launch's `export-graph`.
+	pub fn export_graph(&self, options: &ExportOptions) -> Result<(), String> {
+		render::export(&self.state, options)
+	}
+
+	fn toggle_autostart(&mut self) {
+		let enabled = autostart::is_enabled();
+		let result = if enabled { autostart::disable() } else { autostart::enable() };
+		// The menu item flips its own check mark on click; put it back
+		// unless the change went through.
+		match result {
+			Ok(()) => self.menu_autostart.set_checked(!enabled),
+			Err(e) => {
+				self.menu_autostart.set_checked(enabled);
+				report("error-autostart", e);
+			}
+		}
+	}
+
+	fn save_settings(&mut self) {
+		self.save_due = None;
+		if self.settings_read_only {
+			return;
+		}
+		if let Err(e) = self.settings.save() {
+			report("error-settings", e);
+		}
+	}
+
+	/// Saves the settings once the window being dragged or resized is left
+	/// alone for `SAVE_DELAY`, checked on every tick.
+	fn save_settings_later(&mut self) {
+		self.save_due = Some(Instant::now() + SAVE_DELAY);
+	}
+
 	fn set_view_mode(&mut self, mode: ViewMode) {
 		self.state.set_view_mode(mode);
 
-		let _ = self.menu_mode_all.set_checked(mode == ViewMode::All);
-		let _ = self.menu_mode_down.set_checked(mode == ViewMode::DownloadOnly);
-		let _ = self.menu_mode_up.set_checked(mode == ViewMode::UploadOnly);
+		self.menu_mode_all.set_checked(mode == ViewMode::All);
+		self.menu_mode_down.set_checked(mode == ViewMode::DownloadOnly);
+		self.menu_mode_up.set_checked(mode == ViewMode::UploadOnly);
+		self.menu_mode_overlay.set_checked(mode == ViewMode::Overlay);
 
 		self.popup.update(&self.state);
 	}
+
+	fn set_units(&mut self, units: UnitSystem) {
+		self.state.set_units(units);
+
+		self.menu_units_bytes.set_checked(units.unit == DataUnit::Bytes);
+		self.menu_units_bits.set_checked(units.unit == DataUnit::Bits);
+		self.menu_units_decimal.set_checked(units.prefix == UnitPrefix::Decimal);
+		self.menu_units_binary.set_checked(units.prefix == UnitPrefix::Binary);
+
+		self.settings.units = units;
+		self.save_settings();
+
+		self.popup.update(&self.state);
+		self.widget.update();
+	}
+
+	fn set_icon_style(&mut self, style: IconStyle) {
+		self.state.set_icon_style(style);
+
+		for (item_style, item) in &self.menu_icon_styles {
+			item.set_checked(*item_style == style);
+		}
+
+		self.settings.icon_style = style;
+		self.save_settings();
+
+		self.refresh_icon();
+	}
+
+	fn set_graph_scale(&mut self, scale: GraphScale) {
+		self.state.set_graph_scale(scale);
+		self.menu_graph_log.set_checked(scale == GraphScale::Log);
+
+		self.settings.graph_scale = scale;
+		self.save_settings();
+
+		self.popup.update(&self.state);
+	}
+
+	fn set_theme(&mut self, name: String) {
+		for (item_name, item) in &self.menu_themes {
+			item.set_checked(*item_name == name);
+		}
+
+		self.settings.theme = name;
+		self.save_settings();
+
+		self.apply_theme();
+	}
+
+	/// Called when the OS switches between light and dark mode.
+	pub fn set_system_dark(&mut self, dark: bool) {
+		if self.settings.theme == theme::SYSTEM {
+			self.apply_theme_from(Some(dark));
+		}
+	}
+
+	fn apply_theme(&mut self) {
+		self.apply_theme_from(self.popup.system_dark());
+	}
+
+	fn apply_theme_from(&mut self, system_dark: Option<bool>) {
+		let theme = resolve_theme(&self.settings.theme, system_dark);
+		if theme == self.state.theme {
+			return;
+		}
+		self.state.set_theme(theme);
+
+		// Cached icons were drawn in the old colours.
+		self.icon_cache.clear();
+		self.shown_icon = None;
+		self.refresh_icon();
+		self.popup.update(&self.state);
+		self.widget.update();
+	}
+}
+
+/// Shows an error to the user, who would never see it on stderr, as the
+/// message `id` with the error text as its `$error`.
+fn report(id: &str, error: String) {
+	let mut args = FluentArgs::new();
+	args.set("error", error);
+	let message = tr_args(id, &args);
+	eprintln!("netflux: {}", message);
+	dialog::show_error(&message);
+}
+
+/// The theme setting `name` resolves to, or the default theme once the user
+/// has been told why it did not load.
+fn resolve_theme(name: &str, system_dark: Option<bool>) -> Theme {
+	Theme::resolve(name, system_dark).unwrap_or_else(|e| {
+		report("error-theme", e);
+		Theme::default()
+	})
 }
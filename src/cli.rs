@@ -0,0 +1,539 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::state::ViewMode;
+use crate::metrics;
+use crate::ui::export::{ ExportFormat, ExportOptions };
+use crate::ui::theme;
+use crate::watch::{ WatchFormat, WatchOptions };
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum AutostartAction {
+	Enable,
+	#[cfg(target_os = "linux")]
+	EnableSystemd,
+	Disable,
+	Status,
+}
+
+/// Requests that are applied to the tray app, either at startup or when a
+/// second launch forwards them to the running instance.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Activation {
+	ShowPopup,
+	SetViewMode(ViewMode),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Command {
+	Run(Vec<Activation>),
+	Autostart(AutostartAction),
+	ExportGraph(ExportOptions),
+	/// The terminal frontend, optionally starting on one interface.
+	Tui(Option<String>),
+	Watch(WatchOptions),
+	/// Serve Prometheus metrics on this address.
+	Metrics(String),
+	Help,
+}
+
+pub const USAGE: &str =
+	"Usage: netflux [OPTIONS]
+       netflux export-graph [EXPORT OPTIONS]
+       netflux tui [--interface <NAME>]
+       netflux watch [WATCH OPTIONS]
+       netflux metrics [--listen <ADDRESS>]
+
+Options:
+  --show                       Open the popup (or the running instance's popup)
+  --mode <MODE>                Switch the popup view mode: all, download,
+                               upload or overlay
+  --autostart <on|off|status>  Enable, disable or query launching on login
+                               (on Linux, 'systemd' installs a user unit instead)
+  -h, --help                   Print this help
+
+Export options (saves the running instance's graph history):
+  -o, --output <FILE>          File to write (default: netflux-graph.png)
+  --format <png|svg>           Image format (default: from the file name)
+  --size <WIDTHxHEIGHT>        Image size in pixels (default: 800x400)
+  --theme <THEME>              Theme to draw with (default: the popup's)
+  --title <TEXT>               Title above the graphs
+
+Terminal UI options:
+  -i, --interface <NAME>       Show this interface instead of the busiest
+
+Watch options (prints a line per interval to stdout):
+  --format <FORMAT>            human (default), tsv or json, with tsv and json
+                               in bytes per second; or waybar, i3bar or
+                               polybar for status bars
+  -n, --count <N>              Exit after N lines (default: run until stopped)
+  --interval <SECONDS>         Time between lines (default: 1)
+  -i, --interface <NAME>       Follow this interface instead of the busiest
+  --plain                      No colours (default when not on a terminal)
+
+Metrics options (serves Prometheus metrics at /metrics):
+  --listen <ADDRESS>           Address and port to listen on
+                               (default: 127.0.0.1:9184)";
+
+const DEFAULT_EXPORT_NAME: &str = "netflux-graph";
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+	let mut args = args.into_iter();
+	let mut command = Command::Run(Vec::new());
+
+	while let Some(arg) = args.next() {
+		let (flag, inline_value) = match arg.split_once('=') {
+			Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+			None => (arg, None),
+		};
+
+		match flag.as_str() {
+			"-h" | "--help" => {
+				return Ok(Command::Help);
+			}
+			"export-graph" => {
+				if command != Command::Run(Vec::new()) {
+					return Err("export-graph must come before its options".to_string());
+				}
+				return parse_export(args).map(Command::ExportGraph);
+			}
+			"tui" => {
+				if command != Command::Run(Vec::new()) {
+					return Err("tui must come before its options".to_string());
+				}
+				return parse_tui(args).map(Command::Tui);
+			}
+			"watch" => {
+				if command != Command::Run(Vec::new()) {
+					return Err("watch must come before its options".to_string());
+				}
+				return parse_watch(args).map(Command::Watch);
+			}
+			"metrics" => {
+				if command != Command::Run(Vec::new()) {
+					return Err("metrics must come before its options".to_string());
+				}
+				return parse_metrics(args).map(Command::Metrics);
+			}
+			"--show" => {
+				push_activation(&mut command, Activation::ShowPopup)?;
+			}
+			"--mode" => {
+				let value = inline_value
+					.or_else(|| args.next())
+					.ok_or("--mode requires a value: all, download, upload or overlay")?;
+				push_activation(&mut command, Activation::SetViewMode(parse_view_mode(&value)?))?;
+			}
+			"--autostart" => {
+				let value = inline_value
+					.or_else(|| args.next())
+					.ok_or("--autostart requires a value: on, off or status")?;
+				if command != Command::Run(Vec::new()) {
+					return Err("--autostart cannot be combined with other options".to_string());
+				}
+				command = Command::Autostart(parse_autostart(&value)?);
+			}
+			_ => {
+				return Err(format!("Unknown argument '{}'", flag));
+			}
+		}
+	}
+
+	Ok(command)
+}
+
+fn parse_export<I: Iterator<Item = String>>(mut args: I) -> Result<ExportOptions, String> {
+	let mut path = None;
+	let mut format = None;
+	let mut size = None;
+	let mut theme = None;
+	let mut title = None;
+
+	while let Some(arg) = args.next() {
+		let (flag, inline_value) = match arg.split_once('=') {
+			Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+			None => (arg, None),
+		};
+		let mut value = || {
+			inline_value
+				.clone()
+				.or_else(|| args.next())
+				.ok_or(format!("{} requires a value", flag))
+		};
+		match flag.as_str() {
+			"-o" | "--output" => {
+				path = Some(PathBuf::from(value()?));
+			}
+			"--format" => {
+				format = Some(parse_export_format(&value()?)?);
+			}
+			"--size" => {
+				size = Some(parse_size(&value()?)?);
+			}
+			"--theme" => {
+				theme = Some(parse_theme(&value()?)?);
+			}
+			"--title" => {
+				title = Some(value()?);
+			}
+			_ => {
+				return Err(format!("Unknown export-graph argument '{}'", flag));
+			}
+		}
+	}
+
+	let path = path.unwrap_or_else(|| {
+		let extension = format.unwrap_or(ExportFormat::Png).extension();
+		PathBuf::from(format!("{}.{}", DEFAULT_EXPORT_NAME, extension))
+	});
+	let mut options = ExportOptions::new(path);
+	if let Some(format) = format {
+		options.format = format;
+	}
+	if let Some((width, height)) = size {
+		options.width = width;
+		options.height = height;
+	}
+	options.theme = theme;
+	options.title = title;
+	Ok(options)
+}
+
+fn parse_tui<I: Iterator<Item = String>>(mut args: I) -> Result<Option<String>, String> {
+	let mut interface = None;
+	while let Some(arg) = args.next() {
+		let (flag, inline_value) = match arg.split_once('=') {
+			Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+			None => (arg, None),
+		};
+		match flag.as_str() {
+			"-i" | "--interface" => {
+				let value = inline_value
+					.or_else(|| args.next())
+					.ok_or(format!("{} requires an interface name", flag))?;
+				interface = Some(value);
+			}
+			_ => {
+				return Err(format!("Unknown tui argument '{}'", flag));
+			}
+		}
+	}
+	Ok(interface)
+}
+
+fn parse_watch<I: Iterator<Item = String>>(mut args: I) -> Result<WatchOptions, String> {
+	let mut options = WatchOptions::default();
+	while let Some(arg) = args.next() {
+		let (flag, inline_value) = match arg.split_once('=') {
+			Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+			None => (arg, None),
+		};
+		let mut value = || {
+			inline_value
+				.clone()
+				.or_else(|| args.next())
+				.ok_or(format!("{} requires a value", flag))
+		};
+		match flag.as_str() {
+			"--format" => {
+				options.format = parse_watch_format(&value()?)?;
+			}
+			"-n" | "--count" => {
+				let value = value()?;
+				let count = value.parse::<u64>().ok().filter(|&count| count > 0);
+				let count = count.ok_or_else(|| {
+					format!("Invalid --count value '{}': expected a positive number", value)
+				})?;
+				options.count = Some(count);
+			}
+			"--interval" => {
+				let value = value()?;
+				let seconds = value
+					.parse::<f64>()
+					.ok()
+					.filter(|&seconds| (0.1..=3600.0).contains(&seconds));
+				let seconds = seconds.ok_or_else(|| {
+					format!("Invalid --interval value '{}': expected 0.1 to 3600 seconds", value)
+				})?;
+				options.interval = Duration::from_secs_f64(seconds);
+			}
+			"-i" | "--interface" => {
+				options.interface = Some(value()?);
+			}
+			"--plain" => {
+				options.plain = true;
+			}
+			_ => {
+				return Err(format!("Unknown watch argument '{}'", flag));
+			}
+		}
+	}
+	Ok(options)
+}
+
+fn parse_metrics<I: Iterator<Item = String>>(mut args: I) -> Result<String, String> {
+	let mut listen = metrics::DEFAULT_LISTEN.to_string();
+	while let Some(arg) = args.next() {
+		let (flag, inline_value) = match arg.split_once('=') {
+			Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+			None => (arg, None),
+		};
+		match flag.as_str() {
+			"--listen" => {
+				listen = inline_value
+					.or_else(|| args.next())
+					.ok_or("--listen requires an address such as 127.0.0.1:9184")?;
+			}
+			_ => {
+				return Err(format!("Unknown metrics argument '{}'", flag));
+			}
+		}
+	}
+	Ok(listen)
+}
+
+fn parse_watch_format(value: &str) -> Result<WatchFormat, String> {
+	match value.to_ascii_lowercase().as_str() {
+		"human" => Ok(WatchFormat::Human),
+		"tsv" => Ok(WatchFormat::Tsv),
+		"json" | "jsonl" => Ok(WatchFormat::Json),
+		"waybar" => Ok(WatchFormat::Waybar),
+		"i3bar" | "i3status" => Ok(WatchFormat::I3bar),
+		"polybar" => Ok(WatchFormat::Polybar),
+		_ => {
+			let expected = "human, tsv, json, waybar, i3bar or polybar";
+			Err(format!("Invalid --format value '{}': expected {}", value, expected))
+		}
+	}
+}
+
+/// The arguments that `parse` turns back into `options`, for handing an
+/// export over to the running instance.
+pub fn export_args(options: &ExportOptions) -> Vec<String> {
+	let mut args = vec![
+		"export-graph".to_string(),
+		"--output".to_string(),
+		options.path.to_string_lossy().into_owned(),
+		"--format".to_string(),
+		options.format.extension().to_string(),
+		"--size".to_string(),
+		format!("{}x{}", options.width, options.height),
+	];
+	if let Some(theme) = &options.theme {
+		args.extend(["--theme".to_string(), theme.clone()]);
+	}
+	if let Some(title) = &options.title {
+		args.extend(["--title".to_string(), title.clone()]);
+	}
+	args
+}
+
+fn parse_export_format(value: &str) -> Result<ExportFormat, String> {
+	match value.to_ascii_lowercase().as_str() {
+		"png" => Ok(ExportFormat::Png),
+		"svg" => Ok(ExportFormat::Svg),
+		_ => Err(format!("Invalid --format value '{}': expected png or svg", value)),
+	}
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+	let invalid = || format!("Invalid --size value '{}': expected e.g. 800x400", value);
+	let (width, height) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+	let width = width.trim().parse::<u32>().map_err(|_| invalid())?;
+	let height = height.trim().parse::<u32>().map_err(|_| invalid())?;
+	if width > 8192 || height > 8192 {
+		return Err(format!("--size {} is too large: at most 8192x8192", value));
+	}
+	Ok((width, height))
+}
+
+fn parse_theme(value: &str) -> Result<String, String> {
+	let known = value == theme::SYSTEM
+		|| theme::PRESETS.contains(&value)
+		|| theme::user_themes().iter().any(|name| name == value);
+	if !known {
+		let presets = theme::PRESETS.join(", ");
+		return Err(format!("Unknown --theme '{}': expected system, {} or a user theme", value, presets));
+	}
+	Ok(value.to_string())
+}
+
+fn push_activation(command: &mut Command, activation: Activation) -> Result<(), String> {
+	use std::mem::discriminant;
+
+	match command {
+		Command::Run(activations) => {
+			if activations.iter().any(|given| discriminant(given) == discriminant(&activation)) {
+				let flag = match activation {
+					Activation::ShowPopup => "--show",
+					Activation::SetViewMode(_) => "--mode",
+				};
+				return Err(format!("{} can only be given once", flag));
+			}
+			activations.push(activation);
+			Ok(())
+		}
+		_ => Err("--show and --mode cannot be combined with other commands".to_string()),
+	}
+}
+
+fn parse_view_mode(value: &str) -> Result<ViewMode, String> {
+	match value {
+		"all" => Ok(ViewMode::All),
+		"download" | "down" => Ok(ViewMode::DownloadOnly),
+		"upload" | "up" => Ok(ViewMode::UploadOnly),
+		"overlay" => Ok(ViewMode::Overlay),
+		_ => Err(format!("Invalid --mode value '{}': expected all, download, upload or overlay", value)),
+	}
+}
+
+fn parse_autostart(value: &str) -> Result<AutostartAction, String> {
+	match value {
+		"on" | "enable" => Ok(AutostartAction::Enable),
+		#[cfg(target_os = "linux")]
+		"systemd" => Ok(AutostartAction::EnableSystemd),
+		"off" | "disable" => Ok(AutostartAction::Disable),
+		"status" => Ok(AutostartAction::Status),
+		_ => Err(format!("Invalid --autostart value '{}': expected on, off or status", value)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args(line: &str) -> Vec<String> {
+		line.split_whitespace().map(str::to_string).collect()
+	}
+
+	#[test]
+	fn export_graph_takes_its_own_options() {
+		let Ok(Command::ExportGraph(options)) = parse(args("export-graph")) else {
+			panic!("expected an export");
+		};
+		assert_eq!(options, ExportOptions::new(PathBuf::from("netflux-graph.png")));
+
+		let line = "export-graph -o out.png --format=svg --size 1200x600 --theme light";
+		let Ok(Command::ExportGraph(options)) = parse(args(line)) else {
+			panic!("expected an export");
+		};
+		assert_eq!(options.path, PathBuf::from("out.png"));
+		assert_eq!(options.format, ExportFormat::Svg);
+		assert_eq!((options.width, options.height), (1200, 600));
+		assert_eq!(options.theme.as_deref(), Some("light"));
+
+		assert!(parse(args("export-graph --size 12")).is_err());
+		assert!(parse(args("export-graph --theme nope")).is_err());
+		assert!(parse(args("--show export-graph")).is_err());
+	}
+
+	#[test]
+	fn tui_takes_an_interface() {
+		assert_eq!(parse(args("tui")), Ok(Command::Tui(None)));
+		let wifi = Some("Wi-Fi".to_string());
+		assert_eq!(parse(args("tui --interface=Wi-Fi")), Ok(Command::Tui(wifi)));
+		assert_eq!(parse(args("tui -i eth0")), Ok(Command::Tui(Some("eth0".to_string()))));
+		assert!(parse(args("tui --interface")).is_err());
+		assert!(parse(args("--show tui")).is_err());
+	}
+
+	#[test]
+	fn watch_options() {
+		assert_eq!(parse(args("watch")), Ok(Command::Watch(WatchOptions::default())));
+		let line = "watch --format=tsv -n 5 --interval 0.5 --interface eth0 --plain";
+		let Ok(Command::Watch(options)) = parse(args(line)) else {
+			panic!("expected watch");
+		};
+		assert_eq!(options, WatchOptions {
+			format: WatchFormat::Tsv,
+			count: Some(5),
+			interval: Duration::from_millis(500),
+			interface: Some("eth0".to_string()),
+			plain: true,
+		});
+		assert!(parse(args("watch --count 0")).is_err());
+		assert!(parse(args("watch --interval 0")).is_err());
+		assert!(parse(args("watch --format xml")).is_err());
+		let Ok(Command::Watch(options)) = parse(args("watch --format waybar")) else {
+			panic!("expected watch");
+		};
+		assert_eq!(options.format, WatchFormat::Waybar);
+	}
+
+	#[test]
+	fn metrics_listen_address() {
+		assert_eq!(parse(args("metrics")), Ok(Command::Metrics("127.0.0.1:9184".to_string())));
+		let any = Ok(Command::Metrics("0.0.0.0:9100".to_string()));
+		assert_eq!(parse(args("metrics --listen 0.0.0.0:9100")), any);
+		assert!(parse(args("metrics --listen")).is_err());
+	}
+
+	#[test]
+	fn activation_flags() {
+		use Activation::{ SetViewMode, ShowPopup };
+
+		let cases = [
+			("", vec![]),
+			("--show", vec![ShowPopup]),
+			("--mode all", vec![SetViewMode(ViewMode::All)]),
+			("--mode download", vec![SetViewMode(ViewMode::DownloadOnly)]),
+			("--mode=down", vec![SetViewMode(ViewMode::DownloadOnly)]),
+			("--mode upload", vec![SetViewMode(ViewMode::UploadOnly)]),
+			("--mode up", vec![SetViewMode(ViewMode::UploadOnly)]),
+			("--mode overlay", vec![SetViewMode(ViewMode::Overlay)]),
+			("--mode upload --show", vec![SetViewMode(ViewMode::UploadOnly), ShowPopup]),
+		];
+		for (line, activations) in cases {
+			assert_eq!(parse(args(line)), Ok(Command::Run(activations)), "{}", line);
+		}
+
+		let rejected = [
+			"--popup",
+			"show",
+			"--show --verbose",
+			"--mode",
+			"--mode sideways",
+			"--show --show",
+			"--mode all --mode=upload",
+		];
+		for line in rejected {
+			assert!(parse(args(line)).is_err(), "{}", line);
+		}
+	}
+
+	#[test]
+	fn autostart_takes_one_value() {
+		let cases = [
+			("--autostart on", AutostartAction::Enable),
+			("--autostart=enable", AutostartAction::Enable),
+			("--autostart off", AutostartAction::Disable),
+			("--autostart disable", AutostartAction::Disable),
+			("--autostart status", AutostartAction::Status),
+			#[cfg(target_os = "linux")]
+			("--autostart systemd", AutostartAction::EnableSystemd),
+		];
+		for (line, action) in cases {
+			assert_eq!(parse(args(line)), Ok(Command::Autostart(action)), "{}", line);
+		}
+
+		let rejected = [
+			"--autostart",
+			"--autostart maybe",
+			"--autostart ON",
+			"--autostart on --autostart off",
+			"--show --autostart on",
+			"--autostart on --mode upload",
+		];
+		for line in rejected {
+			assert!(parse(args(line)).is_err(), "{}", line);
+		}
+	}
+
+	#[test]
+	fn export_args_round_trip() {
+		let mut options = ExportOptions::new(PathBuf::from("/tmp/graph.svg"));
+		options.title = Some("Uplink at 14:00".to_string());
+		options.theme = Some("solarized".to_string());
+		assert_eq!(parse(export_args(&options)), Ok(Command::ExportGraph(options)));
+	}
+}
@@ -1,2 +1,3 @@
 pub mod monitor;
+pub mod settings;
 pub mod state;
@@ -1,105 +1,304 @@
+use std::collections::HashMap;
+#[cfg(any(tray, feature = "tui"))]
+use std::time::Duration;
 use std::time::Instant;
-use windows::Win32::NetworkManagement::IpHelper::{
-	GetIfTable2,
-	MIB_IF_ROW2,
-	MIB_IF_TABLE2,
-	FreeMibTable,
-	IF_TYPE_SOFTWARE_LOOPBACK,
-};
-use windows::Win32::NetworkManagement::Ndis::{ IfOperStatusUp, NET_LUID_LH };
-use windows::Win32::Foundation::NO_ERROR;
 
 #[derive(Debug, Clone)]
 pub struct NetStats {
 	pub down_bps: u64,
 	pub up_bps: u64,
+	/// Bytes moved since the previous tick.
+	pub down_bytes: u64,
+	pub up_bytes: u64,
 	pub interface_name: String,
 }
 
-struct InterfaceSnapshot {
-	luid: NET_LUID_LH,
-	in_octets: u64,
-	out_octets: u64,
-	timestamp: Instant,
+/// Counters of one interface as the OS reports them, counting up since the
+/// interface appeared.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct InterfaceCounters {
+	/// The name shown to users: the alias on Windows, e.g. `Wi-Fi`, and the
+	/// kernel name on Linux, e.g. `enp3s0`.
+	pub name: String,
+	pub rx_bytes: u64,
+	pub tx_bytes: u64,
+	pub rx_packets: u64,
+	pub tx_packets: u64,
+	pub rx_errors: u64,
+	pub tx_errors: u64,
 }
 
+/// Samples every interface that is up, except loopback, once per tick.
 pub struct NetMonitor {
-	last_snapshot: Option<InterfaceSnapshot>,
+	last: Option<(Instant, Vec<InterfaceCounters>)>,
+	interfaces: Vec<NetStats>,
+	/// When each interface was first seen up, for OSes that do not keep it.
+	up_since: HashMap<String, Instant>,
 }
 
 impl NetMonitor {
 	pub fn new() -> Self {
-		Self { last_snapshot: None }
+		Self { last: None, interfaces: Vec::new(), up_since: HashMap::new() }
 	}
 
+	/// Rates since the previous tick on the interface that has moved the most
+	/// bytes overall. `None` on the first tick and while nothing is up.
 	pub fn tick(&mut self) -> Option<NetStats> {
+		let counters = platform::interfaces()?;
+		let now = Instant::now();
+		let busiest = counters
+			.iter()
+			.filter(|counter| counter.rx_bytes + counter.tx_bytes > 0)
+			.max_by_key(|counter| counter.rx_bytes + counter.tx_bytes)
+			.map(|counter| counter.name.clone());
+
+		track_up_since(&mut self.up_since, &counters, now);
+		self.interfaces = match &self.last {
+			Some((then, previous)) => {
+				rates(previous, &counters, now.duration_since(*then).as_secs_f64())
+			}
+			None => Vec::new(),
+		};
+		self.last = Some((now, counters));
+
+		let busiest = busiest?;
+		self.interfaces.iter().find(|stats| stats.interface_name == busiest).cloned()
+	}
+
+	/// Rates on every interface from the latest tick, in the order the OS
+	/// lists them.
+	pub fn interfaces(&self) -> &[NetStats] {
+		&self.interfaces
+	}
+
+	/// Counters on every interface as of the latest tick.
+	pub fn counters(&self) -> &[InterfaceCounters] {
+		self.last.as_ref().map_or(&[], |(_, counters)| counters)
+	}
+
+	/// How long `name` has been up as far as this monitor has seen: since the
+	/// first tick for interfaces that were already up then.
+	#[cfg(any(tray, feature = "tui"))]
+	pub fn uptime(&self, name: &str) -> Option<Duration> {
+		self.up_since.get(name).map(Instant::elapsed)
+	}
+}
+
+/// Starts the clock for interfaces that came up and stops it for those that
+/// went away.
+fn track_up_since(
+	up_since: &mut HashMap<String, Instant>,
+	counters: &[InterfaceCounters],
+	now: Instant
+) {
+	up_since.retain(|name, _| counters.iter().any(|counter| &counter.name == name));
+	for counter in counters {
+		up_since.entry(counter.name.clone()).or_insert(now);
+	}
+}
+
+/// Rates from two readings `seconds` apart, for the interfaces in both.
+/// Counters that went backwards, e.g. after a driver reset, count as idle.
+fn rates(
+	previous: &[InterfaceCounters],
+	current: &[InterfaceCounters],
+	seconds: f64
+) -> Vec<NetStats> {
+	if seconds <= 0.0 {
+		return Vec::new();
+	}
+	let previous: HashMap<&str, &InterfaceCounters> = previous
+		.iter()
+		.map(|counter| (counter.name.as_str(), counter))
+		.collect();
+	current
+		.iter()
+		.filter_map(|counter| {
+			let last = previous.get(counter.name.as_str())?;
+			let down_bytes = counter.rx_bytes.saturating_sub(last.rx_bytes);
+			let up_bytes = counter.tx_bytes.saturating_sub(last.tx_bytes);
+			Some(NetStats {
+				down_bps: ((down_bytes as f64) / seconds) as u64,
+				up_bps: ((up_bytes as f64) / seconds) as u64,
+				down_bytes,
+				up_bytes,
+				interface_name: counter.name.clone(),
+			})
+		})
+		.collect()
+}
+
+#[cfg(windows)]
+mod platform {
+	use windows::Win32::Foundation::NO_ERROR;
+	use windows::Win32::NetworkManagement::IpHelper::{
+		FreeMibTable,
+		GetIfTable2,
+		MIB_IF_TABLE2,
+		IF_TYPE_SOFTWARE_LOOPBACK,
+	};
+	use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+
+	use super::InterfaceCounters;
+
+	/// `FilterInterface` in `InterfaceAndOperStatusFlags`: a filter driver
+	/// layered on another interface, repeating its counters.
+	const FILTER_INTERFACE: u8 = 0b10;
+
+	pub fn interfaces() -> Option<Vec<InterfaceCounters>> {
 		unsafe {
 			let mut table_ptr: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
 			if GetIfTable2(&mut table_ptr) != NO_ERROR {
 				return None;
 			}
 			let table = &*table_ptr;
+			let rows = std::slice::from_raw_parts(table.Table.as_ptr(), table.NumEntries as usize);
 
-			let mut best_iface: Option<&MIB_IF_ROW2> = None;
-			let mut max_bytes = 0;
+			let counters = rows
+				.iter()
+				.filter(|row| {
+					row.OperStatus == IfOperStatusUp &&
+						row.Type != IF_TYPE_SOFTWARE_LOOPBACK &&
+						row.InterfaceAndOperStatusFlags._bitfield & FILTER_INTERFACE == 0
+				})
+				.map(|row| InterfaceCounters {
+					name: String::from_utf16_lossy(&row.Alias)
+						.trim_matches(char::from(0))
+						.to_string(),
+					rx_bytes: row.InOctets,
+					tx_bytes: row.OutOctets,
+					rx_packets: row.InUcastPkts.saturating_add(row.InNUcastPkts),
+					tx_packets: row.OutUcastPkts.saturating_add(row.OutNUcastPkts),
+					rx_errors: row.InErrors,
+					tx_errors: row.OutErrors,
+				})
+				.collect();
 
-			let rows = std::slice::from_raw_parts(table.Table.as_ptr(), table.NumEntries as usize);
+			FreeMibTable(table_ptr as *const std::ffi::c_void);
+			Some(counters)
+		}
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+	use std::fs;
 
-			for row in rows {
-				if row.OperStatus == IfOperStatusUp && row.Type != IF_TYPE_SOFTWARE_LOOPBACK {
-					let total = row.InOctets + row.OutOctets;
-					if total > max_bytes {
-						max_bytes = total;
-						best_iface = Some(row);
-					}
-				}
+	use super::{ parse_proc_net_dev, InterfaceCounters };
+
+	/// Interfaces whose operstate is `up`, or `unknown` as tunnels and some
+	/// drivers report it.
+	pub fn interfaces() -> Option<Vec<InterfaceCounters>> {
+		let text = fs::read_to_string("/proc/net/dev").ok()?;
+		let up = |name: &str| {
+			fs::read_to_string(format!("/sys/class/net/{}/operstate", name))
+				.map_or(true, |state| matches!(state.trim(), "up" | "unknown"))
+		};
+		Some(
+			parse_proc_net_dev(&text)
+				.into_iter()
+				.filter(|counter| counter.name != "lo" && up(&counter.name))
+				.collect()
+		)
+	}
+}
+
+/// Counters from `/proc/net/dev`: two header lines, then `name:` and eight
+/// receive columns followed by eight transmit columns, each starting with
+/// bytes, packets and errors.
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_net_dev(text: &str) -> Vec<InterfaceCounters> {
+	text.lines()
+		.skip(2)
+		.filter_map(|line| {
+			let (name, columns) = line.split_once(':')?;
+			let columns: Vec<u64> = columns
+				.split_whitespace()
+				.map(|column| column.parse().ok())
+				.collect::<Option<_>>()?;
+			if columns.len() < 16 {
+				return None;
 			}
+			Some(InterfaceCounters {
+				name: name.trim().to_string(),
+				rx_bytes: columns[0],
+				tx_bytes: columns[8],
+				rx_packets: columns[1],
+				tx_packets: columns[9],
+				rx_errors: columns[2],
+				tx_errors: columns[10],
+			})
+		})
+		.collect()
+}
 
-			let result = if let Some(row) = best_iface {
-				let now = Instant::now();
-				let current_in = row.InOctets;
-				let current_out = row.OutOctets;
-
-				let stats = if let Some(last) = &self.last_snapshot {
-					if last.luid.Value == row.InterfaceLuid.Value {
-						let dt = now.duration_since(last.timestamp).as_secs_f64();
-						if dt > 0.0 {
-							let down = ((current_in.saturating_sub(last.in_octets) as f64) / dt) as u64;
-							let up = ((current_out.saturating_sub(last.out_octets) as f64) / dt) as u64;
-
-							let name = String::from_utf16_lossy(&row.Alias)
-								.trim_matches(char::from(0))
-								.to_string();
-
-							Some(NetStats {
-								down_bps: down,
-								up_bps: up,
-								interface_name: name,
-							})
-						} else {
-							None
-						}
-					} else {
-						None
-					}
-				} else {
-					None
-				};
-
-				self.last_snapshot = Some(InterfaceSnapshot {
-					luid: row.InterfaceLuid,
-					in_octets: current_in,
-					out_octets: current_out,
-					timestamp: now,
-				});
-
-				stats
-			} else {
-				None
-			};
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
 
-			FreeMibTable(table_ptr as *const std::ffi::c_void);
-			result
-		}
+	fn counters(name: &str, rx_bytes: u64, tx_bytes: u64) -> InterfaceCounters {
+		InterfaceCounters { name: name.to_string(), rx_bytes, tx_bytes, ..Default::default() }
+	}
+
+	#[test]
+	fn parses_proc_net_dev() {
+		let text =
+			"Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  123456     789    0    0    0     0          0         0   123456     789    0    0    0     0       0          0
+enp3s0:9876543210 6543210    1    2    0     0          0      1234 123456789  987654    0    0    0     0       0          0
+wg0: 42 1 0 0 0 0 0 0 7 1 0 0 0 0 0 0
+";
+		let parsed = parse_proc_net_dev(text);
+		let names: Vec<&str> = parsed.iter().map(|counters| counters.name.as_str()).collect();
+		assert_eq!(names, ["lo", "enp3s0", "wg0"]);
+		assert_eq!(parsed[1], InterfaceCounters {
+			name: "enp3s0".to_string(),
+			rx_bytes: 9_876_543_210,
+			tx_bytes: 123_456_789,
+			rx_packets: 6_543_210,
+			tx_packets: 987_654,
+			rx_errors: 1,
+			tx_errors: 0,
+		});
+		assert_eq!((parsed[2].rx_bytes, parsed[2].tx_bytes, parsed[2].tx_packets), (42, 7, 1));
+	}
+
+	#[test]
+	fn uptime_restarts_when_an_interface_comes_back() {
+		let mut up_since = HashMap::new();
+		let start = Instant::now();
+		let later = start + Duration::from_secs(30);
+		track_up_since(&mut up_since, &[counters("eth0", 0, 0), counters("wlan0", 0, 0)], start);
+		track_up_since(&mut up_since, &[counters("eth0", 9, 9)], later);
+		assert_eq!(up_since.get("eth0"), Some(&start));
+		assert_eq!(up_since.get("wlan0"), None);
+
+		track_up_since(&mut up_since, &[counters("eth0", 9, 9), counters("wlan0", 0, 0)], later);
+		assert_eq!(up_since.get("wlan0"), Some(&later));
+	}
+
+	#[test]
+	fn rates_cover_interfaces_seen_twice() {
+		let previous = [
+			counters("eth0", 1_000, 500),
+			counters("wlan0", 0, 0),
+			counters("usb0", 9, 9),
+		];
+		let current = [
+			counters("eth0", 3_000, 400),
+			counters("wlan0", 10, 20),
+			counters("wg0", 5, 5),
+		];
+		let stats = rates(&previous, &current, 2.0);
+
+		let names: Vec<&str> = stats.iter().map(|stats| stats.interface_name.as_str()).collect();
+		assert_eq!(names, ["eth0", "wlan0"]);
+		// eth0's transmit counter went backwards.
+		assert_eq!((stats[0].down_bps, stats[0].up_bps), (1_000, 0));
+		assert_eq!((stats[0].down_bytes, stats[0].up_bytes), (2_000, 0));
+		assert_eq!((stats[1].down_bps, stats[1].up_bps), (5, 10));
+		assert!(rates(&previous, &current, 0.0).is_empty());
 	}
 }
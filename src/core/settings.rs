@@ -0,0 +1,165 @@
+use std::path::{ Path, PathBuf };
+use serde::{ Deserialize, Serialize };
+
+use crate::core::state::{ GraphScale, IconStyle };
+#[cfg(any(tray, test))]
+use crate::core::state::ViewMode;
+use crate::sys::paths;
+use crate::ui::theme;
+use crate::utils::format::UnitSystem;
+
+const FILE_NAME: &str = "settings.toml";
+const DEFAULT_POPUP_WIDTH: u32 = 240;
+/// Height of one download or upload section.
+const DEFAULT_SECTION_HEIGHT: u32 = 110;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+	pub units: UnitSystem,
+	pub icon_style: IconStyle,
+	pub graph_scale: GraphScale,
+	pub popup_size: PopupSize,
+	pub details_expanded: bool,
+	/// Keeps the popup open and where it was dragged to.
+	pub popup_pinned: bool,
+	/// Top-left corner of the pinned popup, in physical pixels.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub popup_position: Option<(i32, i32)>,
+	/// Shows the compact mini widget, a window of its own beside the popup.
+	pub widget: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub widget_position: Option<(i32, i32)>,
+	/// `system`, a preset such as `light`, or the name of a file in the
+	/// `themes` directory next to this one.
+	pub theme: String,
+	/// BCP 47 tag such as `de` or `ru-RU`; unset follows the OS locale.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub language: Option<String>,
+	/// Address such as `127.0.0.1:9184` to serve Prometheus metrics on while
+	/// the tray app runs; unset serves none.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub metrics_listen: Option<String>,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			units: UnitSystem::default(),
+			icon_style: IconStyle::default(),
+			graph_scale: GraphScale::default(),
+			popup_size: PopupSize::default(),
+			details_expanded: false,
+			popup_pinned: false,
+			popup_position: None,
+			widget: false,
+			widget_position: None,
+			theme: theme::DEFAULT_THEME.to_string(),
+			language: None,
+			metrics_listen: None,
+		}
+	}
+}
+
+impl Settings {
+	pub fn path() -> Option<PathBuf> {
+		paths::app_config_dir().map(|dir| dir.join(FILE_NAME))
+	}
+
+	/// The saved settings, or the defaults before anything was saved. A file
+	/// that cannot be read or parsed is an error, so callers can tell the
+	/// user and leave it alone instead of saving the defaults over it.
+	pub fn load() -> Result<Self, String> {
+		match Self::path() {
+			Some(path) => Self::load_from(&path),
+			None => Ok(Self::default()),
+		}
+	}
+
+	fn load_from(path: &Path) -> Result<Self, String> {
+		match std::fs::read_to_string(path) {
+			Ok(text) => toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+			Err(e) => Err(format!("{}: {}", path.display(), e)),
+		}
+	}
+
+	#[cfg(tray)]
+	pub fn save(&self) -> Result<(), String> {
+		let path = Self::path().ok_or("No configuration directory available")?;
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+		}
+		let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+		std::fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+	}
+}
+
+/// Popup size in logical pixels. The height is per section, so a size the
+/// user picked carries over between view modes.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct PopupSize {
+	pub width: u32,
+	pub section_height: u32,
+}
+
+impl Default for PopupSize {
+	fn default() -> Self {
+		Self { width: DEFAULT_POPUP_WIDTH, section_height: DEFAULT_SECTION_HEIGHT }
+	}
+}
+
+#[cfg(any(tray, test))]
+impl PopupSize {
+	pub const MIN: Self = Self { width: 160, section_height: 80 };
+
+	/// The size of a window that has been resized to `width` × `height` in
+	/// `view_mode`, clamped to the minimum.
+	pub fn from_window(view_mode: ViewMode, width: u32, height: u32) -> Self {
+		Self {
+			width: width.max(Self::MIN.width),
+			section_height: ((height * 2) / Self::half_sections(view_mode))
+				.max(Self::MIN.section_height),
+		}
+	}
+
+	/// Window width and height for `view_mode`.
+	pub fn window(&self, view_mode: ViewMode) -> (u32, u32) {
+		(self.width, (self.section_height * Self::half_sections(view_mode)) / 2)
+	}
+
+	/// Window height in half sections. The overlay chart gets one and a half
+	/// so that each direction still has room.
+	fn half_sections(view_mode: ViewMode) -> u32 {
+		match view_mode {
+			ViewMode::All => 4,
+			ViewMode::Overlay => 3,
+			ViewMode::DownloadOnly | ViewMode::UploadOnly => 2,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::format::DataUnit;
+
+	#[test]
+	fn missing_file_is_the_defaults_and_a_broken_one_an_error() {
+		let dir = std::env::temp_dir().join(format!("netflux-settings-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let path = dir.join(FILE_NAME);
+		let _ = std::fs::remove_file(&path);
+		assert_eq!(Settings::load_from(&path), Ok(Settings::default()));
+
+		std::fs::write(&path, "widget = true\n[units]\nunit = \"bits\"\n").unwrap();
+		let settings = Settings::load_from(&path).unwrap();
+		assert!(settings.widget);
+		assert_eq!(settings.units.unit, DataUnit::Bits);
+
+		std::fs::write(&path, "widget = tru\n").unwrap();
+		let error = Settings::load_from(&path).unwrap_err();
+		assert!(error.starts_with(&path.display().to_string()), "{}", error);
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
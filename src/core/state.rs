@@ -1,19 +1,100 @@
 use std::collections::VecDeque;
+use std::time::{ Duration, Instant };
+use serde::{ Deserialize, Serialize };
+use crate::core::monitor::NetStats;
+#[cfg(any(tray, test))]
+use crate::sys::clock::ClockTime;
+#[cfg(any(tray, feature = "tui", test))]
+use crate::sys::netinfo::InterfaceDetails;
+use crate::ui::theme::Theme;
+use crate::utils::format::{ NumberFormat, UnitSystem };
 
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum ViewMode {
 	All,
 	DownloadOnly,
 	UploadOnly,
+	/// Download and upload in one chart, upload mirrored below.
+	Overlay,
 }
 
+/// Seconds of history kept for the graphs.
+pub const HISTORY_LEN: usize = 240;
+
+/// What the tray icon shows.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+	/// Download speed as a number with its unit.
+	#[default]
+	Number,
+	/// Download and upload history of the last half minute.
+	Sparkline,
+	/// Download and upload as two vertical bars.
+	Bars,
+	/// Download and upload numbers stacked.
+	Split,
+	/// A single dot coloured by how busy the link is.
+	Dot,
+}
+
+/// How the popup graphs map rates to height.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphScale {
+	#[default]
+	Linear,
+	/// Logarithmic, so small rates stay visible next to large bursts.
+	Log,
+}
+
+/// When a sample was taken: the monotonic instant to measure ages by, and
+/// the wall clock to show the user.
+#[cfg(any(tray, test))]
+#[derive(Clone, Copy, Debug)]
+pub struct SampleTime {
+	pub at: Instant,
+	pub clock: ClockTime,
+}
+
+#[cfg(any(tray, test))]
+impl SampleTime {
+	pub fn now() -> Self {
+		Self { at: Instant::now(), clock: ClockTime::now() }
+	}
+}
+
+#[derive(Clone)]
 pub struct AppState {
 	pub down_bps: u64,
 	pub up_bps: u64,
 	pub down_history: VecDeque<u64>,
 	pub up_history: VecDeque<u64>,
+	/// When each history sample was taken, oldest first.
+	#[cfg(any(tray, test))]
+	pub sample_times: VecDeque<SampleTime>,
+	#[cfg(any(tray, test))]
 	pub view_mode: ViewMode,
+	#[cfg(any(tray, test))]
+	pub icon_style: IconStyle,
+	#[cfg(any(tray, feature = "tui", test))]
+	pub graph_scale: GraphScale,
+	pub theme: Theme,
+	pub units: UnitSystem,
+	pub numbers: NumberFormat,
 	pub interface_name: String,
+	/// Configuration of the active interface, loaded while the details
+	/// section is open.
+	#[cfg(any(tray, feature = "tui", test))]
+	pub details: Option<InterfaceDetails>,
+	#[cfg(any(tray, test))]
+	pub details_expanded: bool,
+	/// The popup stays open when it loses focus and can be dragged.
+	#[cfg(any(tray, test))]
+	pub pinned: bool,
+	pub total_down: u64,
+	pub total_up: u64,
+	pub started: Instant,
 }
 
 impl AppState {
@@ -21,30 +102,122 @@ impl AppState {
 		Self {
 			down_bps: 0,
 			up_bps: 0,
-			down_history: VecDeque::with_capacity(240),
-			up_history: VecDeque::with_capacity(240),
+			down_history: VecDeque::with_capacity(HISTORY_LEN),
+			up_history: VecDeque::with_capacity(HISTORY_LEN),
+			#[cfg(any(tray, test))]
+			sample_times: VecDeque::with_capacity(HISTORY_LEN),
+			#[cfg(any(tray, test))]
 			view_mode: ViewMode::All,
+			#[cfg(any(tray, test))]
+			icon_style: IconStyle::default(),
+			#[cfg(any(tray, feature = "tui", test))]
+			graph_scale: GraphScale::default(),
+			theme: Theme::default(),
+			units: UnitSystem::default(),
+			numbers: NumberFormat::default(),
 			interface_name: String::new(),
+			#[cfg(any(tray, feature = "tui", test))]
+			details: None,
+			#[cfg(any(tray, test))]
+			details_expanded: false,
+			#[cfg(any(tray, test))]
+			pinned: false,
+			total_down: 0,
+			total_up: 0,
+			started: Instant::now(),
 		}
 	}
 
-	pub fn update(&mut self, down: u64, up: u64, interface: String) {
+	pub fn update(&mut self, stats: NetStats) {
+		let (down, up) = (stats.down_bps, stats.up_bps);
 		self.down_bps = down;
 		self.up_bps = up;
-		self.interface_name = interface;
+		self.interface_name = stats.interface_name;
+		self.total_down = self.total_down.saturating_add(stats.down_bytes);
+		self.total_up = self.total_up.saturating_add(stats.up_bytes);
+		self.record(down, up);
+		#[cfg(any(tray, test))]
+		self.record_time(SampleTime::now());
+	}
 
-		if self.down_history.len() >= 240 {
+	/// Appends one sample to the history, dropping the oldest once full.
+	pub fn record(&mut self, down: u64, up: u64) {
+		if self.down_history.len() >= HISTORY_LEN {
 			self.down_history.pop_front();
 		}
 		self.down_history.push_back(down);
 
-		if self.up_history.len() >= 240 {
+		if self.up_history.len() >= HISTORY_LEN {
 			self.up_history.pop_front();
 		}
 		self.up_history.push_back(up);
 	}
 
+	pub fn session_duration(&self) -> Duration {
+		self.started.elapsed()
+	}
+
+	pub fn set_theme(&mut self, theme: Theme) {
+		self.theme = theme;
+	}
+
+	pub fn set_units(&mut self, units: UnitSystem) {
+		self.units = units;
+	}
+
+	pub fn set_numbers(&mut self, numbers: NumberFormat) {
+		self.numbers = numbers;
+	}
+
+	#[cfg(any(tray, feature = "tui"))]
+	pub fn set_details(&mut self, details: Option<InterfaceDetails>) {
+		self.details = details;
+	}
+}
+
+/// Sample times, which only the popup shows.
+#[cfg(any(tray, test))]
+impl AppState {
+	/// Records when the newest sample was taken, dropping the oldest time
+	/// along with its sample.
+	pub fn record_time(&mut self, time: SampleTime) {
+		if self.sample_times.len() >= HISTORY_LEN {
+			self.sample_times.pop_front();
+		}
+		self.sample_times.push_back(time);
+	}
+
+	/// How long before the newest sample the one at `index` was taken.
+	pub fn sample_age(&self, index: usize) -> Option<Duration> {
+		let newest = self.sample_times.back()?.at;
+		self.sample_times.get(index).map(|time| newest.duration_since(time.at))
+	}
+
+	/// The local clock time the sample at `index` was taken at.
+	pub fn sample_clock(&self, index: usize) -> Option<ClockTime> {
+		self.sample_times.get(index).map(|time| time.clock)
+	}
+}
+
+#[cfg(tray)]
+impl AppState {
 	pub fn set_view_mode(&mut self, mode: ViewMode) {
 		self.view_mode = mode;
 	}
+
+	pub fn set_icon_style(&mut self, style: IconStyle) {
+		self.icon_style = style;
+	}
+
+	pub fn set_graph_scale(&mut self, scale: GraphScale) {
+		self.graph_scale = scale;
+	}
+
+	pub fn set_details_expanded(&mut self, expanded: bool) {
+		self.details_expanded = expanded;
+	}
+
+	pub fn set_pinned(&mut self, pinned: bool) {
+		self.pinned = pinned;
+	}
 }
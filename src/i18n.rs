@@ -0,0 +1,112 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{ FluentArgs, FluentResource };
+use fluent_langneg::{ negotiate_languages, NegotiationStrategy };
+use once_cell::sync::OnceCell;
+use unic_langid::{ langid, LanguageIdentifier };
+
+use crate::sys::locale::system_locale;
+use crate::utils::format::NumberFormat;
+
+const CATALOGS: &[(&str, &str)] = &[
+	("en", include_str!("../assets/locales/en.ftl")),
+	("de", include_str!("../assets/locales/de.ftl")),
+	("ru", include_str!("../assets/locales/ru.ftl")),
+];
+
+const FALLBACK: LanguageIdentifier = langid!("en");
+
+static I18N: OnceCell<I18n> = OnceCell::new();
+
+pub struct I18n {
+	bundle: FluentBundle<FluentResource>,
+	fallback: FluentBundle<FluentResource>,
+	numbers: NumberFormat,
+}
+
+impl I18n {
+	/// `language` overrides the OS locale, e.g. `Some("de")` from settings.
+	pub fn new(language: Option<&str>) -> Self {
+		let requested = language
+			.map(str::to_string)
+			.or_else(system_locale)
+			.unwrap_or_else(|| FALLBACK.to_string());
+		let requested: LanguageIdentifier = requested.parse().unwrap_or(FALLBACK);
+
+		let available: Vec<LanguageIdentifier> = CATALOGS.iter()
+			.filter_map(|(tag, _)| tag.parse().ok())
+			.collect();
+		let chosen = negotiate_languages(
+			&[&requested],
+			&available,
+			Some(&FALLBACK),
+			NegotiationStrategy::Lookup
+		)
+			.first()
+			.map(|&id| id.clone())
+			.unwrap_or(FALLBACK);
+
+		Self {
+			bundle: load_bundle(&chosen),
+			fallback: load_bundle(&FALLBACK),
+			// Separators follow the requested region even when we have no
+			// catalog for its language.
+			numbers: NumberFormat::for_locale(&requested.to_string()),
+		}
+	}
+
+	pub fn numbers(&self) -> NumberFormat {
+		self.numbers
+	}
+
+	pub fn message(&self, id: &str, args: Option<&FluentArgs>) -> String {
+		format_message(&self.bundle, id, args)
+			.or_else(|| format_message(&self.fallback, id, args))
+			.unwrap_or_else(|| id.to_string())
+	}
+}
+
+fn load_bundle(language: &LanguageIdentifier) -> FluentBundle<FluentResource> {
+	let source = CATALOGS.iter()
+		.find(|(tag, _)| *tag == language.language.as_str())
+		.map(|(_, source)| *source)
+		.unwrap_or(CATALOGS[0].1);
+
+	let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+	let mut bundle = FluentBundle::new_concurrent(vec![language.clone()]);
+	// Unicode isolation marks render as boxes in tray tooltips and menus.
+	bundle.set_use_isolating(false);
+	let _ = bundle.add_resource(resource);
+	bundle
+}
+
+fn format_message(
+	bundle: &FluentBundle<FluentResource>,
+	id: &str,
+	args: Option<&FluentArgs>
+) -> Option<String> {
+	let pattern = bundle.get_message(id)?.value()?;
+	let mut errors = Vec::new();
+	Some(bundle.format_pattern(pattern, args, &mut errors).into_owned())
+}
+
+/// Sets the process-wide language. Later calls are ignored.
+pub fn init(language: Option<&str>) {
+	let _ = I18N.set(I18n::new(language));
+}
+
+pub fn current() -> &'static I18n {
+	I18N.get_or_init(|| I18n::new(None))
+}
+
+#[cfg(any(tray, feature = "tui", test))]
+pub fn tr(id: &str) -> String {
+	current().message(id, None)
+}
+
+pub fn tr_args(id: &str, args: &FluentArgs) -> String {
+	current().message(id, Some(args))
+}
+
+pub fn numbers() -> NumberFormat {
+	current().numbers()
+}
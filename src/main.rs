@@ -1,30 +1,239 @@
-#![windows_subsystem = "windows"]
+#![cfg_attr(tray, windows_subsystem = "windows")]
 
+#[cfg(tray)]
 mod app;
+mod cli;
 mod core;
+mod i18n;
+mod metrics;
 mod sys;
+#[cfg(feature = "tui")]
+mod tui;
 mod ui;
 mod utils;
+mod watch;
 
-use std::time::Duration;
+#[cfg(tray)]
+use std::sync::atomic::{ AtomicBool, Ordering };
+#[cfg(tray)]
+use std::sync::{ mpsc, Arc };
+#[cfg(tray)]
 use std::thread;
-use winit::event::{ Event, WindowEvent };
+#[cfg(tray)]
+use std::time::Duration;
+#[cfg(tray)]
+use winit::event::{ ElementState, Event, MouseButton, WindowEvent };
+#[cfg(tray)]
 use winit::event_loop::{ ControlFlow, EventLoopBuilder };
+#[cfg(tray)]
 use tray_icon::TrayIconEvent;
+#[cfg(tray)]
 use crate::app::App;
-use crate::sys::registry::enable_autostart;
+#[cfg(tray)]
+use crate::cli::Activation;
+use crate::cli::{ AutostartAction, Command };
+use crate::sys::autostart;
+#[cfg(tray)]
+use crate::sys::instance::{ Instance, InstanceGuard };
+use crate::sys::instance;
+use crate::ui::export::ExportOptions;
 
+/// How long `export-graph` waits for the running instance to write the file.
+#[cfg(tray)]
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(tray)]
 #[derive(Debug)]
 enum UserEvent {
 	Tick,
+	Activate(Vec<Activation>),
+	/// The display layout or DPI changed, which can resize the tray icon.
+	DisplayChanged,
+	/// A second launch asked for an export and waits for the result.
+	ExportGraph(ExportRequest),
+}
+
+/// An `export-graph` from a second launch. Whichever side claims it first
+/// decides: the event loop writes the file, or the listener gives up waiting
+/// and the file is never written behind the caller's back.
+#[cfg(tray)]
+#[derive(Debug)]
+struct ExportRequest {
+	options: ExportOptions,
+	reply: mpsc::Sender<Result<(), String>>,
+	claimed: Arc<AtomicBool>,
+}
+
+#[cfg(tray)]
+impl ExportRequest {
+	/// Runs the export and replies, unless the listener already gave up.
+	fn answer(self, export: impl FnOnce(&ExportOptions) -> Result<(), String>) {
+		if !self.claimed.swap(true, Ordering::SeqCst) {
+			let _ = self.reply.send(export(&self.options));
+		}
+	}
 }
 
 fn main() {
-	enable_autostart();
+	match cli::parse(std::env::args().skip(1)) {
+		#[cfg(tray)]
+		Ok(Command::Run(activations)) => {
+			match instance::acquire() {
+				Ok(Instance::Primary(guard)) => run_tray(Some(guard), activations),
+				Ok(Instance::Secondary) => {
+					let args: Vec<String> = std::env::args().skip(1).collect();
+					if let Err(e) = instance::forward(&args) {
+						attach_console();
+						eprintln!("netflux: {}", e);
+						std::process::exit(1);
+					}
+				}
+				Err(e) => {
+					attach_console();
+					eprintln!("netflux: single-instance check failed: {}", e);
+					run_tray(None, activations);
+				}
+			}
+		}
+		#[cfg(not(tray))]
+		Ok(Command::Run(_)) => {
+			eprintln!("netflux: this build has no tray icon\n\n{}", cli::USAGE);
+			std::process::exit(2);
+		}
+		Ok(Command::Autostart(action)) => {
+			attach_console();
+			if let Err(e) = run_autostart(action) {
+				eprintln!("netflux: {}", e);
+				std::process::exit(1);
+			}
+		}
+		Ok(Command::ExportGraph(options)) => {
+			attach_console();
+			if let Err(e) = run_export(options) {
+				eprintln!("netflux: {}", e);
+				std::process::exit(1);
+			}
+		}
+		#[cfg(feature = "tui")]
+		Ok(Command::Tui(interface)) => {
+			attach_console();
+			if let Err(e) = tui::run(interface) {
+				eprintln!("netflux: {}", e);
+				std::process::exit(1);
+			}
+		}
+		#[cfg(not(feature = "tui"))]
+		Ok(Command::Tui(_)) => {
+			eprintln!("netflux: this build has no terminal UI");
+			std::process::exit(2);
+		}
+		Ok(Command::Watch(options)) => {
+			attach_console();
+			if let Err(e) = watch::run(options) {
+				eprintln!("netflux: {}", e);
+				std::process::exit(1);
+			}
+		}
+		Ok(Command::Metrics(listen)) => {
+			attach_console();
+			if let Err(e) = metrics::run(&listen) {
+				eprintln!("netflux: {}", e);
+				std::process::exit(1);
+			}
+		}
+		Ok(Command::Help) => {
+			attach_console();
+			println!("{}", cli::USAGE);
+		}
+		Err(e) => {
+			attach_console();
+			eprintln!("netflux: {}\n\n{}", e, cli::USAGE);
+			std::process::exit(2);
+		}
+	}
+}
+
+fn attach_console() {
+	#[cfg(windows)]
+	sys::console::attach_parent_console();
+}
 
+fn run_autostart(action: AutostartAction) -> Result<(), String> {
+	match action {
+		AutostartAction::Enable => autostart::enable()?,
+		#[cfg(target_os = "linux")]
+		AutostartAction::EnableSystemd => autostart::enable_with(autostart::AutostartMethod::SystemdUnit)?,
+		AutostartAction::Disable => autostart::disable()?,
+		AutostartAction::Status => {}
+	}
+	println!("autostart: {}", if autostart::is_enabled() { "on" } else { "off" });
+	Ok(())
+}
+
+/// Hands the export to the running instance, which has the history, and
+/// reports how it went.
+fn run_export(mut options: ExportOptions) -> Result<(), String> {
+	options.path = std::path::absolute(&options.path).map_err(|e|
+		format!("{}: {}", options.path.display(), e)
+	)?;
+	if !instance::is_running()? {
+		return Err("NetFlux is not running, so there is no graph history to export".to_string());
+	}
+
+	instance::forward(&cli::export_args(&options))?;
+	println!("Saved {}", options.path.display());
+	Ok(())
+}
+
+#[cfg(tray)]
+fn run_tray(mut guard: Option<InstanceGuard>, activations: Vec<Activation>) {
 	let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build().unwrap();
 	let proxy = event_loop.create_proxy();
 
+	if let Some(guard) = &mut guard {
+		let proxy = proxy.clone();
+		let listening = guard.listen(move |args| {
+			// A bare second launch just brings the popup up.
+			let activations = match cli::parse(args)? {
+				Command::Run(activations) if !activations.is_empty() => activations,
+				Command::Run(_) => vec![Activation::ShowPopup],
+				Command::ExportGraph(options) => {
+					let (reply, result) = mpsc::channel();
+					let claimed = Arc::new(AtomicBool::new(false));
+					let request = ExportRequest { options, reply, claimed: claimed.clone() };
+					let _ = proxy.send_event(UserEvent::ExportGraph(request));
+					if let Ok(answer) = result.recv_timeout(EXPORT_TIMEOUT) {
+						return answer;
+					}
+					// Once the event loop has started writing, wait for it to finish.
+					if claimed.swap(true, Ordering::SeqCst) {
+						return result.recv().unwrap_or_else(|_| {
+							Err("The running instance stopped while writing the graph".to_string())
+						});
+					}
+					return Err("The running instance was too busy to export the graph".to_string());
+				}
+				_ => {
+					return Err("The running instance cannot do that".to_string());
+				}
+			};
+			let _ = proxy.send_event(UserEvent::Activate(activations));
+			Ok(())
+		});
+		if let Err(e) = listening {
+			eprintln!("netflux: cannot accept activation requests: {}", e);
+		}
+	}
+
+	let mut app = App::new(&event_loop);
+	app.activate(&activations);
+	{
+		let proxy = proxy.clone();
+		app.watch_displays(move || {
+			let _ = proxy.send_event(UserEvent::DisplayChanged);
+		});
+	}
+
 	thread::spawn(move || {
 		loop {
 			thread::sleep(Duration::from_millis(1000));
@@ -32,21 +241,14 @@ fn main() {
 		}
 	});
 
-	let mut app = App::new(&event_loop);
-
 	event_loop
 		.run(move |event, elwt| {
 			elwt.set_control_flow(ControlFlow::Wait);
 
-			if let Ok(event) = TrayIconEvent::receiver().try_recv() {
-				match event {
-					TrayIconEvent::Click { button, .. } => {
-						if button == tray_icon::MouseButton::Left {
-							app.toggle_popup();
-						}
-					}
-					_ => {}
-				}
+			if let Ok(TrayIconEvent::Click { button: tray_icon::MouseButton::Left, .. }) =
+				TrayIconEvent::receiver().try_recv()
+			{
+				app.toggle_popup();
 			}
 
 			if let Ok(event) = tray_icon::menu::MenuEvent::receiver().try_recv() {
@@ -59,11 +261,56 @@ fn main() {
 				Event::UserEvent(UserEvent::Tick) => {
 					app.tick();
 				}
+				Event::UserEvent(UserEvent::Activate(activations)) => {
+					app.activate(&activations);
+				}
+				Event::UserEvent(UserEvent::DisplayChanged) => {
+					app.refresh_icon_size();
+				}
+				Event::UserEvent(UserEvent::ExportGraph(request)) => {
+					request.answer(|options| app.export_graph(options));
+				}
+				Event::WindowEvent { window_id, event } if app.is_widget(window_id) => {
+					app.widget_event(event);
+				}
 				Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } => {
 					app.redraw_popup();
 				}
+				Event::WindowEvent { event: WindowEvent::ScaleFactorChanged { scale_factor, .. }, .. } => {
+					app.set_scale_factor(scale_factor);
+				}
+				Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, .. } => {
+					app.hover_popup(Some(position));
+				}
+				Event::WindowEvent { event: WindowEvent::CursorLeft { .. }, .. } => {
+					app.hover_popup(None);
+				}
+				Event::WindowEvent {
+					event: WindowEvent::MouseInput {
+						state: ElementState::Pressed,
+						button: MouseButton::Left,
+						..
+					},
+					..
+				} => {
+					app.click_popup();
+				}
+				Event::WindowEvent { event: WindowEvent::Focused(false), .. } => {
+					app.popup_focus_lost();
+				}
+				Event::WindowEvent { event: WindowEvent::Moved(position), .. } => {
+					app.move_popup(position);
+				}
+				Event::WindowEvent { event: WindowEvent::Resized(size), .. } => {
+					app.resize_popup(size);
+				}
+				Event::WindowEvent { event: WindowEvent::ThemeChanged(theme), .. } => {
+					app.set_system_dark(theme == winit::window::Theme::Dark);
+				}
 				_ => {}
 			}
 		})
 		.unwrap();
+
+	drop(guard);
 }
@@ -0,0 +1,271 @@
+use std::fmt::Write as _;
+use std::io::{ BufRead, BufReader, Read, Write };
+use std::net::{ SocketAddr, TcpListener, TcpStream };
+use std::sync::{ Arc, Mutex };
+use std::thread;
+use std::time::Duration;
+
+use crate::core::monitor::{ InterfaceCounters, NetMonitor, NetStats };
+
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:9184";
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a client has to send its request, or take the response, before
+/// it is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most bytes read of a request line and headers; scrapers send a few hundred.
+const MAX_REQUEST_HEAD: u64 = 8 * 1024;
+const OPENMETRICS: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const PLAIN_TEXT: &str = "text/plain; charset=utf-8";
+
+type Counter = fn(&InterfaceCounters) -> u64;
+type Rate = fn(&NetStats) -> u64;
+
+const COUNTERS: [(&str, &str, Counter); 6] = [
+	("netflux_receive_bytes", "Bytes received on the interface.", |c| c.rx_bytes),
+	("netflux_transmit_bytes", "Bytes sent on the interface.", |c| c.tx_bytes),
+	("netflux_receive_packets", "Packets received on the interface.", |c| c.rx_packets),
+	("netflux_transmit_packets", "Packets sent on the interface.", |c| c.tx_packets),
+	("netflux_receive_errors", "Inbound packets with errors.", |c| c.rx_errors),
+	("netflux_transmit_errors", "Outbound packets that failed to send.", |c| c.tx_errors),
+];
+
+const RATES: [(&str, &str, Rate); 2] = [
+	("netflux_receive_bytes_per_second", "Download rate over the last second.", |s| s.down_bps),
+	("netflux_transmit_bytes_per_second", "Upload rate over the last second.", |s| s.up_bps),
+];
+
+/// The latest reading, written on every tick and read by every scrape.
+#[derive(Default)]
+struct Snapshot {
+	counters: Vec<InterfaceCounters>,
+	rates: Vec<NetStats>,
+}
+
+/// Serves `/metrics` on a background thread, showing whatever was last
+/// passed to `update`.
+pub struct Server {
+	snapshot: Arc<Mutex<Snapshot>>,
+	address: SocketAddr,
+}
+
+impl Server {
+	/// Binds `listen` and starts answering scrapes, one at a time on the
+	/// thread that accepts them; a client that stalls holds up the next for
+	/// at most `REQUEST_TIMEOUT`.
+	pub fn start(listen: &str) -> Result<Self, String> {
+		let listener = TcpListener::bind(listen)
+			.map_err(|e| format!("Cannot listen on {}: {}", listen, e))?;
+		let address = listener.local_addr().map_err(|e| e.to_string())?;
+		let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+
+		let scraped = Arc::clone(&snapshot);
+		thread::spawn(move || {
+			for stream in listener.incoming().flatten() {
+				let _ = answer(stream, &scraped);
+			}
+		});
+		Ok(Self { snapshot, address })
+	}
+
+	/// The address bound, which tells the port when `listen` asks for port 0.
+	pub fn address(&self) -> SocketAddr {
+		self.address
+	}
+
+	/// Publishes the counters and rates of `monitor`'s latest tick.
+	pub fn update(&self, monitor: &NetMonitor) {
+		let fresh = Snapshot {
+			counters: monitor.counters().to_vec(),
+			rates: monitor.interfaces().to_vec(),
+		};
+		*self.snapshot.lock().unwrap_or_else(|e| e.into_inner()) = fresh;
+	}
+}
+
+/// Samples the interfaces every second and serves them in the foreground
+/// until the process is stopped.
+pub fn run(listen: &str) -> Result<(), String> {
+	let server = Server::start(listen)?;
+	// The first reading is taken up front so counters are there from the
+	// first scrape; rates follow a second later.
+	let mut monitor = NetMonitor::new();
+	monitor.tick();
+	server.update(&monitor);
+	println!("Serving metrics on http://{}/metrics", server.address());
+	loop {
+		thread::sleep(SAMPLE_INTERVAL);
+		monitor.tick();
+		server.update(&monitor);
+	}
+}
+
+fn answer(stream: TcpStream, snapshot: &Mutex<Snapshot>) -> std::io::Result<()> {
+	stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+	stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+	respond(&stream, snapshot)
+}
+
+/// Answers one HTTP/1.1 request and closes the connection.
+fn respond(mut stream: impl Read + Write, snapshot: &Mutex<Snapshot>) -> std::io::Result<()> {
+	let mut reader = BufReader::new((&mut stream).take(MAX_REQUEST_HEAD));
+	let mut request = String::new();
+	reader.read_line(&mut request)?;
+	// Nothing in the headers changes the answer, but they are read so the
+	// client is not reset while still sending them.
+	let mut header = String::new();
+	while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+		header.clear();
+	}
+	let too_long = reader.get_ref().limit() == 0;
+	drop(reader);
+
+	let mut parts = request.split_whitespace();
+	let method = parts.next().unwrap_or_default();
+	let path = parts.next().unwrap_or_default().split('?').next().unwrap_or_default();
+	let (status, content_type, body) = match (method, path) {
+		_ if too_long => {
+			("431 Request Header Fields Too Large", PLAIN_TEXT, "Request too long\n".to_string())
+		}
+		("GET" | "HEAD", "/metrics") => {
+			let snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+			("200 OK", OPENMETRICS, render(&snapshot.counters, &snapshot.rates))
+		}
+		("GET" | "HEAD", _) => {
+			("404 Not Found", PLAIN_TEXT, "Metrics are at /metrics\n".to_string())
+		}
+		_ => {
+			("405 Method Not Allowed", PLAIN_TEXT, "Only GET and HEAD are supported\n".to_string())
+		}
+	};
+
+	write!(
+		stream,
+		"HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+		status,
+		content_type,
+		body.len()
+	)?;
+	if method != "HEAD" {
+		stream.write_all(body.as_bytes())?;
+	}
+	stream.flush()
+}
+
+/// The OpenMetrics text exposition: a counter family per `COUNTERS` entry
+/// and a gauge per `RATES` entry, with a sample for every interface.
+fn render(counters: &[InterfaceCounters], rates: &[NetStats]) -> String {
+	let mut text = String::new();
+	for (name, help, value) in COUNTERS {
+		let _ = writeln!(text, "# TYPE {} counter\n# HELP {} {}", name, name, help);
+		for counters in counters {
+			let interface = escape(&counters.name);
+			let value = value(counters);
+			let _ = writeln!(text, "{}_total{{interface=\"{}\"}} {}", name, interface, value);
+		}
+	}
+	for (name, help, value) in RATES {
+		let _ = writeln!(text, "# TYPE {} gauge\n# HELP {} {}", name, name, help);
+		for stats in rates {
+			let interface = escape(&stats.interface_name);
+			let _ = writeln!(text, "{}{{interface=\"{}\"}} {}", name, interface, value(stats));
+		}
+	}
+	text.push_str("# EOF\n");
+	text
+}
+
+/// A label value with backslashes, quotes and newlines escaped.
+fn escape(value: &str) -> String {
+	value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn renders_counters_and_gauges_per_interface() {
+		let counters = [InterfaceCounters {
+			name: "Ethernet \"2\"".to_string(),
+			rx_bytes: 1_000,
+			tx_bytes: 200,
+			rx_packets: 10,
+			tx_packets: 2,
+			rx_errors: 1,
+			tx_errors: 0,
+		}];
+		let rates = [NetStats {
+			down_bps: 500,
+			up_bps: 50,
+			down_bytes: 500,
+			up_bytes: 50,
+			interface_name: "Ethernet \"2\"".to_string(),
+		}];
+		let text = render(&counters, &rates);
+
+		assert!(text.starts_with(
+			"# TYPE netflux_receive_bytes counter\n\
+			 # HELP netflux_receive_bytes Bytes received on the interface.\n\
+			 netflux_receive_bytes_total{interface=\"Ethernet \\\"2\\\"\"} 1000\n"
+		));
+		let label = "{interface=\"Ethernet \\\"2\\\"\"}";
+		assert!(text.contains(&format!("netflux_transmit_packets_total{} 2\n", label)));
+		assert!(text.contains(&format!("netflux_receive_errors_total{} 1\n", label)));
+		assert!(text.contains("# TYPE netflux_transmit_bytes_per_second gauge\n"));
+		assert!(text.contains(&format!("netflux_transmit_bytes_per_second{} 50\n", label)));
+		assert!(text.ends_with("} 50\n# EOF\n"));
+	}
+
+	/// A client that has sent `request` and keeps what it is sent back.
+	struct Client {
+		request: std::io::Cursor<Vec<u8>>,
+		response: Vec<u8>,
+	}
+
+	impl Read for Client {
+		fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+			self.request.read(buf)
+		}
+	}
+
+	impl Write for Client {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.response.write(buf)
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	fn exchange(request: &[u8]) -> (String, u64) {
+		let request = std::io::Cursor::new(request.to_vec());
+		let mut client = Client { request, response: Vec::new() };
+		respond(&mut client, &Mutex::default()).unwrap();
+		(String::from_utf8(client.response).unwrap(), client.request.position())
+	}
+
+	#[test]
+	fn reads_no_further_than_the_request_head_limit() {
+		let (response, read) = exchange(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		assert!(response.ends_with("# EOF\n"), "{}", response);
+		assert!(read < MAX_REQUEST_HEAD);
+
+		let padding = "a".repeat(2 * MAX_REQUEST_HEAD as usize);
+		let request = format!("GET /metrics HTTP/1.1\r\nX-Padding: {}\r\n\r\n", padding);
+		let (response, read) = exchange(request.as_bytes());
+		assert!(response.starts_with("HTTP/1.1 431 "), "{}", response);
+		assert_eq!(read, MAX_REQUEST_HEAD);
+
+		let (response, read) = exchange(format!("GET /{}", padding).as_bytes());
+		assert!(response.starts_with("HTTP/1.1 431 "), "{}", response);
+		assert_eq!(read, MAX_REQUEST_HEAD);
+	}
+
+	#[test]
+	fn label_values_are_escaped() {
+		assert_eq!(escape("a\\b\"c\nd"), "a\\\\b\\\"c\\nd");
+	}
+}
@@ -0,0 +1,40 @@
+#[cfg(target_os = "linux")]
+pub use crate::sys::xdg::AutostartMethod;
+
+#[cfg(windows)]
+pub fn enable() -> Result<(), String> {
+	crate::sys::registry::enable_autostart()
+}
+
+#[cfg(windows)]
+pub fn disable() -> Result<(), String> {
+	crate::sys::registry::disable_autostart()
+}
+
+#[cfg(windows)]
+pub fn is_enabled() -> bool {
+	crate::sys::registry::is_autostart_enabled()
+}
+
+#[cfg(target_os = "linux")]
+pub fn enable() -> Result<(), String> {
+	enable_with(AutostartMethod::DesktopEntry)
+}
+
+#[cfg(target_os = "linux")]
+pub fn enable_with(method: AutostartMethod) -> Result<(), String> {
+	crate::sys::xdg::XdgAutostart::from_env()?.enable(method)
+}
+
+#[cfg(target_os = "linux")]
+pub fn disable() -> Result<(), String> {
+	crate::sys::xdg::XdgAutostart::from_env()?.disable()
+}
+
+#[cfg(target_os = "linux")]
+pub fn is_enabled() -> bool {
+	crate::sys::xdg::XdgAutostart
+		::from_env()
+		.map(|autostart| autostart.is_enabled())
+		.unwrap_or(false)
+}
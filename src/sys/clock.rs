@@ -0,0 +1,71 @@
+use std::fmt;
+
+const SECONDS_PER_DAY: u32 = 86_400;
+
+/// A time of day on the local clock, to the second.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClockTime(u32);
+
+impl ClockTime {
+	/// The time `seconds` after midnight, wrapping at a day.
+	pub fn from_seconds(seconds: u32) -> Self {
+		Self(seconds % SECONDS_PER_DAY)
+	}
+
+	/// The time of day now, in the user's time zone.
+	#[cfg(windows)]
+	pub fn now() -> Self {
+		use windows::Win32::System::SystemInformation::GetLocalTime;
+
+		let time = unsafe { GetLocalTime() };
+		Self::from_seconds(
+			(time.wHour as u32) * 3600 + (time.wMinute as u32) * 60 + (time.wSecond as u32)
+		)
+	}
+
+	/// The time of day now, in the user's time zone, or UTC if the C library
+	/// cannot tell.
+	#[cfg(unix)]
+	pub fn now() -> Self {
+		let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+		let local = unsafe {
+			let now = libc::time(std::ptr::null_mut());
+			!libc::localtime_r(&now, &mut tm).is_null()
+		};
+		if !local {
+			let since_epoch = std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap_or_default();
+			return Self::from_seconds((since_epoch.as_secs() % (SECONDS_PER_DAY as u64)) as u32);
+		}
+		Self::from_seconds(
+			(tm.tm_hour as u32) * 3600 + (tm.tm_min as u32) * 60 + (tm.tm_sec as u32)
+		)
+	}
+}
+
+impl fmt::Display for ClockTime {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let (hours, minutes, seconds) = (self.0 / 3600, (self.0 / 60) % 60, self.0 % 60);
+		write!(f, "{:02}:{:02}:{:02}", hours, minutes, seconds)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn shows_hours_minutes_and_seconds() {
+		assert_eq!(ClockTime::from_seconds(14 * 3600 + 3 * 60 + 27).to_string(), "14:03:27");
+		assert_eq!(ClockTime::from_seconds(0).to_string(), "00:00:00");
+		assert_eq!(ClockTime::from_seconds(SECONDS_PER_DAY + 61).to_string(), "00:01:01");
+	}
+
+	#[test]
+	fn now_is_a_time_of_day() {
+		let now = ClockTime::now().to_string();
+		assert_eq!(now.len(), 8, "{}", now);
+		assert!(now.as_str() < "24:00:00", "{}", now);
+	}
+}
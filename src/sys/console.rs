@@ -0,0 +1,9 @@
+use windows::Win32::System::Console::{ AttachConsole, ATTACH_PARENT_PROCESS };
+
+/// The binary uses the GUI subsystem, so stdout goes nowhere unless we
+/// borrow the console of the shell that launched us.
+pub fn attach_parent_console() {
+	unsafe {
+		let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+	}
+}
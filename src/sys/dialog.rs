@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+/// Asks where to save a graph, offering PNG and SVG. `None` when the user
+/// cancels. The common dialog pumps messages itself while it is open.
+pub fn save_graph_file(title: &str, default_name: &str) -> Option<PathBuf> {
+	use windows::core::{ PCWSTR, PWSTR };
+	use windows::Win32::UI::Controls::Dialogs::{
+		GetSaveFileNameW,
+		OPENFILENAMEW,
+		OFN_NOCHANGEDIR,
+		OFN_OVERWRITEPROMPT,
+		OFN_PATHMUSTEXIST,
+	};
+
+	let mut file: Vec<u16> = default_name.encode_utf16().collect();
+	file.resize(32_768, 0);
+	let filter: Vec<u16> = "PNG image (*.png)\0*.png\0SVG image (*.svg)\0*.svg\0\0"
+		.encode_utf16()
+		.collect();
+	let title = wide_string(title);
+	let extension = wide_string("png");
+
+	let mut dialog = OPENFILENAMEW {
+		lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+		lpstrFilter: PCWSTR::from_raw(filter.as_ptr()),
+		nFilterIndex: 1,
+		lpstrFile: PWSTR::from_raw(file.as_mut_ptr()),
+		nMaxFile: file.len() as u32,
+		lpstrTitle: PCWSTR::from_raw(title.as_ptr()),
+		lpstrDefExt: PCWSTR::from_raw(extension.as_ptr()),
+		Flags: OFN_OVERWRITEPROMPT | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR,
+		..Default::default()
+	};
+	if !(unsafe { GetSaveFileNameW(&mut dialog) }).as_bool() {
+		return None;
+	}
+	let len = file.iter().position(|&c| c == 0).unwrap_or(file.len());
+	Some(PathBuf::from(String::from_utf16_lossy(&file[..len])))
+}
+
+/// Tells the user something went wrong, without waiting for them to
+/// dismiss it; the tray app has no console to print to.
+pub fn show_error(message: &str) {
+	use windows::core::PCWSTR;
+	use windows::Win32::Foundation::HWND;
+	use windows::Win32::UI::WindowsAndMessaging::{
+		MessageBoxW,
+		MB_ICONWARNING,
+		MB_OK,
+		MB_SETFOREGROUND,
+	};
+
+	let text = wide_string(message);
+	std::thread::spawn(move || {
+		let caption = wide_string("NetFlux");
+		unsafe {
+			MessageBoxW(
+				HWND::default(),
+				PCWSTR::from_raw(text.as_ptr()),
+				PCWSTR::from_raw(caption.as_ptr()),
+				MB_OK | MB_ICONWARNING | MB_SETFOREGROUND
+			);
+		}
+	});
+}
+
+fn wide_string(s: &str) -> Vec<u16> {
+	s.encode_utf16().chain(std::iter::once(0)).collect()
+}
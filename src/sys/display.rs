@@ -0,0 +1,100 @@
+use windows::Win32::Foundation::{ HWND, LPARAM, LRESULT, WPARAM };
+use windows::Win32::Graphics::Gdi::HMONITOR;
+
+use crate::ui::placement::{ Screen, ScreenRect };
+
+/// Pixel size the notification area draws its icons at on the monitor
+/// containing `point`, or the primary one, if the OS reports one. Windows
+/// uses the small-icon metric for that monitor's DPI, which already includes
+/// display scaling.
+pub fn tray_icon_size(point: Option<(i32, i32)>) -> Option<u32> {
+	use windows::Win32::UI::HiDpi::GetSystemMetricsForDpi;
+	use windows::Win32::UI::WindowsAndMessaging::SM_CXSMICON;
+
+	let dpi = monitor_dpi(monitor_at(point))?;
+	let size = unsafe { GetSystemMetricsForDpi(SM_CXSMICON, dpi) };
+	(size > 0).then_some(size as u32)
+}
+
+/// The monitor containing `point`, or the primary one, with its scale
+/// factor for per-monitor DPI.
+pub fn screen_at(point: Option<(i32, i32)>) -> Option<(Screen, f64)> {
+	use windows::Win32::Foundation::RECT;
+	use windows::Win32::Graphics::Gdi::{ GetMonitorInfoW, MONITORINFO };
+
+	let monitor = monitor_at(point);
+	let mut info = MONITORINFO {
+		cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+		..Default::default()
+	};
+	if !(unsafe { GetMonitorInfoW(monitor, &mut info) }).as_bool() {
+		return None;
+	}
+	let rect = |r: RECT| ScreenRect::new(r.left, r.top, r.right - r.left, r.bottom - r.top);
+	let screen = Screen { bounds: rect(info.rcMonitor), work_area: rect(info.rcWork) };
+	let scale = monitor_dpi(monitor).map_or(1.0, |dpi| (dpi as f64) / 96.0);
+	Some((screen, scale))
+}
+
+fn monitor_at(point: Option<(i32, i32)>) -> HMONITOR {
+	use windows::Win32::Foundation::POINT;
+	use windows::Win32::Graphics::Gdi::{
+		MonitorFromPoint,
+		MONITOR_DEFAULTTONEAREST,
+		MONITOR_DEFAULTTOPRIMARY,
+	};
+
+	unsafe {
+		match point {
+			Some((x, y)) => MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST),
+			None => MonitorFromPoint(POINT::default(), MONITOR_DEFAULTTOPRIMARY),
+		}
+	}
+}
+
+fn monitor_dpi(monitor: HMONITOR) -> Option<u32> {
+	use windows::Win32::UI::HiDpi::{ GetDpiForMonitor, MDT_EFFECTIVE_DPI };
+
+	let (mut dpi_x, mut dpi_y) = (0, 0);
+	let dpi = unsafe { GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) };
+	dpi.ok().filter(|_| dpi_x > 0).map(|_| dpi_x)
+}
+
+/// Calls `on_change` whenever `window` hears that the display layout, a
+/// monitor's DPI or the work area changed, any of which can resize the
+/// notification area's icons. The window may stay hidden; top-level windows
+/// get these messages either way.
+pub fn watch_displays(window: HWND, on_change: impl Fn() + 'static) {
+	use windows::Win32::UI::Shell::SetWindowSubclass;
+
+	// Lives as long as the window, which is the life of the app.
+	let on_change: Box<Box<dyn Fn()>> = Box::new(Box::new(on_change));
+	let data = Box::into_raw(on_change) as usize;
+	unsafe {
+		let _ = SetWindowSubclass(window, Some(display_proc), DISPLAY_SUBCLASS_ID, data);
+	}
+}
+
+const DISPLAY_SUBCLASS_ID: usize = 1;
+
+unsafe extern "system" fn display_proc(
+	window: HWND,
+	message: u32,
+	wparam: WPARAM,
+	lparam: LPARAM,
+	_id: usize,
+	data: usize
+) -> LRESULT {
+	use windows::Win32::UI::Shell::DefSubclassProc;
+	use windows::Win32::UI::WindowsAndMessaging::{
+		WM_DISPLAYCHANGE,
+		WM_DPICHANGED,
+		WM_SETTINGCHANGE,
+	};
+
+	if matches!(message, WM_DISPLAYCHANGE | WM_DPICHANGED | WM_SETTINGCHANGE) {
+		let on_change = &*(data as *const Box<dyn Fn()>);
+		on_change();
+	}
+	DefSubclassProc(window, message, wparam, lparam)
+}
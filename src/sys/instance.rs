@@ -0,0 +1,484 @@
+use std::io::{ Read, Write };
+use std::time::Duration;
+
+/// How long a second launch keeps retrying while the first one is still
+/// bringing up its listener.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+const FORWARD_RETRY: Duration = Duration::from_millis(100);
+/// Longest request or reply accepted; argument lists and error messages are
+/// far shorter.
+const MAX_FRAME: usize = 64 * 1024;
+
+#[cfg(tray)]
+pub enum Instance {
+	Primary(InstanceGuard),
+	Secondary,
+}
+
+/// Held for the lifetime of the first instance. Dropping it releases the
+/// mutex and pipe, or the lock file.
+#[cfg(tray)]
+pub struct InstanceGuard {
+	inner: platform::Guard,
+}
+
+#[cfg(tray)]
+impl InstanceGuard {
+	/// Spawns a thread that hands every forwarded argument list to
+	/// `on_message`, one at a time, and sends what it returns back to the
+	/// launch that forwarded it.
+	pub fn listen<F>(&mut self, on_message: F) -> Result<(), String>
+		where F: Fn(Vec<String>) -> Result<(), String> + Send + 'static
+	{
+		platform::listen(&mut self.inner, on_message)
+	}
+}
+
+#[cfg(tray)]
+pub fn acquire() -> Result<Instance, String> {
+	Ok(match platform::acquire()? {
+		Some(inner) => Instance::Primary(InstanceGuard { inner }),
+		None => Instance::Secondary,
+	})
+}
+
+/// Whether another launch holds the instance, briefly taking it to find out.
+pub fn is_running() -> Result<bool, String> {
+	Ok(platform::acquire()?.is_none())
+}
+
+/// Sends `args` to the running instance and waits for its answer, which is
+/// an error if it could not act on them.
+pub fn forward(args: &[String]) -> Result<(), String> {
+	let started = std::time::Instant::now();
+	let stream = loop {
+		match platform::connect() {
+			Ok(stream) => break stream,
+			Err(e) if started.elapsed() >= FORWARD_TIMEOUT => {
+				return Err(format!("Could not reach the running instance: {}", e));
+			}
+			Err(_) => std::thread::sleep(FORWARD_RETRY),
+		}
+	};
+	exchange(stream, args)
+}
+
+/// The second launch's side of a connection: one request, one reply.
+fn exchange(mut stream: impl Read + Write, args: &[String]) -> Result<(), String> {
+	let lost = |e: std::io::Error| format!("Lost the running instance: {}", e);
+	write_frame(&mut stream, &encode(args)).map_err(lost)?;
+	decode_reply(&read_frame(&mut stream).map_err(lost)?)
+}
+
+/// The first instance's side of a connection.
+#[cfg(any(tray, all(unix, test)))]
+fn serve<F>(mut stream: impl Read + Write, on_message: &F) -> std::io::Result<()>
+	where F: Fn(Vec<String>) -> Result<(), String>
+{
+	let args = decode(&read_frame(&mut stream)?);
+	write_frame(&mut stream, &encode_reply(&on_message(args)))
+}
+
+/// Messages go both ways as a little-endian `u32` length and that many bytes,
+/// since a Windows pipe cannot be half-closed to mark the end of a request.
+fn write_frame(stream: &mut impl Write, payload: &[u8]) -> std::io::Result<()> {
+	stream.write_all(&(payload.len() as u32).to_le_bytes())?;
+	stream.write_all(payload)?;
+	stream.flush()
+}
+
+fn read_frame(stream: &mut impl Read) -> std::io::Result<Vec<u8>> {
+	let mut len = [0; 4];
+	stream.read_exact(&mut len)?;
+	let len = u32::from_le_bytes(len) as usize;
+	if len > MAX_FRAME {
+		return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "message too long"));
+	}
+	let mut payload = vec![0; len];
+	stream.read_exact(&mut payload)?;
+	Ok(payload)
+}
+
+fn encode(args: &[String]) -> Vec<u8> {
+	args.join("\0").into_bytes()
+}
+
+#[cfg(any(tray, test))]
+fn decode(message: &[u8]) -> Vec<String> {
+	if message.is_empty() {
+		return Vec::new();
+	}
+	String::from_utf8_lossy(message).split('\0').map(str::to_string).collect()
+}
+
+/// `+` on success, `-` and the message on failure.
+#[cfg(any(tray, test))]
+fn encode_reply(reply: &Result<(), String>) -> Vec<u8> {
+	match reply {
+		Ok(()) => b"+".to_vec(),
+		Err(e) => format!("-{}", e).into_bytes(),
+	}
+}
+
+fn decode_reply(reply: &[u8]) -> Result<(), String> {
+	match reply.split_first() {
+		Some((b'+', _)) => Ok(()),
+		Some((b'-', message)) => Err(String::from_utf8_lossy(message).into_owned()),
+		_ => Err("The running instance sent an unexpected reply".to_string()),
+	}
+}
+
+#[cfg(windows)]
+mod platform {
+	use std::fs::{ File, OpenOptions };
+	use std::os::windows::io::FromRawHandle;
+	use windows::Win32::Foundation::{ CloseHandle, GetLastError, HANDLE, ERROR_ALREADY_EXISTS };
+	use windows::Win32::System::Threading::CreateMutexW;
+	use windows::core::PCWSTR;
+
+	const MUTEX_NAME: &str = "Local\\NetFlux.SingleInstance";
+
+	pub struct Guard {
+		mutex: HANDLE,
+		/// The first instance of the activation pipe, which reserves its name.
+		pipe: Option<File>,
+	}
+
+	impl Drop for Guard {
+		fn drop(&mut self) {
+			// Give up the pipe before the mutex, so the next launch to take
+			// the mutex can also create the pipe.
+			drop(self.pipe.take());
+			unsafe {
+				let _ = CloseHandle(self.mutex);
+			}
+		}
+	}
+
+	/// Pipe names are machine-wide, unlike the session-local mutex, so the
+	/// session keeps a second logon of the same user apart.
+	fn pipe_name() -> String {
+		use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+		use windows::Win32::System::Threading::GetCurrentProcessId;
+
+		let mut session = 0;
+		let _ = unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session) };
+		let user = std::env::var("USERNAME").unwrap_or_default();
+		format!("\\\\.\\pipe\\NetFlux.Activation.{}.{}", session, user)
+	}
+
+	/// A new instance of the activation pipe for local clients. Only the
+	/// `first` may create the name; that fails if anyone else holds it.
+	fn create_pipe(first: bool) -> std::io::Result<File> {
+		use windows::Win32::Storage::FileSystem::{
+			FILE_FLAG_FIRST_PIPE_INSTANCE,
+			PIPE_ACCESS_DUPLEX,
+		};
+		use windows::Win32::System::Pipes::{
+			CreateNamedPipeW,
+			PIPE_TYPE_BYTE,
+			PIPE_READMODE_BYTE,
+			PIPE_REJECT_REMOTE_CLIENTS,
+			PIPE_WAIT,
+			PIPE_UNLIMITED_INSTANCES,
+		};
+
+		let name = wide_string(&pipe_name());
+		let mut access = PIPE_ACCESS_DUPLEX;
+		if first {
+			access |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+		}
+		let pipe = unsafe {
+			CreateNamedPipeW(
+				PCWSTR::from_raw(name.as_ptr()),
+				access,
+				PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+				PIPE_UNLIMITED_INSTANCES,
+				4096,
+				4096,
+				0,
+				None
+			)
+		};
+		if pipe.is_invalid() {
+			return Err(std::io::Error::last_os_error());
+		}
+		// The File takes ownership of the handle and closes it on drop.
+		Ok(unsafe { File::from_raw_handle(pipe.0) })
+	}
+
+	pub fn acquire() -> Result<Option<Guard>, String> {
+		unsafe {
+			let name = wide_string(MUTEX_NAME);
+			let mutex = CreateMutexW(None, true, PCWSTR::from_raw(name.as_ptr())).map_err(|e|
+				e.to_string()
+			)?;
+
+			if GetLastError() == ERROR_ALREADY_EXISTS {
+				let _ = CloseHandle(mutex);
+				return Ok(None);
+			}
+
+			// Whoever already has the pipe would get every forwarded launch,
+			// so this one is not the first after all.
+			match create_pipe(true) {
+				Ok(pipe) => Ok(Some(Guard { mutex, pipe: Some(pipe) })),
+				Err(_) => {
+					let _ = CloseHandle(mutex);
+					Ok(None)
+				}
+			}
+		}
+	}
+
+	#[cfg(tray)]
+	pub fn listen<F>(guard: &mut Guard, on_message: F) -> Result<(), String>
+		where F: Fn(Vec<String>) -> Result<(), String> + Send + 'static
+	{
+		use std::os::windows::io::AsRawHandle;
+		use windows::Win32::Foundation::ERROR_PIPE_CONNECTED;
+		use windows::Win32::System::Pipes::ConnectNamedPipe;
+
+		let mut pipe = guard.pipe.take().ok_or("Already listening")?;
+		std::thread::spawn(move || {
+			loop {
+				let handle = HANDLE(pipe.as_raw_handle());
+				let connected = match unsafe { ConnectNamedPipe(handle, None) } {
+					Ok(()) => true,
+					Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+				};
+				if connected && super::serve(&pipe, &on_message).is_ok() {
+					// Closing discards a reply the client has not read yet.
+					let _ = pipe.sync_all();
+				}
+				// The next launch gets a fresh instance; dropping this one
+				// disconnects the last.
+				pipe = match create_pipe(false) {
+					Ok(next) => next,
+					Err(_) => {
+						return;
+					}
+				};
+			}
+		});
+		Ok(())
+	}
+
+	pub fn connect() -> Result<File, String> {
+		OpenOptions::new()
+			.read(true)
+			.write(true)
+			.open(pipe_name())
+			.map_err(|e| e.to_string())
+	}
+
+	fn wide_string(s: &str) -> Vec<u16> {
+		s.encode_utf16().chain(std::iter::once(0)).collect()
+	}
+}
+
+#[cfg(unix)]
+mod platform {
+	use std::fs::{ DirBuilder, File, OpenOptions, TryLockError };
+	use std::io::ErrorKind;
+	use std::os::unix::fs::{ DirBuilderExt, MetadataExt };
+	use std::os::unix::net::UnixStream;
+	use std::path::{ Path, PathBuf };
+
+	/// How long the first instance waits for a connected launch to send its
+	/// request before moving on to the next.
+	#[cfg(any(tray, test))]
+	const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+	pub struct Guard {
+		_lock: File,
+		/// Where the socket goes once the instance listens.
+		#[cfg(any(tray, test))]
+		dir: PathBuf,
+	}
+
+	fn runtime_dir() -> Result<PathBuf, String> {
+		match std::env::var_os("XDG_RUNTIME_DIR") {
+			Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+			_ => {
+				let user = std::env::var("USER").unwrap_or_default();
+				let dir = std::env::temp_dir().join(format!("netflux-{}", user));
+				private_dir(&dir)?;
+				Ok(dir)
+			}
+		}
+	}
+
+	/// Creates `dir` for this user alone, or checks that the one already
+	/// there is, since anyone can create names in the shared temp directory.
+	pub(super) fn private_dir(dir: &Path) -> Result<(), String> {
+		match DirBuilder::new().mode(0o700).create(dir) {
+			Ok(()) => {}
+			Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+			Err(e) => {
+				return Err(format!("{}: {}", dir.display(), e));
+			}
+		}
+		let meta = std::fs::symlink_metadata(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+		if !meta.is_dir() || meta.uid() != unsafe { libc::geteuid() } {
+			return Err(format!("{} is not a directory owned by this user", dir.display()));
+		}
+		if meta.mode() & 0o077 != 0 {
+			return Err(format!("{} is open to other users", dir.display()));
+		}
+		Ok(())
+	}
+
+	pub fn acquire() -> Result<Option<Guard>, String> {
+		acquire_in(&runtime_dir()?)
+	}
+
+	pub(super) fn acquire_in(dir: &Path) -> Result<Option<Guard>, String> {
+		std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+		let lock = OpenOptions::new()
+			.create(true)
+			.truncate(false)
+			.write(true)
+			.open(dir.join("netflux.lock"))
+			.map_err(|e| e.to_string())?;
+
+		match lock.try_lock() {
+			Ok(()) => Ok(Some(Guard {
+				_lock: lock,
+				#[cfg(any(tray, test))]
+				dir: dir.to_path_buf(),
+			})),
+			Err(TryLockError::WouldBlock) => Ok(None),
+			Err(TryLockError::Error(e)) => Err(e.to_string()),
+		}
+	}
+
+	#[cfg(any(tray, test))]
+	pub fn listen<F>(guard: &mut Guard, on_message: F) -> Result<(), String>
+		where F: Fn(Vec<String>) -> Result<(), String> + Send + 'static
+	{
+		use std::os::unix::net::UnixListener;
+
+		// Holding the lock means any socket left behind belongs to a dead process.
+		let path = guard.dir.join("netflux.sock");
+		let _ = std::fs::remove_file(&path);
+		let listener = UnixListener::bind(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+		std::thread::spawn(move || {
+			for stream in listener.incoming().flatten() {
+				if stream.set_read_timeout(Some(REQUEST_TIMEOUT)).is_ok() {
+					let _ = super::serve(stream, &on_message);
+				}
+			}
+		});
+		Ok(())
+	}
+
+	pub fn connect() -> Result<UnixStream, String> {
+		connect_in(&runtime_dir()?)
+	}
+
+	pub(super) fn connect_in(dir: &Path) -> Result<UnixStream, String> {
+		UnixStream::connect(dir.join("netflux.sock")).map_err(|e| e.to_string())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args(list: &[&str]) -> Vec<String> {
+		list.iter().map(|arg| arg.to_string()).collect()
+	}
+
+	#[test]
+	fn messages_round_trip() {
+		let forwarded = args(&["--show", "export-graph", "-o", "C:\\graphs\\a b.png"]);
+		assert_eq!(encode(&forwarded), b"--show\0export-graph\0-o\0C:\\graphs\\a b.png");
+		assert_eq!(decode(&encode(&forwarded)), forwarded);
+		// A bare second launch sends nothing and gets no arguments back.
+		assert_eq!(encode(&[]), b"");
+		assert!(decode(b"").is_empty());
+		assert_eq!(decode(&encode(&args(&["", "x"]))), args(&["", "x"]));
+	}
+
+	#[test]
+	fn replies_round_trip() {
+		assert_eq!(decode_reply(&encode_reply(&Ok(()))), Ok(()));
+		let failed = Err("C:\\graphs: Access is denied.".to_string());
+		assert_eq!(decode_reply(&encode_reply(&failed)), failed);
+		assert!(decode_reply(b"").is_err());
+	}
+
+	#[test]
+	fn frames_carry_their_length() {
+		let mut wire = Vec::new();
+		write_frame(&mut wire, b"one").unwrap();
+		write_frame(&mut wire, b"").unwrap();
+		assert_eq!(wire, b"\x03\0\0\0one\0\0\0\0");
+
+		let mut reader = wire.as_slice();
+		assert_eq!(read_frame(&mut reader).unwrap(), b"one");
+		assert_eq!(read_frame(&mut reader).unwrap(), b"");
+		assert!(read_frame(&mut reader).is_err());
+
+		let oversized = ((MAX_FRAME + 1) as u32).to_le_bytes();
+		assert!(read_frame(&mut oversized.as_slice()).is_err());
+	}
+
+	#[cfg(unix)]
+	fn test_dir(name: &str) -> std::path::PathBuf {
+		let dir = std::env::temp_dir().join(format!("netflux-{}-{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+		dir
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn second_instance_forwards_to_the_first_until_it_exits() {
+		use std::sync::mpsc;
+		use std::time::Duration;
+
+		let dir = test_dir("instance");
+		let mut first = platform::acquire_in(&dir)
+			.unwrap()
+			.expect("the first launch holds the lock");
+		assert!(platform::acquire_in(&dir).unwrap().is_none());
+
+		let (sender, received) = mpsc::channel();
+		let listening = platform::listen(&mut first, move |args| {
+			let reply = if args.is_empty() { Err("nothing to do".to_string()) } else { Ok(()) };
+			sender.send(args).unwrap();
+			reply
+		});
+		listening.unwrap();
+		let forward = |list: &[&str]| exchange(platform::connect_in(&dir).unwrap(), &args(list));
+		assert_eq!(forward(&["--mode", "upload"]), Ok(()));
+		let forwarded = received.recv_timeout(Duration::from_secs(5)).unwrap();
+		assert_eq!(forwarded, args(&["--mode", "upload"]));
+		// The first instance's answer makes it back to the second.
+		assert_eq!(forward(&[]), Err("nothing to do".to_string()));
+
+		// Once the first instance is gone, the next launch takes over.
+		drop(first);
+		assert!(platform::acquire_in(&dir).unwrap().is_some());
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn fallback_directory_is_private() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let dir = test_dir("private");
+		platform::private_dir(&dir).unwrap();
+		let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+		assert_eq!(mode & 0o777, 0o700);
+		platform::private_dir(&dir).unwrap();
+
+		std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+		assert!(platform::private_dir(&dir).is_err());
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
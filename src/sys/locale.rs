@@ -0,0 +1,34 @@
+/// The user's preferred locale as a BCP 47 tag such as `de-DE`, if the OS
+/// reports one.
+#[cfg(windows)]
+pub fn system_locale() -> Option<String> {
+	use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+	// LOCALE_NAME_MAX_LENGTH
+	let mut buffer = [0u16; 85];
+	let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+	if len <= 1 {
+		return None;
+	}
+	Some(String::from_utf16_lossy(&buffer[..(len as usize) - 1]))
+}
+
+#[cfg(not(windows))]
+pub fn system_locale() -> Option<String> {
+	["LC_ALL", "LC_MESSAGES", "LANG"]
+		.iter()
+		.filter_map(|name| std::env::var(name).ok())
+		.find(|value| !value.is_empty())
+		.and_then(|value| posix_to_bcp47(&value))
+}
+
+/// `de_DE.UTF-8@euro` becomes `de-DE`; the `C` and `POSIX` locales carry no
+/// language at all.
+#[cfg(not(windows))]
+fn posix_to_bcp47(value: &str) -> Option<String> {
+	let tag = value.split(['.', '@']).next()?;
+	if tag.is_empty() || tag == "C" || tag == "POSIX" {
+		return None;
+	}
+	Some(tag.replace('_', "-"))
+}
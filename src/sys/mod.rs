@@ -1,2 +1,18 @@
-pub mod gdi;
+pub mod autostart;
+#[cfg(any(tray, test))]
+pub mod clock;
+#[cfg(windows)]
+pub mod console;
+#[cfg(tray)]
+pub mod dialog;
+#[cfg(tray)]
+pub mod display;
+pub mod instance;
+pub mod locale;
+#[cfg(any(tray, feature = "tui", test))]
+pub mod netinfo;
+pub mod paths;
+#[cfg(windows)]
 pub mod registry;
+#[cfg(target_os = "linux")]
+pub mod xdg;
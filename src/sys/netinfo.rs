@@ -0,0 +1,490 @@
+use std::net::{ IpAddr, Ipv4Addr, Ipv6Addr };
+use std::time::Duration;
+
+/// What kind of link an interface is, as far as the OS tells.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Media {
+	Ethernet,
+	WiFi,
+	Mobile,
+	Ppp,
+	Tunnel,
+	Other,
+}
+
+/// Configuration of one network interface, for the popup's details section.
+/// Anything the OS does not report is left empty.
+#[derive(PartialEq, Clone, Debug)]
+pub struct InterfaceDetails {
+	/// The name shown to users, e.g. `Wi-Fi` or `enp3s0`.
+	pub alias: String,
+	/// Adapter model on Windows, driver name on Linux.
+	pub description: String,
+	pub mac: Option<String>,
+	pub ipv4: Vec<Ipv4Addr>,
+	pub ipv6: Vec<Ipv6Addr>,
+	pub gateways: Vec<IpAddr>,
+	pub dns: Vec<IpAddr>,
+	/// Negotiated speed in bits per second.
+	pub link_speed: Option<u64>,
+	pub media: Media,
+	pub mtu: Option<u32>,
+	/// Time since the link last came up.
+	pub uptime: Option<Duration>,
+}
+
+/// Looks up the interface `alias`, as reported in `NetStats::interface_name`.
+#[cfg(all(windows, any(tray, feature = "tui")))]
+pub fn interface_details(alias: &str) -> Option<InterfaceDetails> {
+	use windows::Win32::Foundation::{ ERROR_BUFFER_OVERFLOW, NO_ERROR };
+	use windows::Win32::NetworkManagement::IpHelper::{
+		GetAdaptersAddresses,
+		GAA_FLAG_INCLUDE_GATEWAYS,
+		GAA_FLAG_SKIP_ANYCAST,
+		GAA_FLAG_SKIP_MULTICAST,
+		IP_ADAPTER_ADDRESSES_LH,
+	};
+	use windows::Win32::Networking::WinSock::AF_UNSPEC;
+
+	let flags = GAA_FLAG_INCLUDE_GATEWAYS | GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST;
+	// The documented starting size; grown once if the call asks for more.
+	let mut size: u32 = 15_000;
+	let mut buffer: Vec<u64>;
+	loop {
+		// u64 elements keep the list aligned for the structs written into it.
+		buffer = vec![0; (size as usize).div_ceil(8)];
+		let first = buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH;
+		let result = unsafe {
+			GetAdaptersAddresses(AF_UNSPEC.0 as u32, flags, None, Some(first), &mut size)
+		};
+		match result {
+			r if r == NO_ERROR.0 => break,
+			r if r == ERROR_BUFFER_OVERFLOW.0 => continue,
+			_ => return None,
+		}
+	}
+
+	let mut adapter = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+	while let Some(row) = unsafe { adapter.as_ref() } {
+		if unsafe { row.FriendlyName.to_string() }.ok().as_deref() == Some(alias) {
+			return Some(unsafe { adapter_details(row) });
+		}
+		adapter = row.Next;
+	}
+	None
+}
+
+#[cfg(all(windows, any(tray, feature = "tui")))]
+unsafe fn adapter_details(
+	row: &windows::Win32::NetworkManagement::IpHelper::IP_ADAPTER_ADDRESSES_LH
+) -> InterfaceDetails {
+	use windows::Win32::NetworkManagement::IpHelper::{
+		IF_TYPE_ETHERNET_CSMACD,
+		IF_TYPE_IEEE80211,
+		IF_TYPE_PPP,
+		IF_TYPE_TUNNEL,
+		IF_TYPE_WWANPP,
+		IF_TYPE_WWANPP2,
+	};
+
+	let mac = &row.PhysicalAddress[..(row.PhysicalAddressLength as usize).min(8)];
+	let mac = (!mac.is_empty()).then(|| {
+		mac.iter()
+			.map(|byte| format!("{:02X}", byte))
+			.collect::<Vec<_>>()
+			.join("-")
+	});
+
+	let mut ipv4 = Vec::new();
+	let mut ipv6 = Vec::new();
+	let mut unicast = row.FirstUnicastAddress;
+	while let Some(address) = unicast.as_ref() {
+		match socket_address(&address.Address) {
+			Some(IpAddr::V4(ip)) => ipv4.push(ip),
+			Some(IpAddr::V6(ip)) => ipv6.push(ip),
+			None => {}
+		}
+		unicast = address.Next;
+	}
+
+	let mut gateways = Vec::new();
+	let mut gateway = row.FirstGatewayAddress;
+	while let Some(address) = gateway.as_ref() {
+		gateways.extend(socket_address(&address.Address));
+		gateway = address.Next;
+	}
+
+	let mut dns = Vec::new();
+	let mut server = row.FirstDnsServerAddress;
+	while let Some(address) = server.as_ref() {
+		dns.extend(socket_address(&address.Address));
+		server = address.Next;
+	}
+
+	let media = match row.IfType {
+		IF_TYPE_ETHERNET_CSMACD => Media::Ethernet,
+		IF_TYPE_IEEE80211 => Media::WiFi,
+		IF_TYPE_WWANPP | IF_TYPE_WWANPP2 => Media::Mobile,
+		IF_TYPE_PPP => Media::Ppp,
+		IF_TYPE_TUNNEL => Media::Tunnel,
+		_ => Media::Other,
+	};
+	// Unknown speeds are reported as all ones.
+	let link_speed = row.TransmitLinkSpeed.max(row.ReceiveLinkSpeed);
+	let link_speed = (link_speed != 0 && link_speed != u64::MAX).then_some(link_speed);
+
+	InterfaceDetails {
+		alias: row.FriendlyName.to_string().unwrap_or_default(),
+		description: row.Description.to_string().unwrap_or_default(),
+		mac,
+		ipv4,
+		ipv6,
+		gateways,
+		dns,
+		link_speed,
+		media,
+		mtu: (row.Mtu != u32::MAX).then_some(row.Mtu),
+		uptime: uptime(row.Anonymous1.Anonymous.IfIndex),
+	}
+}
+
+#[cfg(all(windows, any(tray, feature = "tui")))]
+unsafe fn socket_address(
+	address: &windows::Win32::Networking::WinSock::SOCKET_ADDRESS
+) -> Option<IpAddr> {
+	use windows::Win32::Networking::WinSock::{ AF_INET, AF_INET6, SOCKADDR_IN, SOCKADDR_IN6 };
+
+	let sockaddr = address.lpSockaddr.as_ref()?;
+	match sockaddr.sa_family {
+		AF_INET => {
+			let v4 = &*(address.lpSockaddr as *const SOCKADDR_IN);
+			// S_addr is in network byte order.
+			Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(v4.sin_addr.S_un.S_addr))))
+		}
+		AF_INET6 => {
+			let v6 = &*(address.lpSockaddr as *const SOCKADDR_IN6);
+			Some(IpAddr::V6(Ipv6Addr::from(v6.sin6_addr.u.Byte)))
+		}
+		_ => None,
+	}
+}
+
+/// Time since the interface entered its current state. `dwLastChange` is
+/// in hundredths of a second since boot.
+#[cfg(all(windows, any(tray, feature = "tui")))]
+unsafe fn uptime(index: u32) -> Option<Duration> {
+	use windows::Win32::Foundation::NO_ERROR;
+	use windows::Win32::NetworkManagement::IpHelper::{ GetIfEntry, MIB_IFROW };
+	use windows::Win32::System::SystemInformation::GetTickCount64;
+
+	let mut row = MIB_IFROW { dwIndex: index, ..Default::default() };
+	if GetIfEntry(&mut row) != NO_ERROR.0 {
+		return None;
+	}
+	let changed_ms = (row.dwLastChange as u64) * 10;
+	GetTickCount64().checked_sub(changed_ms).map(Duration::from_millis)
+}
+
+/// With systemd-resolved, `/etc/resolv.conf` only names its local stub
+/// resolver; the upstream servers are listed under `/run`.
+#[cfg(all(target_os = "linux", feature = "tui"))]
+const RESOLV_CONF_PATHS: [&str; 2] = ["/run/systemd/resolve/resolv.conf", "/etc/resolv.conf"];
+
+/// Reads sysfs, `getifaddrs` (netlink underneath), the kernel routing
+/// tables and the resolver configuration. Linux keeps no timestamp for when
+/// a link came up, so `uptime` stays empty for the caller to fill in.
+#[cfg(all(target_os = "linux", feature = "tui"))]
+pub fn interface_details(name: &str) -> Option<InterfaceDetails> {
+	use std::path::Path;
+
+	let resolv_conf = RESOLV_CONF_PATHS.map(Path::new);
+	let (class_net, proc_net) = (Path::new("/sys/class/net"), Path::new("/proc/net"));
+	let mut details = read_details(name, class_net, proc_net, &resolv_conf)?;
+	(details.ipv4, details.ipv6) = addresses(name);
+	Some(details)
+}
+
+/// Everything but the addresses, from the sysfs directory `class_net`, the
+/// procfs directory `proc_net` and the first readable of `resolv_conf`.
+#[cfg(any(all(target_os = "linux", feature = "tui"), all(unix, test)))]
+fn read_details(
+	name: &str,
+	class_net: &std::path::Path,
+	proc_net: &std::path::Path,
+	resolv_conf: &[&std::path::Path]
+) -> Option<InterfaceDetails> {
+	use std::fs;
+
+	let sys = class_net.join(name);
+	if !sys.exists() {
+		return None;
+	}
+	let read = |file: &str| {
+		fs::read_to_string(sys.join(file))
+			.ok()
+			.map(|text| text.trim().to_string())
+			.filter(|text| !text.is_empty())
+	};
+
+	let description = fs::read_link(sys.join("device/driver"))
+		.ok()
+		.and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+		.unwrap_or_default();
+	let wireless = sys.join("wireless").exists() || sys.join("phy80211").exists();
+	let media = media(wireless, read("type").and_then(|kind| kind.parse().ok()).unwrap_or(0));
+
+	let mut gateways: Vec<IpAddr> = fs::read_to_string(proc_net.join("route"))
+		.map(|text| parse_ipv4_gateways(&text, name).into_iter().map(IpAddr::V4).collect())
+		.unwrap_or_default();
+	if let Ok(text) = fs::read_to_string(proc_net.join("ipv6_route")) {
+		gateways.extend(parse_ipv6_gateways(&text, name).into_iter().map(IpAddr::V6));
+	}
+
+	Some(InterfaceDetails {
+		alias: read("ifalias").unwrap_or_else(|| name.to_string()),
+		description,
+		mac: read("address").filter(|mac| mac != "00:00:00:00:00:00"),
+		ipv4: Vec::new(),
+		ipv6: Vec::new(),
+		gateways,
+		dns: resolv_conf
+			.iter()
+			.find_map(|path| fs::read_to_string(path).ok())
+			.map(|text| parse_resolv_conf(&text))
+			.unwrap_or_default(),
+		// Mbit/s, or -1 when the driver cannot tell (most Wi-Fi).
+		link_speed: read("speed")
+			.and_then(|speed| speed.parse::<i64>().ok())
+			.filter(|&speed| speed > 0)
+			.map(|speed| (speed as u64) * 1_000_000),
+		media,
+		mtu: read("mtu").and_then(|mtu| mtu.parse().ok()),
+		uptime: None,
+	})
+}
+
+#[cfg(all(target_os = "linux", feature = "tui"))]
+fn addresses(name: &str) -> (Vec<Ipv4Addr>, Vec<Ipv6Addr>) {
+	let (mut ipv4, mut ipv6) = (Vec::new(), Vec::new());
+	unsafe {
+		let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+		if libc::getifaddrs(&mut list) != 0 {
+			return (ipv4, ipv6);
+		}
+		let mut entry = list;
+		while let Some(ifa) = entry.as_ref() {
+			entry = ifa.ifa_next;
+			let Some(address) = ifa.ifa_addr.as_ref() else {
+				continue;
+			};
+			if std::ffi::CStr::from_ptr(ifa.ifa_name).to_bytes() != name.as_bytes() {
+				continue;
+			}
+			match address.sa_family as i32 {
+				libc::AF_INET => {
+					let v4 = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+					ipv4.push(Ipv4Addr::from(u32::from_be(v4.sin_addr.s_addr)));
+				}
+				libc::AF_INET6 => {
+					let v6 = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+					ipv6.push(Ipv6Addr::from(v6.sin6_addr.s6_addr));
+				}
+				_ => {}
+			}
+		}
+		libc::freeifaddrs(list);
+	}
+	(ipv4, ipv6)
+}
+
+/// The media of a link with the `ARPHRD_*` type `kind`; wireless links
+/// report plain Ethernet.
+#[cfg(any(all(target_os = "linux", feature = "tui"), test))]
+fn media(wireless: bool, kind: u32) -> Media {
+	if wireless {
+		return Media::WiFi;
+	}
+	match kind {
+		// ARPHRD_ETHER
+		1 => Media::Ethernet,
+		// ARPHRD_PPP
+		512 => Media::Ppp,
+		// ARPHRD_TUNNEL, ARPHRD_TUNNEL6, ARPHRD_SIT, ARPHRD_NONE (WireGuard, tun)
+		768 | 769 | 776 | 65534 => Media::Tunnel,
+		// ARPHRD_RAWIP, used by mobile broadband modems
+		519 => Media::Mobile,
+		_ => Media::Other,
+	}
+}
+
+/// Default-route gateways of `name` from `/proc/net/route`, which prints
+/// each network-order address as a native-endian hex word.
+#[cfg(any(all(target_os = "linux", feature = "tui"), test))]
+fn parse_ipv4_gateways(table: &str, name: &str) -> Vec<Ipv4Addr> {
+	const RTF_GATEWAY: u32 = 0x2;
+	table
+		.lines()
+		.skip(1)
+		.filter_map(|line| {
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			let (iface, destination, gateway, flags) = (
+				*fields.first()?,
+				*fields.get(1)?,
+				*fields.get(2)?,
+				*fields.get(3)?,
+			);
+			let flags = u32::from_str_radix(flags, 16).ok()?;
+			if iface != name || destination != "00000000" || flags & RTF_GATEWAY == 0 {
+				return None;
+			}
+			let gateway = u32::from_str_radix(gateway, 16).ok()?;
+			Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+		})
+		.collect()
+}
+
+/// Default-route next hops of `name` from `/proc/net/ipv6_route`.
+#[cfg(any(all(target_os = "linux", feature = "tui"), test))]
+fn parse_ipv6_gateways(table: &str, name: &str) -> Vec<Ipv6Addr> {
+	table
+		.lines()
+		.filter_map(|line| {
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.len() < 10 || fields[9] != name || fields[1] != "00" {
+				return None;
+			}
+			let next_hop = u128::from_str_radix(fields[4], 16).ok()?;
+			(next_hop != 0).then(|| Ipv6Addr::from(next_hop))
+		})
+		.collect()
+}
+
+#[cfg(any(all(target_os = "linux", feature = "tui"), test))]
+fn parse_resolv_conf(text: &str) -> Vec<IpAddr> {
+	text.lines()
+		.filter_map(|line| {
+			let mut words = line.split_whitespace();
+			if words.next()? != "nameserver" {
+				return None;
+			}
+			words.next()
+		})
+		// A scoped IPv6 server such as `fe80::1%eth0` keeps its zone apart.
+		.filter_map(|address| address.split('%').next()?.parse().ok())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ipv4_default_gateway_comes_from_the_route_table() {
+		let table = "\
+Iface	Destination	Gateway 	Flags	RefCnt	Use	Metric	Mask		MTU	Window	IRTT
+wlan0	00000000	0102A8C0	0003	0	0	600	00000000	0	0	0
+wlan0	0002A8C0	00000000	0001	0	0	600	00FFFFFF	0	0	0
+eth0	00000000	FE01000A	0003	0	0	100	00000000	0	0	0
+";
+		assert_eq!(parse_ipv4_gateways(table, "wlan0"), vec![Ipv4Addr::new(192, 168, 2, 1)]);
+		assert_eq!(parse_ipv4_gateways(table, "eth0"), vec![Ipv4Addr::new(10, 0, 1, 254)]);
+		assert!(parse_ipv4_gateways(table, "lo").is_empty());
+	}
+
+	#[test]
+	fn ipv6_default_gateway_comes_from_the_route_table() {
+		let table = "\
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003  wlan0
+fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001  wlan0
+";
+		assert_eq!(parse_ipv6_gateways(table, "wlan0"), vec!["fe80::1".parse::<Ipv6Addr>().unwrap()]);
+		assert!(parse_ipv6_gateways(table, "eth0").is_empty());
+	}
+
+	#[test]
+	fn media_follows_the_link_type() {
+		assert_eq!(media(true, 1), Media::WiFi);
+		assert_eq!(media(false, 1), Media::Ethernet);
+		assert_eq!(media(false, 512), Media::Ppp);
+		assert_eq!(media(false, 65534), Media::Tunnel);
+		assert_eq!(media(false, 519), Media::Mobile);
+		assert_eq!(media(false, 772), Media::Other);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn details_come_from_sysfs_procfs_and_resolv_conf() {
+		use std::fs;
+
+		let root = std::env::temp_dir().join(format!("netflux-netinfo-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&root);
+		let class_net = root.join("sys/class/net");
+		let driver = root.join("sys/bus/pci/drivers/e1000e");
+		fs::create_dir_all(&driver).unwrap();
+		fs::create_dir_all(class_net.join("eth0/device")).unwrap();
+		std::os::unix::fs::symlink(&driver, class_net.join("eth0/device/driver")).unwrap();
+		fs::create_dir_all(class_net.join("wlan0/wireless")).unwrap();
+		let files = [
+			("eth0/address", "52:54:00:12:34:56\n"),
+			("eth0/type", "1\n"),
+			("eth0/speed", "1000\n"),
+			("eth0/mtu", "1500\n"),
+			("eth0/ifalias", "\n"),
+			("wlan0/address", "00:00:00:00:00:00\n"),
+			("wlan0/type", "1\n"),
+			("wlan0/speed", "-1\n"),
+			("wlan0/ifalias", "Home Wi-Fi\n"),
+		];
+		for (file, text) in files {
+			fs::write(class_net.join(file), text).unwrap();
+		}
+		let proc_net = root.join("proc/net");
+		fs::create_dir_all(&proc_net).unwrap();
+		fs::write(
+			proc_net.join("route"),
+			"Iface\tDestination\tGateway\tFlags\nwlan0\t00000000\t0102A8C0\t0003\n"
+		).unwrap();
+		fs::write(proc_net.join("resolv.conf"), "nameserver 192.168.2.1\n").unwrap();
+		// The systemd-resolved copy is missing, so the next one is read.
+		let resolv_conf = [root.join("run/resolv.conf"), proc_net.join("resolv.conf")];
+		let resolv_conf = resolv_conf.each_ref().map(|path| path.as_path());
+
+		let eth0 = read_details("eth0", &class_net, &proc_net, &resolv_conf).unwrap();
+		assert_eq!(eth0.alias, "eth0");
+		assert_eq!(eth0.description, "e1000e");
+		assert_eq!(eth0.mac.as_deref(), Some("52:54:00:12:34:56"));
+		assert_eq!(eth0.media, Media::Ethernet);
+		assert_eq!(eth0.link_speed, Some(1_000_000_000));
+		assert_eq!(eth0.mtu, Some(1500));
+		assert!(eth0.gateways.is_empty());
+		assert_eq!(eth0.dns, vec!["192.168.2.1".parse::<IpAddr>().unwrap()]);
+
+		let wlan0 = read_details("wlan0", &class_net, &proc_net, &resolv_conf).unwrap();
+		assert_eq!(wlan0.alias, "Home Wi-Fi");
+		assert_eq!(wlan0.description, "");
+		assert_eq!(wlan0.mac, None);
+		assert_eq!(wlan0.media, Media::WiFi);
+		assert_eq!(wlan0.link_speed, None);
+		assert_eq!(wlan0.mtu, None);
+		assert_eq!(wlan0.gateways, vec![IpAddr::V4(Ipv4Addr::new(192, 168, 2, 1))]);
+
+		assert_eq!(read_details("eth1", &class_net, &proc_net, &resolv_conf), None);
+		let _ = fs::remove_dir_all(&root);
+	}
+
+	#[test]
+	fn resolv_conf_lists_nameservers() {
+		let text = "\
+# Generated by NetworkManager
+search lan
+nameserver 192.168.2.1
+nameserver fe80::1%wlan0
+nameserver not-an-address
+options edns0
+";
+		assert_eq!(parse_resolv_conf(text), vec![
+			"192.168.2.1".parse::<IpAddr>().unwrap(),
+			"fe80::1".parse::<IpAddr>().unwrap(),
+		]);
+	}
+}
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+/// Per-user configuration root: `%APPDATA%` on Windows, `$XDG_CONFIG_HOME`
+/// (or `~/.config`) elsewhere.
+pub fn config_home() -> Option<PathBuf> {
+	#[cfg(windows)]
+	let dir = std::env::var_os("APPDATA").filter(|dir| !dir.is_empty()).map(PathBuf::from);
+
+	#[cfg(not(windows))]
+	let dir = match std::env::var_os("XDG_CONFIG_HOME") {
+		Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+		_ => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+	};
+
+	dir
+}
+
+pub fn app_config_dir() -> Option<PathBuf> {
+	#[cfg(windows)]
+	let name = "NetFlux";
+	#[cfg(not(windows))]
+	let name = "netflux";
+
+	config_home().map(|dir| dir.join(name))
+}
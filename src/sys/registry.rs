@@ -1,46 +1,95 @@
 use windows::Win32::System::Registry::{
 	RegCreateKeyExW,
 	RegSetValueExW,
+	RegDeleteValueW,
+	RegGetValueW,
+	RegCloseKey,
 	HKEY_CURRENT_USER,
 	KEY_WRITE,
 	REG_SZ,
 	REG_OPTION_NON_VOLATILE,
+	RRF_RT_REG_SZ,
 	HKEY,
 };
+use windows::Win32::Foundation::{ ERROR_FILE_NOT_FOUND, ERROR_SUCCESS };
 use windows::core::PCWSTR;
 
-pub fn enable_autostart() {
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+const VALUE_NAME: &str = "NetFlux";
+
+pub fn enable_autostart() -> Result<(), String> {
+	let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+	let path_str = exe_path.to_str().ok_or("Executable path is not valid UTF-8")?;
+	let path_wide = wide_string(&format!("\"{}\"", path_str));
+
 	unsafe {
-		let mut hkey = HKEY::default();
-		let subkey = wide_string("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
-
-		if
-			RegCreateKeyExW(
-				HKEY_CURRENT_USER,
-				PCWSTR::from_raw(subkey.as_ptr()),
-				0,
-				None,
-				REG_OPTION_NON_VOLATILE,
-				KEY_WRITE,
-				None,
-				&mut hkey,
-				None
-			).is_ok()
-		{
-			let exe_path = std::env::current_exe().unwrap_or_default();
-			let path_str = exe_path.to_str().unwrap_or_default();
-			let path_wide = wide_string(path_str);
-
-			let val_name = wide_string("NetFlux");
-			let _ = RegSetValueExW(
-				hkey,
-				PCWSTR::from_raw(val_name.as_ptr()),
-				0,
-				REG_SZ,
-				Some(std::slice::from_raw_parts(path_wide.as_ptr() as *const u8, path_wide.len() * 2))
-			);
+		let hkey = open_run_key()?;
+		let val_name = wide_string(VALUE_NAME);
+		let result = RegSetValueExW(
+			hkey,
+			PCWSTR::from_raw(val_name.as_ptr()),
+			0,
+			REG_SZ,
+			Some(std::slice::from_raw_parts(path_wide.as_ptr() as *const u8, path_wide.len() * 2))
+		);
+		let _ = RegCloseKey(hkey);
+
+		if result != ERROR_SUCCESS {
+			return Err(format!("RegSetValueExW failed: {}", result.0));
+		}
+	}
+	Ok(())
+}
+
+pub fn disable_autostart() -> Result<(), String> {
+	unsafe {
+		let hkey = open_run_key()?;
+		let val_name = wide_string(VALUE_NAME);
+		let result = RegDeleteValueW(hkey, PCWSTR::from_raw(val_name.as_ptr()));
+		let _ = RegCloseKey(hkey);
+
+		if result != ERROR_SUCCESS && result != ERROR_FILE_NOT_FOUND {
+			return Err(format!("RegDeleteValueW failed: {}", result.0));
 		}
 	}
+	Ok(())
+}
+
+pub fn is_autostart_enabled() -> bool {
+	unsafe {
+		let subkey = wide_string(RUN_KEY);
+		let val_name = wide_string(VALUE_NAME);
+		RegGetValueW(
+			HKEY_CURRENT_USER,
+			PCWSTR::from_raw(subkey.as_ptr()),
+			PCWSTR::from_raw(val_name.as_ptr()),
+			RRF_RT_REG_SZ,
+			None,
+			None,
+			None
+		) == ERROR_SUCCESS
+	}
+}
+
+unsafe fn open_run_key() -> Result<HKEY, String> {
+	let mut hkey = HKEY::default();
+	let subkey = wide_string(RUN_KEY);
+
+	RegCreateKeyExW(
+		HKEY_CURRENT_USER,
+		PCWSTR::from_raw(subkey.as_ptr()),
+		0,
+		None,
+		REG_OPTION_NON_VOLATILE,
+		KEY_WRITE,
+		None,
+		&mut hkey,
+		None
+	)
+		.ok()
+		.map_err(|e| e.to_string())?;
+
+	Ok(hkey)
 }
 
 fn wide_string(s: &str) -> Vec<u16> {
@@ -0,0 +1,259 @@
+use std::fs;
+use std::io;
+use std::path::{ Path, PathBuf };
+use crate::sys::paths;
+
+const DESKTOP_FILE: &str = "netflux.desktop";
+const UNIT_FILE: &str = "netflux.service";
+const UNIT_TARGET: &str = "graphical-session.target";
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum AutostartMethod {
+	DesktopEntry,
+	SystemdUnit,
+}
+
+pub struct XdgAutostart {
+	config_dir: PathBuf,
+	exe_path: PathBuf,
+}
+
+impl XdgAutostart {
+	pub fn new(config_dir: PathBuf, exe_path: PathBuf) -> Self {
+		Self { config_dir, exe_path }
+	}
+
+	pub fn from_env() -> Result<Self, String> {
+		let config_dir = paths::config_home().ok_or("Neither XDG_CONFIG_HOME nor HOME is set")?;
+		let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+		Ok(Self::new(config_dir, exe_path))
+	}
+
+	pub fn desktop_entry_path(&self) -> PathBuf {
+		self.config_dir.join("autostart").join(DESKTOP_FILE)
+	}
+
+	pub fn unit_path(&self) -> PathBuf {
+		self.config_dir.join("systemd").join("user").join(UNIT_FILE)
+	}
+
+	fn unit_link_path(&self) -> PathBuf {
+		self.config_dir
+			.join("systemd")
+			.join("user")
+			.join(format!("{}.wants", UNIT_TARGET))
+			.join(UNIT_FILE)
+	}
+
+	pub fn enable(&self, method: AutostartMethod) -> Result<(), String> {
+		// Only one mechanism may be active, otherwise the session starts two copies.
+		self.disable()?;
+
+		match method {
+			AutostartMethod::DesktopEntry => {
+				write_file(&self.desktop_entry_path(), &self.desktop_entry())
+			}
+			AutostartMethod::SystemdUnit => {
+				write_file(&self.unit_path(), &self.unit())?;
+				let link = self.unit_link_path();
+				create_parent(&link)?;
+				std::os::unix::fs::symlink(self.unit_path(), &link).map_err(|e| e.to_string())
+			}
+		}
+	}
+
+	pub fn disable(&self) -> Result<(), String> {
+		remove_file(&self.desktop_entry_path())?;
+		remove_file(&self.unit_link_path())?;
+		remove_file(&self.unit_path())
+	}
+
+	pub fn enabled_method(&self) -> Option<AutostartMethod> {
+		if self.desktop_entry_path().is_file() {
+			Some(AutostartMethod::DesktopEntry)
+		} else if fs::symlink_metadata(self.unit_link_path()).is_ok() {
+			Some(AutostartMethod::SystemdUnit)
+		} else {
+			None
+		}
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.enabled_method().is_some()
+	}
+
+	fn desktop_entry(&self) -> String {
+		format!(
+			"[Desktop Entry]\n\
+			Type=Application\n\
+			Name=NetFlux\n\
+			Comment=Network speed monitor\n\
+			Exec={}\n\
+			Terminal=false\n\
+			X-GNOME-Autostart-enabled=true\n",
+			desktop_exec(&self.exe_path)
+		)
+	}
+
+	fn unit(&self) -> String {
+		format!(
+			"[Unit]\n\
+			Description=NetFlux network speed monitor\n\
+			PartOf={target}\n\
+			After={target}\n\
+			\n\
+			[Service]\n\
+			ExecStart={exec}\n\
+			Restart=on-failure\n\
+			\n\
+			[Install]\n\
+			WantedBy={target}\n",
+			target = UNIT_TARGET,
+			exec = systemd_exec(&self.exe_path)
+		)
+	}
+}
+
+/// `path` as the program in a desktop entry's `Exec` key. `%` is doubled so
+/// it is not a field code. Paths with reserved characters are quoted with
+/// `"`, `` ` ``, `$` and `\` escaped, and then every backslash is doubled,
+/// since string values are unescaped before the command line is split.
+fn desktop_exec(path: &Path) -> String {
+	const RESERVED: &[char] = &[
+		' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|',
+		'&', ';', '$', '*', '?', '#', '(', ')', '`',
+	];
+	let path = path.to_string_lossy().replace('%', "%%");
+	if !path.contains(RESERVED) {
+		return path;
+	}
+	let mut quoted = String::new();
+	for c in path.chars() {
+		if matches!(c, '"' | '`' | '$' | '\\') {
+			quoted.push('\\');
+		}
+		quoted.push(c);
+	}
+	format!("\"{}\"", quoted.replace('\\', "\\\\").replace('\n', "\\n"))
+}
+
+/// `path` as the program in a unit's `ExecStart`, with `%` and `$` doubled so
+/// they are not specifiers or variables, and quoted with C-style escapes.
+fn systemd_exec(path: &Path) -> String {
+	let path = path.to_string_lossy().replace('%', "%%").replace('$', "$$");
+	if !path.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\')) {
+		return path;
+	}
+	let escaped = path.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+	format!("\"{}\"", escaped)
+}
+
+fn create_parent(path: &Path) -> Result<(), String> {
+	match path.parent() {
+		Some(parent) => fs::create_dir_all(parent).map_err(|e| e.to_string()),
+		None => Ok(()),
+	}
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+	create_parent(path)?;
+	fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn remove_file(path: &Path) -> Result<(), String> {
+	match fs::remove_file(path) {
+		Ok(()) => Ok(()),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(e) => Err(format!("{}: {}", path.display(), e)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_home(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("netflux-xdg-{}-{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn manager(home: &Path) -> XdgAutostart {
+		XdgAutostart::new(home.join(".config"), PathBuf::from("/opt/net flux/netflux"))
+	}
+
+	#[test]
+	fn desktop_entry_round_trip() {
+		let home = temp_home("desktop");
+		let autostart = manager(&home);
+		assert!(!autostart.is_enabled());
+
+		autostart.enable(AutostartMethod::DesktopEntry).unwrap();
+		let entry = fs::read_to_string(home.join(".config/autostart/netflux.desktop")).unwrap();
+		assert!(entry.starts_with("[Desktop Entry]\n"));
+		assert!(entry.contains("Exec=\"/opt/net flux/netflux\"\n"));
+		assert_eq!(autostart.enabled_method(), Some(AutostartMethod::DesktopEntry));
+
+		autostart.disable().unwrap();
+		assert!(!autostart.is_enabled());
+		assert!(!home.join(".config/autostart/netflux.desktop").exists());
+
+		fs::remove_dir_all(&home).unwrap();
+	}
+
+	#[test]
+	fn systemd_unit_round_trip() {
+		let home = temp_home("systemd");
+		let autostart = manager(&home);
+
+		autostart.enable(AutostartMethod::SystemdUnit).unwrap();
+		let unit = fs::read_to_string(home.join(".config/systemd/user/netflux.service")).unwrap();
+		assert!(unit.contains("ExecStart=\"/opt/net flux/netflux\"\n"));
+		assert!(unit.contains("WantedBy=graphical-session.target\n"));
+		let link = home.join(".config/systemd/user/graphical-session.target.wants/netflux.service");
+		assert_eq!(fs::read_link(&link).unwrap(), autostart.unit_path());
+		assert_eq!(autostart.enabled_method(), Some(AutostartMethod::SystemdUnit));
+
+		autostart.disable().unwrap();
+		assert!(!autostart.is_enabled());
+		assert!(fs::symlink_metadata(&link).is_err());
+		assert!(!autostart.unit_path().exists());
+
+		fs::remove_dir_all(&home).unwrap();
+	}
+
+	#[test]
+	fn exec_lines_escape_field_codes_specifiers_and_variables() {
+		assert_eq!(desktop_exec(Path::new("/usr/bin/netflux")), "/usr/bin/netflux");
+		assert_eq!(systemd_exec(Path::new("/usr/bin/netflux")), "/usr/bin/netflux");
+
+		let path = Path::new("/opt/100%/net$flux");
+		assert_eq!(desktop_exec(path), r#""/opt/100%%/net\\$flux""#);
+		assert_eq!(systemd_exec(path), "/opt/100%%/net$$flux");
+
+		let path = Path::new(r#"/home/a "b"/`x`\netflux"#);
+		assert_eq!(desktop_exec(path), r#""/home/a \\"b\\"/\\`x\\`\\\\netflux""#);
+		assert_eq!(systemd_exec(path), r#""/home/a \"b\"/`x`\\netflux""#);
+	}
+
+	#[test]
+	fn switching_method_replaces_previous_entry() {
+		let home = temp_home("switch");
+		let autostart = manager(&home);
+
+		autostart.enable(AutostartMethod::SystemdUnit).unwrap();
+		autostart.enable(AutostartMethod::DesktopEntry).unwrap();
+		assert!(!autostart.unit_path().exists());
+		assert_eq!(autostart.enabled_method(), Some(AutostartMethod::DesktopEntry));
+
+		fs::remove_dir_all(&home).unwrap();
+	}
+
+	#[test]
+	fn disable_when_nothing_installed_is_ok() {
+		let home = temp_home("noop");
+		assert!(manager(&home).disable().is_ok());
+		fs::remove_dir_all(&home).unwrap();
+	}
+}
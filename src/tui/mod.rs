@@ -0,0 +1,262 @@
+mod view;
+
+use std::collections::BTreeMap;
+use std::time::{ Duration, Instant };
+use crossterm::event::{ self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers };
+
+use crate::core::monitor::{ NetMonitor, NetStats };
+use crate::core::settings::Settings;
+use crate::core::state::{ AppState, ViewMode };
+use crate::i18n;
+use crate::sys::netinfo;
+use crate::utils::format::{ DataUnit, NumberFormat, UnitPrefix, UnitSystem };
+
+const TICK: Duration = Duration::from_secs(1);
+/// How often open interface details are looked up again, as in the popup.
+const DETAILS_REFRESH: Duration = Duration::from_secs(10);
+
+/// What the terminal frontend shows: a history per interface, so switching
+/// between them keeps their graphs, and which one is on screen.
+pub struct Tui {
+	histories: BTreeMap<String, AppState>,
+	/// Interfaces from the latest tick, in the order the OS lists them.
+	active: Vec<String>,
+	/// The interface chosen by the user; `None` follows the busiest.
+	selected: Option<String>,
+	busiest: Option<String>,
+	/// The details of the shown interface replace the stats.
+	details_open: bool,
+	/// When the details were last looked up, and for which interface.
+	details_fetched: Option<(Instant, String)>,
+	view_mode: ViewMode,
+	units: UnitSystem,
+	numbers: NumberFormat,
+	quit: bool,
+}
+
+impl Tui {
+	pub fn new(selected: Option<String>, units: UnitSystem, numbers: NumberFormat) -> Self {
+		Self {
+			histories: BTreeMap::new(),
+			active: Vec::new(),
+			selected,
+			busiest: None,
+			details_open: false,
+			details_fetched: None,
+			view_mode: ViewMode::All,
+			units,
+			numbers,
+			quit: false,
+		}
+	}
+
+	/// Records one tick of `NetMonitor`: the busiest interface and the rates
+	/// on all of them.
+	pub fn update(&mut self, busiest: Option<NetStats>, interfaces: &[NetStats]) {
+		if let Some(busiest) = busiest {
+			self.busiest = Some(busiest.interface_name);
+		}
+		self.active = interfaces.iter().map(|stats| stats.interface_name.clone()).collect();
+		for stats in interfaces {
+			let (units, numbers) = (self.units, self.numbers);
+			self.histories
+				.entry(stats.interface_name.clone())
+				.or_insert_with(|| {
+					let mut state = AppState::new();
+					state.set_units(units);
+					state.set_numbers(numbers);
+					state
+				})
+				.update(stats.clone());
+		}
+	}
+
+	/// The interface on screen: the chosen one while it is up, else the
+	/// busiest.
+	pub fn shown(&self) -> Option<&AppState> {
+		let name = self.selected_active().or(self.busiest.as_ref())?;
+		self.histories.get(name)
+	}
+
+	/// The chosen interface, unless it went away; it is shown again when it
+	/// comes back.
+	fn selected_active(&self) -> Option<&String> {
+		self.selected.as_ref().filter(|name| self.active.contains(name))
+	}
+
+	/// Looks up the details of the shown interface while they are open, when
+	/// it changed or the last lookup is stale.
+	fn refresh_details(&mut self, monitor: &NetMonitor) {
+		if !self.details_open {
+			return;
+		}
+		let Some(name) = self.shown().map(|state| state.interface_name.clone()) else {
+			return;
+		};
+		let fresh = self.details_fetched.as_ref().is_some_and(|(at, fetched)| {
+			fetched == &name && at.elapsed() < DETAILS_REFRESH
+		});
+		if fresh {
+			return;
+		}
+		let mut details = netinfo::interface_details(&name);
+		if let Some(details) = &mut details {
+			details.uptime = details.uptime.or_else(|| monitor.uptime(&name));
+		}
+		if let Some(state) = self.histories.get_mut(&name) {
+			state.set_details(details);
+		}
+		self.details_fetched = Some((Instant::now(), name));
+	}
+
+	pub fn handle_key(&mut self, key: KeyEvent) {
+		if key.kind == KeyEventKind::Release {
+			return;
+		}
+		match key.code {
+			KeyCode::Char('q') | KeyCode::Esc => {
+				self.quit = true;
+			}
+			KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+				self.quit = true;
+			}
+			KeyCode::Char('m') => {
+				self.view_mode = match self.view_mode {
+					ViewMode::All => ViewMode::DownloadOnly,
+					ViewMode::DownloadOnly => ViewMode::UploadOnly,
+					ViewMode::UploadOnly => ViewMode::Overlay,
+					ViewMode::Overlay => ViewMode::All,
+				};
+			}
+			KeyCode::Char('i') | KeyCode::Tab => self.cycle_interface(true),
+			KeyCode::Char('I') | KeyCode::BackTab => self.cycle_interface(false),
+			KeyCode::Char('d') => {
+				self.details_open = !self.details_open;
+			}
+			KeyCode::Char('u') => {
+				let unit = match self.units.unit {
+					DataUnit::Bytes => DataUnit::Bits,
+					DataUnit::Bits => DataUnit::Bytes,
+				};
+				self.set_units(UnitSystem::new(unit, self.units.prefix));
+			}
+			KeyCode::Char('p') => {
+				let prefix = match self.units.prefix {
+					UnitPrefix::Binary => UnitPrefix::Decimal,
+					UnitPrefix::Decimal => UnitPrefix::Binary,
+				};
+				self.set_units(UnitSystem::new(self.units.unit, prefix));
+			}
+			_ => {}
+		}
+	}
+
+	/// Steps through following the busiest interface, then each active one.
+	fn cycle_interface(&mut self, forward: bool) {
+		let choices: Vec<Option<&String>> = std::iter::once(None)
+			.chain(self.active.iter().map(Some))
+			.collect();
+		let current = choices
+			.iter()
+			.position(|choice| choice == &self.selected.as_ref())
+			.unwrap_or(0);
+		let next = if forward {
+			(current + 1) % choices.len()
+		} else {
+			(current + choices.len() - 1) % choices.len()
+		};
+		self.selected = choices[next].cloned();
+	}
+
+	fn set_units(&mut self, units: UnitSystem) {
+		self.units = units;
+		for state in self.histories.values_mut() {
+			state.set_units(units);
+		}
+	}
+}
+
+/// Runs the terminal frontend until the user quits, sampling once a second.
+/// `interface` starts on that interface instead of the busiest.
+pub fn run(interface: Option<String>) -> Result<(), String> {
+	let settings = Settings::load().unwrap_or_else(|e| {
+		eprintln!("netflux: {}", e);
+		Settings::default()
+	});
+	i18n::init(settings.language.as_deref());
+	let mut tui = Tui::new(interface, settings.units, i18n::numbers());
+	let mut monitor = NetMonitor::new();
+	let busiest = monitor.tick();
+	tui.update(busiest, monitor.interfaces());
+
+	let mut terminal = ratatui::try_init().map_err(|e| format!("Cannot open the terminal: {}", e))?;
+	let result = (|| -> std::io::Result<()> {
+		let mut next_tick = Instant::now() + TICK;
+		while !tui.quit {
+			tui.refresh_details(&monitor);
+			terminal.draw(|frame| view::draw(&tui, frame))?;
+			let timeout = next_tick.saturating_duration_since(Instant::now());
+			if event::poll(timeout)? {
+				if let Event::Key(key) = event::read()? {
+					tui.handle_key(key);
+				}
+			}
+			if Instant::now() >= next_tick {
+				let busiest = monitor.tick();
+				tui.update(busiest, monitor.interfaces());
+				next_tick += TICK;
+			}
+		}
+		Ok(())
+	})();
+	ratatui::restore();
+	result.map_err(|e| format!("Terminal error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn stats(name: &str, down_bps: u64, up_bps: u64) -> NetStats {
+		let interface_name = name.to_string();
+		NetStats { down_bps, up_bps, down_bytes: down_bps, up_bytes: up_bps, interface_name }
+	}
+
+	fn press(tui: &mut Tui, code: KeyCode) {
+		tui.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+	}
+
+	#[test]
+	fn keys_switch_interface_mode_and_units() {
+		let mut tui = Tui::new(None, UnitSystem::default(), NumberFormat::POINT);
+		let interfaces = [stats("eth0", 5_000, 100), stats("wlan0", 20, 10)];
+		tui.update(Some(interfaces[0].clone()), &interfaces);
+		assert_eq!(tui.shown().unwrap().interface_name, "eth0");
+
+		press(&mut tui, KeyCode::Tab);
+		press(&mut tui, KeyCode::Tab);
+		assert_eq!(tui.shown().unwrap().interface_name, "wlan0");
+		assert_eq!(tui.shown().unwrap().down_bps, 20);
+		press(&mut tui, KeyCode::Tab);
+		assert_eq!(tui.selected, None);
+		press(&mut tui, KeyCode::BackTab);
+		assert_eq!(tui.selected.as_deref(), Some("wlan0"));
+
+		// While wlan0 is down, the busiest stands in for it.
+		tui.update(Some(interfaces[0].clone()), &interfaces[..1]);
+		assert_eq!(tui.shown().unwrap().interface_name, "eth0");
+		tui.update(Some(interfaces[0].clone()), &interfaces);
+		assert_eq!(tui.shown().unwrap().interface_name, "wlan0");
+
+		press(&mut tui, KeyCode::Char('m'));
+		assert_eq!(tui.view_mode, ViewMode::DownloadOnly);
+		press(&mut tui, KeyCode::Char('u'));
+		press(&mut tui, KeyCode::Char('p'));
+		assert_eq!(tui.units, UnitSystem::new(DataUnit::Bits, UnitPrefix::Decimal));
+		assert_eq!(tui.shown().unwrap().units, tui.units);
+
+		assert!(!tui.quit);
+		tui.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+		assert!(tui.quit);
+	}
+}
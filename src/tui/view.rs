@@ -0,0 +1,379 @@
+use std::collections::VecDeque;
+use fluent_bundle::FluentArgs;
+use ratatui::layout::{ Constraint, Layout, Rect };
+use ratatui::style::{ Color, Modifier, Style };
+use ratatui::text::{ Line, Span };
+use ratatui::widgets::{ Block, Paragraph, Row, Table };
+use ratatui::Frame;
+
+use crate::core::state::{ AppState, ViewMode, HISTORY_LEN };
+use crate::i18n::{ self, tr, tr_args };
+use crate::tui::Tui;
+use crate::ui::axis::{ Axis, MIN_GRAPH_SCALE };
+use crate::ui::details;
+use crate::ui::resample::resample;
+use crate::utils::format::{ format_bytes, format_duration, format_speed_full, format_speed_tick };
+
+/// Seconds of history the average and peak are taken over.
+const STATS_WINDOW: usize = 60;
+/// Interfaces listed before the table stops growing.
+const TABLE_MAX_ROWS: usize = 6;
+/// Dots of each braille character, top to bottom, in its left and right
+/// column.
+const DOTS_LEFT: [u32; 4] = [0x01, 0x02, 0x04, 0x40];
+const DOTS_RIGHT: [u32; 4] = [0x08, 0x10, 0x20, 0x80];
+
+pub fn draw(tui: &Tui, frame: &mut Frame) {
+	let table_rows = tui.active.len().clamp(1, TABLE_MAX_ROWS) as u16;
+	let details = match tui.shown() {
+		Some(state) if tui.details_open => {
+			Some(details::lines(state.details.as_ref(), state.numbers))
+		}
+		_ => None,
+	};
+	let bottom_rows = details.as_ref().map_or(7, |lines| (lines.len() as u16) + 2);
+	let [graphs, table, stats, footer] = Layout::vertical([
+		Constraint::Min(6),
+		Constraint::Length(table_rows + 3),
+		Constraint::Length(bottom_rows),
+		Constraint::Length(1),
+	]).areas(frame.area());
+
+	match tui.shown() {
+		Some(state) => {
+			draw_graphs(state, tui.view_mode, frame, graphs);
+			match &details {
+				Some(lines) => draw_details(state, lines, frame, stats),
+				None => draw_stats(state, frame, stats),
+			}
+		}
+		None => {
+			let waiting = Paragraph::new(tr("tui-waiting")).block(Block::bordered());
+			frame.render_widget(waiting, graphs);
+			frame.render_widget(Block::bordered(), stats);
+		}
+	}
+	draw_table(tui, frame, table);
+	let keys = Paragraph::new(tr("tui-keys")).style(Style::new().fg(Color::DarkGray));
+	frame.render_widget(keys, footer);
+}
+
+fn draw_graphs(state: &AppState, view_mode: ViewMode, frame: &mut Frame, area: Rect) {
+	let theme = &state.theme;
+	let download = (
+		tr("popup-download"),
+		state.down_bps,
+		&state.down_history,
+		color(theme.download_line),
+	);
+	let upload = (tr("popup-upload"), state.up_bps, &state.up_history, color(theme.upload_line));
+	let sections = match view_mode {
+		ViewMode::All => vec![download, upload],
+		ViewMode::DownloadOnly => vec![download],
+		ViewMode::UploadOnly => vec![upload],
+		ViewMode::Overlay => {
+			draw_overlay(state, [download, upload], frame, area);
+			return;
+		}
+	};
+
+	let rows = Layout::vertical(vec![Constraint::Fill(1); sections.len()]).split(area);
+	for ((label, rate, history, color), &rect) in sections.into_iter().zip(rows.iter()) {
+		let peak = history.iter().copied().max().unwrap_or(0);
+		let axis = Axis::new(state.graph_scale, peak, MIN_GRAPH_SCALE, state.units);
+		let block = Block::bordered()
+			.title(rate_title(&label, rate, color, state))
+			.title_top(scale_title(&axis, state));
+		let inner = block.inner(rect);
+		frame.render_widget(block, rect);
+		let lines = graph(history, &axis, inner, false);
+		frame.render_widget(Paragraph::new(lines).style(Style::new().fg(color)), inner);
+	}
+}
+
+type Section<'a> = (String, u64, &'a VecDeque<u64>, Color);
+
+/// Download filling up to the middle, upload hanging below it, on one scale.
+fn draw_overlay(state: &AppState, [download, upload]: [Section; 2], frame: &mut Frame, area: Rect) {
+	let peak = download.2.iter().chain(upload.2).copied().max().unwrap_or(0);
+	let axis = Axis::new(state.graph_scale, peak, MIN_GRAPH_SCALE, state.units);
+	let mut title = rate_title(&download.0, download.1, download.3, state);
+	title.spans.push(Span::raw("  "));
+	title.spans.extend(rate_title(&upload.0, upload.1, upload.3, state).spans);
+	let block = Block::bordered().title(title).title_top(scale_title(&axis, state));
+	let inner = block.inner(area);
+	frame.render_widget(block, area);
+
+	let [above, below] = Layout::vertical([Constraint::Fill(1); 2]).areas(inner);
+	let halves = [(download, above, false), (upload, below, true)];
+	for ((_, _, history, color), rect, hanging) in halves {
+		let lines = graph(history, &axis, rect, hanging);
+		frame.render_widget(Paragraph::new(lines).style(Style::new().fg(color)), rect);
+	}
+}
+
+fn rate_title(label: &str, rate: u64, color: Color, state: &AppState) -> Line<'static> {
+	Line::from(vec![
+		Span::styled(format!(" {} ", label), Style::new().fg(color).add_modifier(Modifier::BOLD)),
+		Span::raw(format!("{} ", format_speed_full(rate, state.units, state.numbers))),
+	])
+}
+
+/// The rate at the top of the graph, on the right.
+fn scale_title(axis: &Axis, state: &AppState) -> Line<'static> {
+	Line::from(format_speed_tick(axis.top, state.units, state.numbers)).right_aligned()
+}
+
+/// `history` as braille filling `rect`, two samples wide per character.
+fn graph(history: &VecDeque<u64>, axis: &Axis, rect: Rect, hanging: bool) -> Vec<Line<'static>> {
+	let samples: Vec<u64> = history.iter().copied().collect();
+	let heights: Vec<f64> = resample(&samples, HISTORY_LEN, (rect.width as usize) * 2)
+		.iter()
+		.map(|column| axis.fraction(column.max))
+		.collect();
+	braille(&heights, rect.height as usize, hanging).into_iter().map(Line::from).collect()
+}
+
+/// Draws each of `heights`, shares of the full height, as a column of
+/// dots filled from the bottom up, or from the top down when `hanging`.
+/// Anything above zero shows at least one dot.
+pub fn braille(heights: &[f64], rows: usize, hanging: bool) -> Vec<String> {
+	let tall = rows * 4;
+	let filled: Vec<usize> = heights
+		.iter()
+		.map(|&height| {
+			let dots = (height * (tall as f64)).round() as usize;
+			(if height > 0.0 { dots.max(1) } else { dots }).min(tall)
+		})
+		.collect();
+
+	(0..rows)
+		.map(|row| {
+			filled
+				.chunks(2)
+				.map(|pair| {
+					let mut bits = 0;
+					for (&dots, masks) in pair.iter().zip([DOTS_LEFT, DOTS_RIGHT]) {
+						for (i, mask) in masks.into_iter().enumerate() {
+							// Dot rows counted from the top of the graph.
+							let y = row * 4 + i;
+							if (hanging && y < dots) || (!hanging && y >= tall - dots) {
+								bits |= mask;
+							}
+						}
+					}
+					char::from_u32(0x2800 + bits).unwrap_or(' ')
+				})
+				.collect()
+		})
+		.collect()
+}
+
+fn draw_table(tui: &Tui, frame: &mut Frame, area: Rect) {
+	let shown = tui.shown().map(|state| state.interface_name.as_str());
+	let rows = tui.active.iter().filter_map(|name| {
+		let state = tui.histories.get(name)?;
+		let (units, numbers) = (state.units, state.numbers);
+		let is_shown = shown == Some(name.as_str());
+		let row = Row::new(vec![
+			format!("{} {}", if is_shown { "▶" } else { " " }, name),
+			format_speed_full(state.down_bps, units, numbers),
+			format_speed_full(state.up_bps, units, numbers),
+			format_bytes(state.total_down, units, numbers),
+			format_bytes(state.total_up, units, numbers),
+		]);
+		Some(if is_shown { row.style(Style::new().add_modifier(Modifier::BOLD)) } else { row })
+	});
+	let header = Row::new(vec![
+		tr("tui-column-interface"),
+		tr("tui-column-down"),
+		tr("tui-column-up"),
+		tr("tui-column-received"),
+		tr("tui-column-sent"),
+	]).style(Style::new().fg(Color::DarkGray));
+	let title = match &tui.selected {
+		Some(name) => {
+			let mut args = FluentArgs::new();
+			args.set("name", name.clone());
+			let id = if tui.selected_active().is_some() {
+				"tui-interfaces-fixed"
+			} else {
+				"tui-interfaces-down"
+			};
+			tr_args(id, &args)
+		}
+		None => tr("tui-interfaces-auto"),
+	};
+	let widths = [
+		Constraint::Fill(2),
+		Constraint::Fill(1),
+		Constraint::Fill(1),
+		Constraint::Fill(1),
+		Constraint::Fill(1),
+	];
+	let table = Table::new(rows, widths)
+		.header(header)
+		.block(Block::bordered().title(format!(" {} ", title)));
+	frame.render_widget(table, area);
+}
+
+/// Current, average and peak rates over the last `STATS_WINDOW` seconds,
+/// and the session totals.
+fn draw_stats(state: &AppState, frame: &mut Frame, area: Rect) {
+	let (units, numbers) = (state.units, state.numbers);
+	let speed = |bps: u64| format_speed_full(bps, units, numbers);
+	let (down_average, down_peak) = window(&state.down_history);
+	let (up_average, up_peak) = window(&state.up_history);
+
+	let mut args = FluentArgs::new();
+	args.set("seconds", STATS_WINDOW);
+	let mut session = FluentArgs::new();
+	session.set("duration", format_duration(state.started.elapsed(), i18n::current()));
+	let rows = [
+		Row::new(vec![tr("tui-stat-now"), speed(state.down_bps), speed(state.up_bps)]),
+		Row::new(vec![tr_args("tui-stat-average", &args), speed(down_average), speed(up_average)]),
+		Row::new(vec![tr_args("tui-stat-peak", &args), speed(down_peak), speed(up_peak)]),
+		Row::new(vec![
+			tr_args("tui-stat-session", &session),
+			format_bytes(state.total_down, units, numbers),
+			format_bytes(state.total_up, units, numbers),
+		]),
+	];
+	let theme = &state.theme;
+	let header = Row::new(vec![
+		Line::default(),
+		Line::styled(tr("popup-download"), Style::new().fg(color(theme.download_line))),
+		Line::styled(tr("popup-upload"), Style::new().fg(color(theme.upload_line))),
+	]);
+	let widths = [Constraint::Fill(2), Constraint::Fill(1), Constraint::Fill(1)];
+	let table = Table::new(rows, widths)
+		.header(header)
+		.block(Block::bordered().title(format!(" {} ", state.interface_name)));
+	frame.render_widget(table, area);
+}
+
+/// The details of the shown interface, in place of the stats.
+fn draw_details(state: &AppState, lines: &[(String, String)], frame: &mut Frame, area: Rect) {
+	let label_width = lines
+		.iter()
+		.map(|(label, _)| label.chars().count())
+		.max()
+		.unwrap_or(0);
+	let rows = lines.iter().map(|(label, value)| {
+		Row::new(vec![
+			Line::styled(label.clone(), Style::new().fg(Color::DarkGray)),
+			Line::raw(value.clone()),
+		])
+	});
+	let widths = [Constraint::Length(label_width as u16), Constraint::Fill(1)];
+	let table = Table::new(rows, widths).block(
+		Block::bordered().title(format!(" {} ", state.interface_name))
+	);
+	frame.render_widget(table, area);
+}
+
+/// Average and peak of the newest `STATS_WINDOW` samples.
+fn window(history: &VecDeque<u64>) -> (u64, u64) {
+	let recent = history.iter().rev().take(STATS_WINDOW);
+	let count = recent.len() as u64;
+	let (sum, peak) = recent.fold((0, 0), |(sum, peak), &value| (sum + value, peak.max(value)));
+	(sum.checked_div(count).unwrap_or(0), peak)
+}
+
+/// A theme `COLORREF` (0x00BBGGRR) as a terminal colour.
+fn color(colorref: u32) -> Color {
+	Color::Rgb(colorref as u8, (colorref >> 8) as u8, (colorref >> 16) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::{ IpAddr, Ipv4Addr };
+	use crate::core::monitor::NetStats;
+	use crate::sys::netinfo::{ InterfaceDetails, Media };
+	use crate::utils::format::{ NumberFormat, UnitSystem };
+	use ratatui::backend::TestBackend;
+	use ratatui::Terminal;
+
+	fn render(tui: &Tui) -> Vec<String> {
+		let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+		terminal.draw(|frame| draw(tui, frame)).unwrap();
+		let buffer = terminal.backend().buffer();
+		(0..buffer.area.height)
+			.map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect())
+			.collect()
+	}
+
+	#[test]
+	fn braille_fills_two_columns_per_character() {
+		assert_eq!(braille(&[1.0, 0.5, 0.0, 0.01], 1, false), ["⣧⢀"]);
+		assert_eq!(braille(&[0.5, 0.25], 2, false), ["⠀", "⣧"]);
+		assert_eq!(braille(&[0.5, 0.25], 2, true), ["⡟", "⠀"]);
+		assert_eq!(braille(&[0.0, 0.0, 1.0], 1, false), ["⠀⡇"]);
+	}
+
+	#[test]
+	fn renders_graphs_table_and_stats() {
+		i18n::init(Some("en"));
+		let mut tui = Tui::new(None, UnitSystem::default(), NumberFormat::POINT);
+		for i in 0..90 {
+			let eth0 = NetStats {
+				down_bps: 1_048_576 * (i % 3),
+				up_bps: 2_048,
+				down_bytes: 1_048_576 * (i % 3),
+				up_bytes: 2_048,
+				interface_name: "eth0".to_string(),
+			};
+			let wlan0 = NetStats { interface_name: "wlan0".to_string(), ..eth0.clone() };
+			tui.update(Some(eth0.clone()), &[eth0, wlan0]);
+		}
+
+		let lines = render(&tui);
+		let screen = lines.join("\n");
+
+		assert!(lines[0].contains("↓ DOWNLOAD 2.0 MiB/s"), "{}", screen);
+		assert!(lines[0].trim_end().ends_with("2 MiB/s┐"), "{}", screen);
+		assert!(screen.contains("⣿"), "{}", screen);
+		assert!(screen.contains("Interfaces · following the busiest"), "{}", screen);
+		assert!(screen.contains("▶ eth0"), "{}", screen);
+		assert!(screen.contains("  wlan0"), "{}", screen);
+		let average = lines.iter().find(|line| line.contains("Average (60 s)")).unwrap();
+		assert!(average.contains("1.0 MiB/s"), "{}", screen);
+		assert!(screen.contains("90.0 MiB"), "{}", screen);
+		assert!(lines[29].starts_with("m view"), "{}", screen);
+	}
+
+	#[test]
+	fn details_replace_the_stats() {
+		i18n::init(Some("en"));
+		let mut tui = Tui::new(None, UnitSystem::default(), NumberFormat::POINT);
+		let eth0 = NetStats {
+			down_bps: 1_024,
+			up_bps: 512,
+			down_bytes: 1_024,
+			up_bytes: 512,
+			interface_name: "eth0".to_string(),
+		};
+		tui.update(Some(eth0.clone()), &[eth0]);
+		tui.details_open = true;
+		tui.histories.get_mut("eth0").unwrap().set_details(Some(InterfaceDetails {
+			alias: "eth0".to_string(),
+			description: "e1000e".to_string(),
+			mac: Some("52:54:00:12:34:56".to_string()),
+			ipv4: vec![Ipv4Addr::new(192, 168, 2, 20)],
+			ipv6: Vec::new(),
+			gateways: vec![IpAddr::V4(Ipv4Addr::new(192, 168, 2, 1))],
+			dns: Vec::new(),
+			link_speed: Some(1_000_000_000),
+			media: Media::Ethernet,
+			mtu: Some(1500),
+			uptime: None,
+		}));
+
+		let screen = render(&tui).join("\n");
+		assert!(screen.contains("Adapter    e1000e"), "{}", screen);
+		assert!(screen.contains("IPv4       192.168.2.20"), "{}", screen);
+		assert!(screen.contains("Link speed 1 Gbit/s"), "{}", screen);
+		assert!(!screen.contains("Average"), "{}", screen);
+	}
+}
@@ -0,0 +1,155 @@
+use crate::core::state::GraphScale;
+use crate::utils::format::UnitSystem;
+
+/// Graphs never scale below 1 MiB/s, so idle noise stays flat.
+pub const MIN_GRAPH_SCALE: u64 = 1024 * 1024;
+/// Intervals a linear axis aims for; rounding the step up to a nice number
+/// leaves two or three.
+const LINEAR_INTERVALS: f64 = 3.0;
+/// Ticks within each prefix on a log axis. The next prefix takes over from
+/// 1000, so binary units never show `1000 KiB/s` next to `1 MiB/s`.
+const LOG_MANTISSAS: [f64; 3] = [1.0, 10.0, 100.0];
+
+/// Vertical scale of a rate graph. Values are bytes per second; ticks are
+/// chosen in the unit being displayed so their labels come out round.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Axis {
+	pub scale: GraphScale,
+	/// Value at the bottom edge: zero, or one kilo-unit on a log axis.
+	pub floor: f64,
+	/// Value at the top edge, which is also the last tick.
+	pub top: f64,
+	/// Gridline values above the floor, ascending.
+	pub ticks: Vec<f64>,
+}
+
+impl Axis {
+	/// An axis that fits `peak`, never scaled below `min_scale`.
+	pub fn new(scale: GraphScale, peak: u64, min_scale: u64, units: UnitSystem) -> Self {
+		let peak = units.quantity(peak.max(min_scale).max(1));
+		// Display units per byte: 1, or 8 for bits.
+		let per_byte = units.quantity(1);
+		let base = units.base();
+
+		let ticks: Vec<f64> = match scale {
+			GraphScale::Linear => {
+				let mut prefix = 1.0;
+				while prefix * base <= peak {
+					prefix *= base;
+				}
+				let step = nice_ceil(peak / prefix / LINEAR_INTERVALS) * prefix;
+				// Tolerate float noise so an exact 1 MiB peak stays one step.
+				let count = (peak / step - 1e-9).ceil().max(1.0) as usize;
+				(1..=count).map(|i| (i as f64) * step).collect()
+			}
+			GraphScale::Log => {
+				let mut ticks = Vec::new();
+				let mut prefix = base;
+				'outer: loop {
+					for mantissa in LOG_MANTISSAS {
+						let tick = prefix * mantissa;
+						// The first kilo-unit is the floor, not a gridline.
+						if tick <= base {
+							continue;
+						}
+						ticks.push(tick);
+						if tick >= peak * (1.0 - 1e-9) {
+							break 'outer;
+						}
+					}
+					prefix *= base;
+				}
+				ticks
+			}
+		};
+
+		let floor = match scale {
+			GraphScale::Linear => 0.0,
+			GraphScale::Log => base / per_byte,
+		};
+		let ticks: Vec<f64> = ticks.into_iter().map(|tick| tick / per_byte).collect();
+		Self { scale, floor, top: *ticks.last().unwrap(), ticks }
+	}
+
+	/// Height of `value` as a share of the axis, clamped to 0..=1.
+	pub fn fraction(&self, value: f64) -> f64 {
+		let fraction = match self.scale {
+			GraphScale::Linear => value / self.top,
+			GraphScale::Log if value <= self.floor => 0.0,
+			GraphScale::Log => (value / self.floor).ln() / (self.top / self.floor).ln(),
+		};
+		fraction.clamp(0.0, 1.0)
+	}
+}
+
+/// The smallest 1, 2 or 5 × 10ⁿ that is at least `value`.
+fn nice_ceil(value: f64) -> f64 {
+	let magnitude = (10f64).powf(value.log10().floor());
+	[1.0, 2.0, 5.0, 10.0]
+		.into_iter()
+		.map(|mantissa| mantissa * magnitude)
+		.find(|&nice| nice >= value * (1.0 - 1e-9))
+		.unwrap_or(10.0 * magnitude)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::format::{ DataUnit, UnitPrefix };
+
+	const BYTES_IEC: UnitSystem = UnitSystem::new(DataUnit::Bytes, UnitPrefix::Binary);
+	const BITS_SI: UnitSystem = UnitSystem::new(DataUnit::Bits, UnitPrefix::Decimal);
+	const KIB: f64 = 1024.0;
+	const MIB: f64 = 1024.0 * KIB;
+
+	#[test]
+	fn nice_numbers_are_one_two_or_five() {
+		assert_eq!(nice_ceil(0.34), 0.5);
+		assert_eq!(nice_ceil(1.0), 1.0);
+		assert_eq!(nice_ceil(1.53), 2.0);
+		assert_eq!(nice_ceil(3.3), 5.0);
+		assert_eq!(nice_ceil(7.0), 10.0);
+		assert_eq!(nice_ceil(420.0), 500.0);
+	}
+
+	#[test]
+	fn linear_ticks_are_round_in_the_displayed_unit() {
+		let axis = Axis::new(GraphScale::Linear, 0, MIB as u64, BYTES_IEC);
+		assert_eq!(axis.ticks, vec![0.5 * MIB, MIB]);
+
+		let axis = Axis::new(GraphScale::Linear, (4.6 * MIB) as u64, MIB as u64, BYTES_IEC);
+		assert_eq!(axis.ticks, vec![2.0 * MIB, 4.0 * MIB, 6.0 * MIB]);
+		assert_eq!(axis.top, 6.0 * MIB);
+
+		// 10 Mbit/s: 5 and 10 Mbit, in bytes.
+		let axis = Axis::new(GraphScale::Linear, 1_250_000, 0, BITS_SI);
+		assert_eq!(axis.ticks, vec![625_000.0, 1_250_000.0]);
+
+		let axis = Axis::new(GraphScale::Linear, 300 * 1024, 0, BYTES_IEC);
+		assert_eq!(axis.ticks, vec![100.0 * KIB, 200.0 * KIB, 300.0 * KIB]);
+	}
+
+	#[test]
+	fn log_ticks_step_by_ten_within_each_prefix() {
+		let axis = Axis::new(GraphScale::Log, (3.0 * MIB) as u64, MIB as u64, BYTES_IEC);
+		assert_eq!(axis.floor, KIB);
+		assert_eq!(axis.ticks, vec![10.0 * KIB, 100.0 * KIB, MIB, 10.0 * MIB]);
+
+		let axis = Axis::new(GraphScale::Log, 0, 0, BYTES_IEC);
+		assert_eq!(axis.ticks, vec![10.0 * KIB]);
+	}
+
+	#[test]
+	fn fraction_spans_floor_to_top() {
+		for scale in [GraphScale::Linear, GraphScale::Log] {
+			let axis = Axis::new(scale, (3.0 * MIB) as u64, MIB as u64, BYTES_IEC);
+			assert_eq!(axis.fraction(0.0), 0.0);
+			assert_eq!(axis.fraction(axis.top), 1.0);
+			assert_eq!(axis.fraction(axis.top * 4.0), 1.0);
+			assert!(axis.fraction(100.0 * KIB) < axis.fraction(MIB));
+		}
+		// On a log axis, 10 KiB/s is clearly visible under a 10 MiB/s top.
+		let axis = Axis::new(GraphScale::Log, (3.0 * MIB) as u64, MIB as u64, BYTES_IEC);
+		assert!(axis.fraction(10.0 * KIB) > 0.2);
+	}
+}
@@ -0,0 +1,88 @@
+/// A point in pixels, fractional for antialiased backends.
+pub type Point = (f32, f32);
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct Rect {
+	pub x: f32,
+	pub y: f32,
+	pub w: f32,
+	pub h: f32,
+}
+
+impl Rect {
+	pub const fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+		Self { x, y, w, h }
+	}
+
+	pub fn right(&self) -> f32 {
+		self.x + self.w
+	}
+
+	pub fn bottom(&self) -> f32 {
+		self.y + self.h
+	}
+
+	pub fn contains(&self, (x, y): Point) -> bool {
+		x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+	}
+
+	/// Shrinks the rectangle by `dx` on the left and right and `dy` on the
+	/// top and bottom.
+	pub fn inset(&self, dx: f32, dy: f32) -> Self {
+		Self::new(self.x + dx, self.y + dy, (self.w - 2.0 * dx).max(0.0), (self.h - 2.0 * dy).max(0.0))
+	}
+
+	/// Cuts `height` off the top, returning the strip and the remainder.
+	pub fn split_top(&self, height: f32) -> (Self, Self) {
+		let height = height.clamp(0.0, self.h);
+		(
+			Self::new(self.x, self.y, self.w, height),
+			Self::new(self.x, self.y + height, self.w, self.h - height),
+		)
+	}
+
+	/// Splits into `count` equal rows.
+	pub fn rows(&self, count: usize) -> Vec<Self> {
+		let height = self.h / (count.max(1) as f32);
+		(0..count).map(|i| Self::new(self.x, self.y + (i as f32) * height, self.w, height)).collect()
+	}
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FontWeight {
+	SemiBold,
+	Bold,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TextStyle {
+	/// Em height in pixels.
+	pub size: f32,
+	pub weight: FontWeight,
+}
+
+impl TextStyle {
+	pub const fn new(size: f32, weight: FontWeight) -> Self {
+		Self { size, weight }
+	}
+}
+
+/// Drawing operations the popup and graph widgets need, implemented by the
+/// software raster and, on Windows, by GDI. Colours are `COLORREF` values
+/// from `ui::theme`.
+pub trait Canvas {
+	fn size(&self) -> (u32, u32);
+
+	fn fill_rect(&mut self, rect: Rect, color: u32);
+
+	/// Fills the closed polygon through `points`.
+	fn fill_polygon(&mut self, points: &[Point], color: u32);
+
+	fn polyline(&mut self, points: &[Point], width: f32, color: u32);
+
+	/// Draws `text` with the top of its line box at `y`.
+	fn text(&mut self, text: &str, x: f32, y: f32, style: TextStyle, color: u32);
+
+	/// Width and line height of `text`.
+	fn measure_text(&mut self, text: &str, style: TextStyle) -> (f32, f32);
+}
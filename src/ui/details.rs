@@ -0,0 +1,66 @@
+use crate::i18n::{ self, tr };
+use crate::sys::netinfo::{ InterfaceDetails, Media };
+use crate::utils::format::{
+	format_duration,
+	format_speed_tick,
+	DataUnit,
+	NumberFormat,
+	UnitPrefix,
+	UnitSystem,
+};
+
+/// Link speeds are quoted the way adapters advertise them.
+const LINK_SPEED_UNITS: UnitSystem = UnitSystem::new(DataUnit::Bits, UnitPrefix::Decimal);
+
+/// Label and value pairs for the details of an interface. Lists get a line
+/// per address, with the label on the first.
+pub fn lines(details: Option<&InterfaceDetails>, numbers: NumberFormat) -> Vec<(String, String)> {
+	let Some(details) = details else {
+		return vec![(tr("details-unavailable"), String::new())];
+	};
+	let InterfaceDetails { alias, description, mac, ipv4, ipv6, gateways, dns, .. } = details;
+
+	let mut lines = vec![(tr("details-interface"), alias.clone())];
+	push_lines(&mut lines, "details-adapter", (!description.is_empty()).then(|| description.clone()));
+	push_lines(&mut lines, "details-media", [tr(media_key(details.media))]);
+	push_lines(&mut lines, "details-mac", mac.clone());
+	push_lines(&mut lines, "details-ipv4", ipv4.iter().map(|ip| ip.to_string()));
+	push_lines(&mut lines, "details-ipv6", ipv6.iter().map(|ip| ip.to_string()));
+	push_lines(&mut lines, "details-gateway", gateways.iter().map(|ip| ip.to_string()));
+	push_lines(&mut lines, "details-dns", dns.iter().map(|ip| ip.to_string()));
+	push_lines(
+		&mut lines,
+		"details-link-speed",
+		details.link_speed.map(|bits| {
+			format_speed_tick((bits as f64) / 8.0, LINK_SPEED_UNITS, numbers)
+		})
+	);
+	push_lines(&mut lines, "details-mtu", details.mtu.map(|mtu| mtu.to_string()));
+	push_lines(
+		&mut lines,
+		"details-uptime",
+		details.uptime.map(|uptime| format_duration(uptime, i18n::current()))
+	);
+	lines
+}
+
+fn push_lines(
+	lines: &mut Vec<(String, String)>,
+	key: &str,
+	values: impl IntoIterator<Item = String>
+) {
+	for (i, value) in values.into_iter().enumerate() {
+		lines.push((if i == 0 { tr(key) } else { String::new() }, value));
+	}
+}
+
+fn media_key(media: Media) -> &'static str {
+	match media {
+		Media::Ethernet => "media-ethernet",
+		Media::WiFi => "media-wifi",
+		Media::Mobile => "media-mobile",
+		Media::Ppp => "media-ppp",
+		Media::Tunnel => "media-tunnel",
+		Media::Other => "media-other",
+	}
+}
@@ -0,0 +1,72 @@
+#[cfg(any(tray, test))]
+pub mod render;
+
+use std::path::{ Path, PathBuf };
+
+pub const DEFAULT_WIDTH: u32 = 800;
+pub const DEFAULT_HEIGHT: u32 = 400;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExportFormat {
+	Png,
+	Svg,
+}
+
+impl ExportFormat {
+	/// The format a file name asks for, by extension.
+	pub fn from_path(path: &Path) -> Option<Self> {
+		let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+		match extension.as_str() {
+			"png" => Some(Self::Png),
+			"svg" => Some(Self::Svg),
+			_ => None,
+		}
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			Self::Png => "png",
+			Self::Svg => "svg",
+		}
+	}
+}
+
+/// What to save and how it should look.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ExportOptions {
+	pub path: PathBuf,
+	pub format: ExportFormat,
+	pub width: u32,
+	pub height: u32,
+	/// A theme setting name; `None` keeps the popup's theme.
+	pub theme: Option<String>,
+	/// `None` uses a generic title.
+	pub title: Option<String>,
+}
+
+impl ExportOptions {
+	/// Default size and look, in the format `path` asks for or PNG.
+	pub fn new(path: PathBuf) -> Self {
+		Self {
+			format: ExportFormat::from_path(&path).unwrap_or(ExportFormat::Png),
+			path,
+			width: DEFAULT_WIDTH,
+			height: DEFAULT_HEIGHT,
+			theme: None,
+			title: None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn format_follows_the_extension() {
+		assert_eq!(ExportFormat::from_path(Path::new("graph.PNG")), Some(ExportFormat::Png));
+		assert_eq!(ExportFormat::from_path(Path::new("a/graph.svg")), Some(ExportFormat::Svg));
+		assert_eq!(ExportFormat::from_path(Path::new("graph")), None);
+		assert_eq!(ExportOptions::new(PathBuf::from("graph.bmp")).format, ExportFormat::Png);
+	}
+}
@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use crate::core::settings::PopupSize;
+use crate::core::state::{ AppState, ViewMode, HISTORY_LEN };
+use crate::i18n::{ self, tr };
+use crate::ui::canvas::{ Canvas, FontWeight, Rect, TextStyle };
+use crate::ui::export::{ ExportFormat, ExportOptions };
+use crate::ui::panel;
+use crate::ui::raster::Raster;
+use crate::ui::svg::Svg;
+use crate::ui::theme::Theme;
+use crate::utils::format::format_relative;
+
+const TITLE_STYLE: TextStyle = TextStyle::new(16.0, FontWeight::Bold);
+const SUBTITLE_STYLE: TextStyle = TextStyle::new(12.0, FontWeight::SemiBold);
+const TITLE_HEIGHT: f32 = 40.0;
+const TIME_AXIS_STYLE: TextStyle = TextStyle::new(10.0, FontWeight::SemiBold);
+const TIME_AXIS_HEIGHT: f32 = 22.0;
+/// Time labels match the minute marks along the graph baseline.
+const TIME_LABEL_SECONDS: u64 = 60;
+const TIME_LABEL_GAP: f32 = 8.0;
+/// Gap between the newest label and the right edge.
+const TIME_LABEL_INSET: f32 = 4.0;
+const PADDING_X: f32 = 16.0;
+
+/// Renders the current history with `options` and writes it to its path.
+pub fn export(state: &AppState, options: &ExportOptions) -> Result<(), String> {
+	let mut state = state.clone();
+	if let Some(theme) = &options.theme {
+		state.set_theme(Theme::resolve(theme, None)?);
+	}
+	let title = options.title.clone().unwrap_or_else(|| tr("export-title"));
+	let (min_width, min_height) = min_size(state.view_mode);
+	let (width, height) = (options.width.max(min_width), options.height.max(min_height));
+
+	let bytes = match options.format {
+		ExportFormat::Png => {
+			let mut raster = Raster::new(width, height)?;
+			draw(&state, &title, &mut raster);
+			raster.encode_png()?
+		}
+		ExportFormat::Svg => {
+			let mut svg = Svg::new(width, height, &state.theme.font);
+			draw(&state, &title, &mut svg);
+			svg.finish().into_bytes()
+		}
+	};
+	std::fs::write(&options.path, bytes).map_err(|e|
+		format!("Cannot write {}: {}", options.path.display(), e)
+	)
+}
+
+/// The smallest image that fits the title, the time axis and the smallest
+/// popup graphs for `view_mode`.
+pub fn min_size(view_mode: ViewMode) -> (u32, u32) {
+	let (width, height) = PopupSize::MIN.window(view_mode);
+	(width, height + ((TITLE_HEIGHT + TIME_AXIS_HEIGHT) as u32))
+}
+
+/// Lays out a title over the popup graphs, with the age of each minute
+/// mark underneath.
+pub fn draw(state: &AppState, title: &str, canvas: &mut dyn Canvas) {
+	let (width, height) = canvas.size();
+	let theme = &state.theme;
+	let bounds = Rect::new(0.0, 0.0, width as f32, height as f32);
+	canvas.fill_rect(bounds, theme.background);
+
+	let (header, rest) = bounds.split_top(TITLE_HEIGHT);
+	let (graphs, axis) = rest.split_top(rest.h - TIME_AXIS_HEIGHT);
+
+	let (title_width, title_height) = canvas.measure_text(title, TITLE_STYLE);
+	let y = (header.y + (header.h - title_height) / 2.0).round();
+	canvas.text(title, header.x + PADDING_X, y, TITLE_STYLE, theme.text);
+	// The interface name goes on the right, if the title leaves room.
+	let (name_width, name_height) = canvas.measure_text(&state.interface_name, SUBTITLE_STYLE);
+	let x = header.right() - PADDING_X - name_width;
+	if x >= header.x + PADDING_X + title_width + PADDING_X {
+		let y = (header.y + (header.h - name_height) / 2.0).round();
+		canvas.text(&state.interface_name, x, y, SUBTITLE_STYLE, theme.text_dim);
+	}
+
+	panel::draw_graphs(state, None, canvas, graphs);
+	draw_time_axis(state, canvas, axis);
+}
+
+/// Labels the minute marks, counted back from the newest sample, skipping
+/// any that would run into the previous label or off the edge.
+fn draw_time_axis(state: &AppState, canvas: &mut dyn Canvas, rect: Rect) {
+	let len = state.down_history.len();
+	if len == 0 {
+		return;
+	}
+	let per_second = rect.w / (HISTORY_LEN as f32);
+	let newest = rect.x + ((len - 1) as f32) * per_second;
+
+	let right = rect.right() - TIME_LABEL_INSET;
+	let mut free = right;
+	for seconds in (0..len as u64).step_by(TIME_LABEL_SECONDS as usize) {
+		let x = newest - (seconds as f32) * per_second;
+		let label = format_relative(Duration::from_secs(seconds), i18n::current());
+		let (label_width, label_height) = canvas.measure_text(&label, TIME_AXIS_STYLE);
+		let left = (x - label_width / 2.0).round().min((right - label_width).floor());
+		if left < rect.x || left + label_width > free {
+			continue;
+		}
+		let y = (rect.y + (rect.h - label_height) / 2.0).round();
+		canvas.text(&label, left, y, TIME_AXIS_STYLE, state.theme.text_dim);
+		free = left - TIME_LABEL_GAP;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ui::export::{ DEFAULT_HEIGHT, DEFAULT_WIDTH };
+	use crate::ui::golden;
+
+	/// The popup tests' traffic, on a named interface.
+	fn synthetic_state() -> AppState {
+		let mut state = golden::synthetic_state(ViewMode::All);
+		state.interface_name = "Ethernet".to_string();
+		state
+	}
+
+	#[test]
+	fn export_matches_golden_image() {
+		i18n::init(Some("en"));
+		let state = synthetic_state();
+		let mut raster = Raster::new(DEFAULT_WIDTH, DEFAULT_HEIGHT).unwrap();
+		draw(&state, "Uplink during the outage", &mut raster);
+		let (width, height) = min_size(ViewMode::All);
+		let mut light = Raster::new(width, height).unwrap();
+		let mut light_state = state.clone();
+		light_state.set_theme(Theme::preset("light").unwrap());
+		draw(&light_state, &tr("export-title"), &mut light);
+		golden::assert_all([
+			("export-default".to_string(), raster),
+			("export-light-small".to_string(), light),
+		]);
+	}
+
+	#[test]
+	fn writes_png_and_svg_files() {
+		i18n::init(Some("en"));
+		let state = synthetic_state();
+		let dir = std::env::temp_dir().join(format!("netflux-export-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+
+		let mut options = ExportOptions::new(dir.join("graph.png"));
+		options.width = 10;
+		export(&state, &options).unwrap();
+		let png = std::fs::read(&options.path).unwrap();
+		assert!(png.starts_with(b"\x89PNG"));
+		assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), min_size(ViewMode::All).0);
+
+		let mut options = ExportOptions::new(dir.join("graph.svg"));
+		options.theme = Some("light".to_string());
+		options.title = Some("A & B".to_string());
+		export(&state, &options).unwrap();
+		let svg = std::fs::read_to_string(&options.path).unwrap();
+		assert!(svg.contains("width=\"800\" height=\"400\""));
+		assert!(svg.contains(">A &amp; B</text>"));
+		assert!(svg.contains(">3 min ago</text>"));
+
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -0,0 +1,112 @@
+use ab_glyph::{ Font, FontRef, GlyphId, OutlineCurve };
+use once_cell::sync::Lazy;
+use tiny_skia::{ Path, PathBuilder };
+
+/// DejaVu Sans Condensed Bold: narrow digits for the tray icon, and Latin,
+/// Cyrillic and arrow coverage for the popup labels.
+static FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansCondensed-Bold.ttf");
+
+static FONT: Lazy<FontRef<'static>> = Lazy::new(|| {
+	FontRef::try_from_slice(FONT_DATA).expect("embedded font is valid")
+});
+
+pub fn font() -> &'static FontRef<'static> {
+	&FONT
+}
+
+/// Pixels per font unit for a font whose em square is `em` pixels tall.
+fn scale(em: f32) -> f32 {
+	em / font().units_per_em().unwrap_or(2048.0)
+}
+
+fn glyph_ids(text: &str) -> impl Iterator<Item = GlyphId> + '_ {
+	text.chars().map(|c| font().glyph_id(c))
+}
+
+pub fn text_width(text: &str, em: f32) -> f32 {
+	let font = font();
+	let mut width = 0.0;
+	let mut previous: Option<GlyphId> = None;
+	for id in glyph_ids(text) {
+		if let Some(prev) = previous {
+			width += font.kern_unscaled(prev, id);
+		}
+		width += font.h_advance_unscaled(id);
+		previous = Some(id);
+	}
+	width * scale(em)
+}
+
+/// Height of the digits above the baseline, used to centre numbers.
+pub fn digit_height(em: f32) -> f32 {
+	font()
+		.outline(font().glyph_id('0'))
+		.map(|outline| outline.bounds.min.y.max(outline.bounds.max.y) * scale(em))
+		.unwrap_or(em * 0.73)
+}
+
+/// Distance from the top of a line of text to its baseline.
+pub fn ascent(em: f32) -> f32 {
+	font().ascent_unscaled() * scale(em)
+}
+
+/// Distance between baselines of consecutive lines.
+pub fn line_height(em: f32) -> f32 {
+	let font = font();
+	(font.ascent_unscaled() - font.descent_unscaled() + font.line_gap_unscaled()) * scale(em)
+}
+
+/// Glyph outlines for `text` with the pen starting at (`x`, `baseline`),
+/// ready to fill with the non-zero rule.
+pub fn text_path(text: &str, x: f32, baseline: f32, em: f32) -> Option<Path> {
+	let font = font();
+	let scale = scale(em);
+	let mut builder = PathBuilder::new();
+	let mut pen = x;
+	let mut previous: Option<GlyphId> = None;
+
+	for id in glyph_ids(text) {
+		if let Some(prev) = previous {
+			pen += font.kern_unscaled(prev, id) * scale;
+		}
+
+		if let Some(outline) = font.outline(id) {
+			let map = |p: ab_glyph::Point| (pen + p.x * scale, baseline - p.y * scale);
+			let mut last: Option<(f32, f32)> = None;
+
+			for curve in &outline.curves {
+				let (start, end) = match *curve {
+					OutlineCurve::Line(a, b) => (map(a), map(b)),
+					OutlineCurve::Quad(a, _, b) => (map(a), map(b)),
+					OutlineCurve::Cubic(a, _, _, b) => (map(a), map(b)),
+				};
+				if last != Some(start) {
+					if last.is_some() {
+						builder.close();
+					}
+					builder.move_to(start.0, start.1);
+				}
+				match *curve {
+					OutlineCurve::Line(_, _) => builder.line_to(end.0, end.1),
+					OutlineCurve::Quad(_, c, _) => {
+						let c = map(c);
+						builder.quad_to(c.0, c.1, end.0, end.1);
+					}
+					OutlineCurve::Cubic(_, c1, c2, _) => {
+						let (c1, c2) = (map(c1), map(c2));
+						builder.cubic_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+					}
+				}
+				last = Some(end);
+			}
+			if last.is_some() {
+				builder.close();
+			}
+		}
+
+		pen += font.h_advance_unscaled(id) * scale;
+		previous = Some(id);
+	}
+
+	builder.finish()
+}
@@ -0,0 +1,287 @@
+use windows::Win32::Foundation::{ COLORREF, HWND, POINT, RECT, SIZE };
+use windows::Win32::Graphics::Gdi::{
+	BitBlt,
+	CreateCompatibleBitmap,
+	CreateCompatibleDC,
+	CreateFontW,
+	CreatePen,
+	CreateSolidBrush,
+	DeleteDC,
+	DeleteObject,
+	FillRect,
+	GetDC,
+	GetTextExtentPoint32W,
+	Polygon,
+	Polyline,
+	ReleaseDC,
+	SelectObject,
+	SetBkMode,
+	SetTextColor,
+	TextOutW,
+	CLIP_DEFAULT_PRECIS,
+	DEFAULT_CHARSET,
+	DEFAULT_QUALITY,
+	FF_DONTCARE,
+	FW_BOLD,
+	FW_SEMIBOLD,
+	HBITMAP,
+	HBRUSH,
+	HDC,
+	HGDIOBJ,
+	OUT_DEFAULT_PRECIS,
+	PEN_STYLE,
+	PS_NULL,
+	PS_SOLID,
+	SRCCOPY,
+	TRANSPARENT,
+};
+use windows::core::PCWSTR;
+
+use crate::ui::canvas::{ Canvas, FontWeight, Point, Rect, TextStyle };
+
+/// An off-screen GDI surface. Everything is drawn into a memory DC and
+/// copied to the window in one go, so redraws do not flicker.
+pub struct GdiCanvas {
+	hdc: HDC,
+	bitmap: HBITMAP,
+	old_bitmap: HGDIOBJ,
+	width: u32,
+	height: u32,
+	font_face: String,
+}
+
+impl GdiCanvas {
+	pub fn new(width: u32, height: u32, font_face: &str) -> Result<Self, String> {
+		unsafe {
+			let screen = GetDC(HWND(std::ptr::null_mut()));
+			let hdc = CreateCompatibleDC(screen);
+			let bitmap = CreateCompatibleBitmap(screen, width as i32, height as i32);
+			ReleaseDC(HWND(std::ptr::null_mut()), screen);
+
+			if hdc.is_invalid() || bitmap.is_invalid() {
+				if !bitmap.is_invalid() {
+					let _ = DeleteObject(bitmap);
+				}
+				if !hdc.is_invalid() {
+					let _ = DeleteDC(hdc);
+				}
+				return Err("Failed to create an off-screen GDI surface".to_string());
+			}
+
+			let old_bitmap = SelectObject(hdc, bitmap);
+			SetBkMode(hdc, TRANSPARENT);
+			Ok(Self {
+				hdc,
+				bitmap,
+				old_bitmap,
+				width,
+				height,
+				font_face: font_face.to_string(),
+			})
+		}
+	}
+
+	/// Copies the finished frame to the top-left of the window's client area.
+	pub fn present(&self, hwnd: HWND) -> Result<(), String> {
+		unsafe {
+			let target = GetDC(hwnd);
+			if target.is_invalid() {
+				return Err("GetDC failed".to_string());
+			}
+			let (w, h) = (self.width as i32, self.height as i32);
+			let result = BitBlt(target, 0, 0, w, h, self.hdc, 0, 0, SRCCOPY);
+			ReleaseDC(hwnd, target);
+			result.map_err(|e| e.to_string())
+		}
+	}
+
+	fn font(&self, style: TextStyle) -> AutoGdiObject {
+		let weight = match style.weight {
+			FontWeight::SemiBold => FW_SEMIBOLD,
+			FontWeight::Bold => FW_BOLD,
+		};
+		// A negative height selects by em size rather than cell height.
+		create_font(-(style.size.round() as i32), weight.0 as i32, &self.font_face)
+	}
+}
+
+impl Drop for GdiCanvas {
+	fn drop(&mut self) {
+		unsafe {
+			SelectObject(self.hdc, self.old_bitmap);
+			let _ = DeleteObject(self.bitmap);
+			let _ = DeleteDC(self.hdc);
+		}
+	}
+}
+
+fn to_points(points: &[Point]) -> Vec<POINT> {
+	points
+		.iter()
+		.map(|&(x, y)| POINT { x: x.round() as i32, y: y.round() as i32 })
+		.collect()
+}
+
+fn wide(text: &str) -> Vec<u16> {
+	text.encode_utf16().collect()
+}
+
+/// Owns a GDI object and deletes it when dropped.
+struct AutoGdiObject {
+	handle: HGDIOBJ,
+}
+
+impl AutoGdiObject {
+	fn new(handle: HGDIOBJ) -> Self {
+		Self { handle }
+	}
+
+	fn handle(&self) -> HGDIOBJ {
+		self.handle
+	}
+
+	fn as_brush(&self) -> HBRUSH {
+		HBRUSH(self.handle.0)
+	}
+}
+
+impl Drop for AutoGdiObject {
+	fn drop(&mut self) {
+		unsafe {
+			if !self.handle.is_invalid() {
+				let _ = DeleteObject(self.handle);
+			}
+		}
+	}
+}
+
+fn create_solid_brush(color: u32) -> AutoGdiObject {
+	unsafe {
+		let handle = CreateSolidBrush(COLORREF(color));
+		AutoGdiObject::new(HGDIOBJ(handle.0 as _))
+	}
+}
+
+fn create_pen(style: PEN_STYLE, width: i32, color: u32) -> AutoGdiObject {
+	unsafe {
+		let handle = CreatePen(style, width, COLORREF(color));
+		AutoGdiObject::new(HGDIOBJ(handle.0 as _))
+	}
+}
+
+fn create_font(height: i32, weight: i32, face: &str) -> AutoGdiObject {
+	unsafe {
+		let face_wide: Vec<u16> = face.encode_utf16().chain(std::iter::once(0)).collect();
+		let handle = CreateFontW(
+			height,
+			0,
+			0,
+			0,
+			weight,
+			0,
+			0,
+			0,
+			DEFAULT_CHARSET.0 as u32,
+			OUT_DEFAULT_PRECIS.0 as u32,
+			CLIP_DEFAULT_PRECIS.0 as u32,
+			DEFAULT_QUALITY.0 as u32,
+			FF_DONTCARE.0 as u32,
+			PCWSTR::from_raw(face_wide.as_ptr())
+		);
+		AutoGdiObject::new(HGDIOBJ(handle.0 as _))
+	}
+}
+
+/// Objects selected into a DC, put back when the scope ends.
+struct DcScope<'a> {
+	hdc: HDC,
+	saved_objects: Vec<HGDIOBJ>,
+	_marker: std::marker::PhantomData<&'a HDC>,
+}
+
+impl<'a> DcScope<'a> {
+	fn new(hdc: HDC) -> Self {
+		Self {
+			hdc,
+			saved_objects: Vec::new(),
+			_marker: std::marker::PhantomData,
+		}
+	}
+
+	fn select(&mut self, obj: &AutoGdiObject) {
+		unsafe {
+			let old = SelectObject(self.hdc, obj.handle());
+			self.saved_objects.push(old);
+		}
+	}
+}
+
+impl<'a> Drop for DcScope<'a> {
+	fn drop(&mut self) {
+		unsafe {
+			for old in self.saved_objects.iter().rev() {
+				let _ = SelectObject(self.hdc, *old);
+			}
+		}
+	}
+}
+
+impl Canvas for GdiCanvas {
+	fn size(&self) -> (u32, u32) {
+		(self.width, self.height)
+	}
+
+	fn fill_rect(&mut self, rect: Rect, color: u32) {
+		let rect = RECT {
+			left: rect.x.round() as i32,
+			top: rect.y.round() as i32,
+			right: rect.right().round() as i32,
+			bottom: rect.bottom().round() as i32,
+		};
+		let brush = create_solid_brush(color);
+		unsafe {
+			FillRect(self.hdc, &rect, brush.as_brush());
+		}
+	}
+
+	fn fill_polygon(&mut self, points: &[Point], color: u32) {
+		let brush = create_solid_brush(color);
+		let pen = create_pen(PS_NULL, 0, 0);
+		let mut scope = DcScope::new(self.hdc);
+		scope.select(&brush);
+		scope.select(&pen);
+		unsafe {
+			let _ = Polygon(self.hdc, &to_points(points));
+		}
+	}
+
+	fn polyline(&mut self, points: &[Point], width: f32, color: u32) {
+		let pen = create_pen(PS_SOLID, width.round() as i32, color);
+		let mut scope = DcScope::new(self.hdc);
+		scope.select(&pen);
+		unsafe {
+			let _ = Polyline(self.hdc, &to_points(points));
+		}
+	}
+
+	fn text(&mut self, text: &str, x: f32, y: f32, style: TextStyle, color: u32) {
+		let font = self.font(style);
+		let mut scope = DcScope::new(self.hdc);
+		scope.select(&font);
+		unsafe {
+			SetTextColor(self.hdc, windows::Win32::Foundation::COLORREF(color));
+			let _ = TextOutW(self.hdc, x.round() as i32, y.round() as i32, &wide(text));
+		}
+	}
+
+	fn measure_text(&mut self, text: &str, style: TextStyle) -> (f32, f32) {
+		let font = self.font(style);
+		let mut scope = DcScope::new(self.hdc);
+		scope.select(&font);
+		let mut size = SIZE::default();
+		unsafe {
+			let _ = GetTextExtentPoint32W(self.hdc, &wide(text), &mut size);
+		}
+		(size.cx as f32, size.cy as f32)
+	}
+}
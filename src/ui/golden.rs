@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+use std::time::{ Duration, Instant };
+use tiny_skia::{ ColorU8, IntSize, Pixmap };
+
+use crate::core::state::{ AppState, SampleTime, ViewMode };
+use crate::sys::clock::ClockTime;
+use crate::ui::raster::Raster;
+use crate::utils::format::NumberFormat;
+
+/// Per-pixel colour distance (0..1 of the largest possible YIQ delta) above
+/// which a pixel counts as changed. Absorbs antialiasing noise between platforms.
+const PIXEL_THRESHOLD: f64 = 0.1;
+/// Share of changed pixels tolerated before an image is reported as different.
+const MAX_CHANGED_RATIO: f64 = 0.005;
+/// Squared YIQ distance between black and white.
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+/// Four minutes of made-up traffic: a slow download wave with bursts and
+/// a smaller, faster upload wave.
+pub fn synthetic_state(view_mode: ViewMode) -> AppState {
+	let mut state = AppState::new();
+	state.view_mode = view_mode;
+	state.numbers = NumberFormat::POINT;
+	let start = Instant::now();
+	for i in 0..240 {
+		let t = i as f64;
+		let burst = if i % 60 > 50 { 3_000_000.0 } else { 0.0 };
+		let down = 1_500_000.0 * (1.0 + (t / 15.0).sin()) + burst;
+		let up = 200_000.0 * (1.0 + (t / 5.0).cos());
+		let time = SampleTime {
+			at: start + Duration::from_secs(i),
+			clock: ClockTime::from_seconds(14 * 3600 + i as u32),
+		};
+		state.record(down as u64, up as u64);
+		state.record_time(time);
+	}
+	state.down_bps = *state.down_history.back().unwrap();
+	state.up_bps = *state.up_history.back().unwrap();
+	state
+}
+
+fn golden_dir() -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn diff_dir() -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target").join("golden-diff")
+}
+
+/// Compares `raster` against `tests/golden/<name>.png`. With `NETFLUX_UPDATE_GOLDEN`
+/// set the reference is rewritten instead; on a mismatch the actual image and a
+/// diff are written to `target/golden-diff/`.
+pub fn check(name: &str, raster: &Raster) -> Result<(), String> {
+	let (width, height) = (raster.width(), raster.height());
+	let actual = raster.to_rgba();
+	let reference_path = golden_dir().join(format!("{}.png", name));
+
+	if std::env::var_os("NETFLUX_UPDATE_GOLDEN").is_some() {
+		return save_png(&reference_path, width, height, &actual);
+	}
+
+	let reference = Pixmap::load_png(&reference_path).map_err(|e|
+		format!(
+			"{}: {} (run with NETFLUX_UPDATE_GOLDEN=1 to create it)",
+			reference_path.display(),
+			e
+		)
+	)?;
+	if (reference.width(), reference.height()) != (width, height) {
+		save_png(&diff_dir().join(format!("{}.actual.png", name)), width, height, &actual)?;
+		return Err(
+			format!(
+				"{}: size {}x{} differs from reference {}x{}",
+				name,
+				width,
+				height,
+				reference.width(),
+				reference.height()
+			)
+		);
+	}
+
+	let expected = straight_rgba(&reference);
+	let mut diff = Vec::with_capacity(actual.len());
+	let mut changed = 0usize;
+	for (a, e) in actual.chunks_exact(4).zip(expected.chunks_exact(4)) {
+		if yiq_delta(a, e) > PIXEL_THRESHOLD * PIXEL_THRESHOLD * MAX_YIQ_DELTA {
+			changed += 1;
+			diff.extend_from_slice(&[255, 0, 0, 255]);
+		} else {
+			// Faded greyscale of the reference, so the red marks stand out.
+			let y = luma(blend_white(e)) as u8;
+			let faded = 255 - (255 - y) / 4;
+			diff.extend_from_slice(&[faded, faded, faded, 255]);
+		}
+	}
+
+	let pixels = (width as usize) * (height as usize);
+	if (changed as f64) > (pixels as f64) * MAX_CHANGED_RATIO {
+		save_png(&diff_dir().join(format!("{}.actual.png", name)), width, height, &actual)?;
+		save_png(&diff_dir().join(format!("{}.diff.png", name)), width, height, &diff)?;
+		return Err(
+			format!(
+				"{}: {} of {} pixels differ (see {})",
+				name,
+				changed,
+				pixels,
+				diff_dir().display()
+			)
+		);
+	}
+	Ok(())
+}
+
+/// Checks every case and panics with all mismatches at once.
+pub fn assert_all(cases: impl IntoIterator<Item = (String, Raster)>) {
+	let failures: Vec<String> = cases
+		.into_iter()
+		.filter_map(|(name, raster)| check(&name, &raster).err())
+		.collect();
+	assert!(failures.is_empty(), "golden images differ:\n{}", failures.join("\n"));
+}
+
+fn blend_white(rgba: &[u8]) -> [f64; 3] {
+	let a = (rgba[3] as f64) / 255.0;
+	[0, 1, 2].map(|i| 255.0 + ((rgba[i] as f64) - 255.0) * a)
+}
+
+fn luma([r, g, b]: [f64; 3]) -> f64 {
+	r * 0.29889531 + g * 0.58662247 + b * 0.11448223
+}
+
+/// Squared perceptual distance in YIQ space, weighted as in pixelmatch.
+fn yiq_delta(a: &[u8], b: &[u8]) -> f64 {
+	let [r1, g1, b1] = blend_white(a);
+	let [r2, g2, b2] = blend_white(b);
+	let y = luma([r1, g1, b1]) - luma([r2, g2, b2]);
+	let i = r1 * 0.59597799 - g1 * 0.2741761 - b1 * 0.32180189 -
+		(r2 * 0.59597799 - g2 * 0.2741761 - b2 * 0.32180189);
+	let q = r1 * 0.21147017 - g1 * 0.52261711 + b1 * 0.31114694 -
+		(r2 * 0.21147017 - g2 * 0.52261711 + b2 * 0.31114694);
+	0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+fn straight_rgba(pixmap: &Pixmap) -> Vec<u8> {
+	pixmap
+		.pixels()
+		.iter()
+		.flat_map(|pixel| {
+			let c = pixel.demultiply();
+			[c.red(), c.green(), c.blue(), c.alpha()]
+		})
+		.collect()
+}
+
+fn save_png(path: &std::path::Path, width: u32, height: u32, rgba: &[u8]) -> Result<(), String> {
+	let size = IntSize::from_wh(width, height).ok_or("Invalid image size")?;
+	let mut data = Vec::with_capacity(rgba.len());
+	for c in rgba.chunks_exact(4) {
+		let p = ColorU8::from_rgba(c[0], c[1], c[2], c[3]).premultiply();
+		data.extend_from_slice(&[p.red(), p.green(), p.blue(), p.alpha()]);
+	}
+	let pixmap = Pixmap::from_vec(data, size).ok_or("Invalid image data")?;
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+	}
+	pixmap.save_png(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
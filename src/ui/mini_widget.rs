@@ -0,0 +1,97 @@
+use winit::dpi::{ LogicalSize, PhysicalPosition, PhysicalSize };
+use winit::window::{ Window, WindowBuilder, WindowId, WindowLevel };
+use winit::event_loop::EventLoopWindowTarget;
+use winit::platform::windows::WindowBuilderExtWindows;
+
+use crate::core::state::AppState;
+use crate::sys::display;
+use crate::ui::panel::{ self, WIDGET_SIZE };
+use crate::ui::placement::{ self, ScreenRect };
+use crate::ui::popup::{ self, SCREEN_GAP };
+
+/// The always-on-top mini widget: both current rates in a small window of
+/// its own, which stays up while the popup comes and goes.
+pub struct MiniWidget {
+	window: Window,
+	/// Where the user dragged the widget to.
+	position: Option<(i32, i32)>,
+}
+
+impl MiniWidget {
+	pub fn new<T>(event_loop: &EventLoopWindowTarget<T>, position: Option<(i32, i32)>) -> Self {
+		let (width, height) = WIDGET_SIZE;
+		let window = WindowBuilder::new()
+			.with_title("NetFlux Widget")
+			.with_decorations(false)
+			.with_window_level(WindowLevel::AlwaysOnTop)
+			.with_skip_taskbar(true)
+			.with_resizable(false)
+			.with_inner_size(LogicalSize::new(width as f64, height as f64))
+			.with_visible(false)
+			.build(event_loop)
+			.expect("Failed to create widget window");
+
+		Self { window, position }
+	}
+
+	pub fn id(&self) -> WindowId {
+		self.window.id()
+	}
+
+	/// Shows the widget where it was dragged to, or beside `anchor`, the
+	/// tray icon, the first time.
+	pub fn show(&self, anchor: Option<ScreenRect>) {
+		let center = anchor.map(|anchor| anchor.center());
+		if let Some((screen, scale)) = display::screen_at(self.position.or(center)) {
+			let (width, height) = WIDGET_SIZE;
+			let logical = LogicalSize::new(width as f64, height as f64);
+			let size: PhysicalSize<i32> = logical.to_physical(scale);
+			let size = (size.width, size.height);
+			let (x, y) = match self.position {
+				Some(position) => placement::keep_inside(position, size, screen.work_area),
+				None => {
+					let gap = (SCREEN_GAP * scale).round() as i32;
+					placement::place(size, anchor, screen, gap)
+				}
+			};
+			// Moving first lets a DPI change settle before the size is set.
+			self.window.set_outer_position(PhysicalPosition::new(x, y));
+			let _ = self.window.request_inner_size(PhysicalSize::new(size.0, size.1));
+		}
+		self.window.set_visible(true);
+		self.window.request_redraw();
+	}
+
+	pub fn hide(&self) {
+		self.window.set_visible(false);
+	}
+
+	fn is_visible(&self) -> bool {
+		self.window.is_visible().unwrap_or(false)
+	}
+
+	pub fn update(&self) {
+		if self.is_visible() {
+			self.window.request_redraw();
+		}
+	}
+
+	pub fn drag(&self) {
+		let _ = self.window.drag_window();
+	}
+
+	/// Records where the user dragged the widget, returning the position if
+	/// it changed.
+	pub fn moved(&mut self, position: PhysicalPosition<i32>) -> Option<(i32, i32)> {
+		let position = Some((position.x, position.y));
+		if !self.is_visible() || self.position == position {
+			return None;
+		}
+		self.position = position;
+		position
+	}
+
+	pub fn draw(&self, state: &AppState) {
+		popup::paint(&self.window, &state.theme.font, |canvas| panel::draw_widget(state, canvas));
+	}
+}
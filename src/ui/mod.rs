@@ -1,3 +1,33 @@
+#[cfg(any(tray, feature = "tui", test))]
+pub mod axis;
+#[cfg(any(tray, test))]
+pub mod canvas;
+pub mod export;
+#[cfg(any(tray, feature = "tui", test))]
+pub mod details;
+#[cfg(any(tray, test))]
+pub mod font;
+#[cfg(tray)]
+pub mod gdi_canvas;
+#[cfg(test)]
+mod golden;
+#[cfg(tray)]
+pub mod mini_widget;
+#[cfg(any(tray, test))]
+pub mod panel;
+#[cfg(any(tray, test))]
+pub mod placement;
+#[cfg(tray)]
 pub mod popup;
+#[cfg(any(tray, test))]
+pub mod raster;
+#[cfg(any(tray, feature = "tui", test))]
+pub mod resample;
+pub mod speed;
+#[cfg(any(tray, test))]
+pub mod svg;
+#[cfg(any(tray, test))]
 pub mod tray;
 pub mod theme;
+#[cfg(any(tray, test))]
+pub mod widgets;
@@ -0,0 +1,522 @@
+use std::collections::VecDeque;
+
+use crate::core::state::{ AppState, ViewMode, HISTORY_LEN };
+use crate::i18n::{ self, tr };
+use crate::ui::axis::{ Axis, MIN_GRAPH_SCALE };
+use crate::ui::canvas::{ Canvas, FontWeight, Point, Rect, TextStyle };
+use crate::ui::details;
+use crate::ui::theme::Theme;
+use crate::ui::widgets::{ AreaGraph, Readout, Tooltip };
+use crate::utils::format::{ format_relative, format_speed_full };
+
+/// Room above each graph for the readout.
+const HEADER_HEIGHT: f32 = 50.0;
+/// Space left below each graph.
+const GRAPH_MARGIN: f32 = 10.0;
+const PADDING_X: f32 = 16.0;
+const PADDING_Y: f32 = 12.0;
+/// Height of the row that opens and closes the details section.
+const DETAILS_TOGGLE_HEIGHT: u32 = 28;
+const DETAILS_LINE_HEIGHT: u32 = 16;
+const DETAILS_STYLE: TextStyle = TextStyle::new(11.0, FontWeight::SemiBold);
+/// Space between the widest details label and the values.
+const DETAILS_COLUMN_GAP: f32 = 10.0;
+const PIN_SIZE: f32 = 16.0;
+/// Distance of the pin button from the top and right edges.
+const PIN_MARGIN: f32 = 6.0;
+/// Mini widget size in logical pixels.
+pub const WIDGET_SIZE: (u32, u32) = (150, 52);
+const WIDGET_STYLE: TextStyle = TextStyle::new(13.0, FontWeight::Bold);
+
+/// Height of the details section under the graphs: the toggle row, plus a
+/// line per entry while it is open.
+pub fn details_height(state: &AppState) -> u32 {
+	if !state.details_expanded {
+		return DETAILS_TOGGLE_HEIGHT;
+	}
+	let lines = details::lines(state.details.as_ref(), state.numbers).len() as u32;
+	DETAILS_TOGGLE_HEIGHT + lines * DETAILS_LINE_HEIGHT + (PADDING_Y as u32)
+}
+
+/// Popup controls that react to a click.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Hit {
+	DetailsToggle,
+	Pin,
+}
+
+/// The control at `point` on a canvas of `size`, if any.
+pub fn hit_test(state: &AppState, size: (u32, u32), point: Point) -> Option<Hit> {
+	let (width, height) = size;
+	let top = height.saturating_sub(details_height(state)) as f32;
+	if pin_rect(width).contains(point) {
+		Some(Hit::Pin)
+	} else if Rect::new(0.0, top, width as f32, DETAILS_TOGGLE_HEIGHT as f32).contains(point) {
+		Some(Hit::DetailsToggle)
+	} else {
+		None
+	}
+}
+
+fn pin_rect(width: u32) -> Rect {
+	Rect::new((width as f32) - PIN_MARGIN - PIN_SIZE, PIN_MARGIN, PIN_SIZE, PIN_SIZE)
+}
+
+/// Draws the popup contents for `state`, laid out to fill the canvas, with
+/// a crosshair at the sample under `hover` if the cursor is over a graph.
+pub fn draw(state: &AppState, hover: Option<Point>, canvas: &mut dyn Canvas) {
+	let (width, height) = canvas.size();
+	let theme = &state.theme;
+	canvas.fill_rect(Rect::new(0.0, 0.0, width as f32, height as f32), theme.background);
+
+	let details = details_height(state).min(height) as f32;
+	let (bounds, details) = Rect::new(0.0, 0.0, width as f32, height as f32).split_top(
+		(height as f32) - details
+	);
+	draw_details(state, canvas, details);
+	draw_graphs(state, hover, canvas, bounds);
+	draw_pin(canvas, pin_rect(width), state.pinned, theme);
+}
+
+/// A push pin, upright when pinned and tilted when not.
+fn draw_pin(canvas: &mut dyn Canvas, rect: Rect, pinned: bool, theme: &Theme) {
+	// Outline on a 16 × 16 grid: cap, body, collar, then the needle.
+	const HEAD: [Point; 10] = [
+		(5.0, 1.0),
+		(11.0, 1.0),
+		(11.0, 3.0),
+		(10.0, 3.0),
+		(10.0, 7.0),
+		(13.0, 10.0),
+		(3.0, 10.0),
+		(6.0, 7.0),
+		(6.0, 3.0),
+		(5.0, 3.0),
+	];
+	const NEEDLE: [Point; 2] = [(8.0, 10.0), (8.0, 15.0)];
+
+	let (angle, color): (f32, u32) = if pinned {
+		(0.0, theme.text)
+	} else {
+		(std::f32::consts::FRAC_PI_4, theme.text_dim)
+	};
+	let (sin, cos) = angle.sin_cos();
+	let scale = rect.w / 16.0;
+	let (cx, cy) = (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+	let transform = |&(x, y): &Point| {
+		let (x, y) = ((x - 8.0) * scale, (y - 8.0) * scale);
+		(cx + x * cos - y * sin, cy + x * sin + y * cos)
+	};
+	let head: Vec<Point> = HEAD.iter().map(transform).collect();
+	let needle: Vec<Point> = NEEDLE.iter().map(transform).collect();
+	canvas.fill_polygon(&head, color);
+	canvas.polyline(&needle, 1.5 * scale, color);
+}
+
+/// The mini widget: both current rates, coloured like their graphs.
+pub fn draw_widget(state: &AppState, canvas: &mut dyn Canvas) {
+	let (width, height) = canvas.size();
+	let theme = &state.theme;
+	let bounds = Rect::new(0.0, 0.0, width as f32, height as f32);
+	canvas.fill_rect(bounds, theme.background);
+
+	let (units, numbers) = (state.units, state.numbers);
+	let lines = [
+		(format!("↓ {}", format_speed_full(state.down_bps, units, numbers)), theme.download_line),
+		(format!("↑ {}", format_speed_full(state.up_bps, units, numbers)), theme.upload_line),
+	];
+	for ((text, color), row) in lines.iter().zip(bounds.inset(PADDING_X / 2.0, 4.0).rows(2)) {
+		let (_, text_height) = canvas.measure_text(text, WIDGET_STYLE);
+		let y = (row.y + (row.h - text_height) / 2.0).round();
+		canvas.text(text, row.x, y, WIDGET_STYLE, *color);
+	}
+}
+
+/// Draws the readouts and graphs for the view mode into `bounds`.
+pub fn draw_graphs(state: &AppState, hover: Option<Point>, canvas: &mut dyn Canvas, bounds: Rect) {
+	let theme = &state.theme;
+	let rects = bounds.rows(if state.view_mode == ViewMode::All { 2 } else { 1 });
+	if state.view_mode == ViewMode::Overlay {
+		draw_overlay(state, canvas, rects[0]);
+	} else {
+		let mut sections = Vec::with_capacity(2);
+		if state.view_mode != ViewMode::UploadOnly {
+			sections.push(Section {
+				label: tr("popup-download"),
+				value: format_speed_full(state.down_bps, state.units, state.numbers),
+				history: &state.down_history,
+				line: theme.download_line,
+				fill: theme.download_fill,
+			});
+		}
+		if state.view_mode != ViewMode::DownloadOnly {
+			sections.push(Section {
+				label: tr("popup-upload"),
+				value: format_speed_full(state.up_bps, state.units, state.numbers),
+				history: &state.up_history,
+				line: theme.upload_line,
+				fill: theme.upload_fill,
+			});
+		}
+		for (section, &rect) in sections.iter().zip(&rects) {
+			section.draw(canvas, rect, state);
+		}
+	}
+
+	if let Some(point) = hover {
+		draw_crosshair(state, canvas, bounds, &rects, point);
+	}
+}
+
+/// Both directions in one chart on a shared scale: download filled above
+/// the centre line, upload as a line mirrored below it.
+fn draw_overlay(state: &AppState, canvas: &mut dyn Canvas, rect: Rect) {
+	let theme = &state.theme;
+	let (header, _) = rect.split_top(HEADER_HEIGHT);
+	let (above, below) = {
+		let graph = graph_rect(rect);
+		graph.split_top((graph.h / 2.0).round())
+	};
+
+	let peak = state.down_history.iter().chain(&state.up_history).copied().max().unwrap_or(0);
+	let axis = Axis::new(state.graph_scale, peak, MIN_GRAPH_SCALE, state.units);
+	let halves = [
+		(&state.down_history, theme.download_line, Some(theme.download_fill), false, above),
+		(&state.up_history, theme.upload_line, None, true, below),
+	];
+	for (history, line, fill, flipped, graph) in halves {
+		let graph_widget = AreaGraph {
+			history,
+			capacity: HISTORY_LEN,
+			axis: &axis,
+			units: state.units,
+			numbers: state.numbers,
+			line,
+			fill,
+			flipped,
+			theme,
+		};
+		graph_widget.draw(canvas, graph);
+	}
+
+	let inner = header.inset(PADDING_X, PADDING_Y);
+	let half = inner.w / 2.0;
+	let readouts = [
+		(tr("popup-download"), state.down_bps, inner.x),
+		(tr("popup-upload"), state.up_bps, inner.x + half),
+	];
+	for (label, speed, x) in readouts {
+		let value = format_speed_full(speed, state.units, state.numbers);
+		let readout = Readout { label: &label, value: &value, compact: true, theme };
+		readout.draw(canvas, Rect::new(x, inner.y, half, inner.h));
+	}
+}
+
+fn graph_rect(section: Rect) -> Rect {
+	let (_, rest) = section.split_top(HEADER_HEIGHT);
+	rest.split_top(rest.h - GRAPH_MARGIN).0
+}
+
+/// Index of the history sample drawn nearest to `x`, if there is one.
+fn sample_at(graph: Rect, x: f32, len: usize) -> Option<usize> {
+	if x < graph.x || x >= graph.right() {
+		return None;
+	}
+	let index = (((x - graph.x) / graph.w) * (HISTORY_LEN as f32)).round() as usize;
+	let index = index.min(HISTORY_LEN - 1);
+	(index < len).then_some(index)
+}
+
+fn draw_crosshair(
+	state: &AppState,
+	canvas: &mut dyn Canvas,
+	bounds: Rect,
+	sections: &[Rect],
+	point: Point
+) {
+	let Some(&section) = sections.iter().find(|rect| rect.contains(point)) else {
+		return;
+	};
+	let graph = graph_rect(section);
+	let Some(index) = sample_at(graph, point.0, state.down_history.len()) else {
+		return;
+	};
+
+	let x = (graph.x + ((index as f32) * graph.w) / (HISTORY_LEN as f32)).round();
+	for &section in sections {
+		let graph = graph_rect(section);
+		canvas.fill_rect(Rect::new(x, graph.y, 1.0, graph.h), state.theme.text_dim);
+	}
+
+	let (units, numbers) = (state.units, state.numbers);
+	let mut lines = Vec::with_capacity(3);
+	if let (Some(clock), Some(age)) = (state.sample_clock(index), state.sample_age(index)) {
+		lines.push(format!("{} · {}", clock, format_relative(age, i18n::current())));
+	}
+	if let Some(&down) = state.down_history.get(index) {
+		lines.push(format!("↓ {}", format_speed_full(down, units, numbers)));
+	}
+	if let Some(&up) = state.up_history.get(index) {
+		lines.push(format!("↑ {}", format_speed_full(up, units, numbers)));
+	}
+	Tooltip { lines: &lines, theme: &state.theme }.draw(canvas, bounds, (x, point.1));
+}
+
+/// The toggle row, with the interface name on the right, and the details
+/// below it while open.
+fn draw_details(state: &AppState, canvas: &mut dyn Canvas, rect: Rect) {
+	let theme = &state.theme;
+	canvas.fill_rect(Rect::new(rect.x, rect.y, rect.w, 1.0), theme.track);
+
+	let (toggle, rest) = rect.split_top(DETAILS_TOGGLE_HEIGHT as f32);
+	let arrow = if state.details_expanded { "▾" } else { "▸" };
+	let label = format!("{} {}", arrow, tr("popup-details"));
+	let (_, label_height) = canvas.measure_text(&label, DETAILS_STYLE);
+	let y = (toggle.y + (toggle.h - label_height) / 2.0).round();
+	canvas.text(&label, toggle.x + PADDING_X, y, DETAILS_STYLE, theme.text_dim);
+	let (name_width, _) = canvas.measure_text(&state.interface_name, DETAILS_STYLE);
+	let x = toggle.right() - PADDING_X - name_width;
+	canvas.text(&state.interface_name, x, y, DETAILS_STYLE, theme.text_dim);
+
+	if !state.details_expanded {
+		return;
+	}
+	let lines = details::lines(state.details.as_ref(), state.numbers);
+	let column = lines
+		.iter()
+		.map(|(label, _)| canvas.measure_text(label, DETAILS_STYLE).0)
+		.fold(0.0, f32::max);
+	let mut y = rest.y;
+	for (label, value) in &lines {
+		canvas.text(label, rest.x + PADDING_X, y, DETAILS_STYLE, theme.text_dim);
+		let x = (rest.x + PADDING_X + column + DETAILS_COLUMN_GAP).round();
+		canvas.text(value, x, y, DETAILS_STYLE, theme.text);
+		y += DETAILS_LINE_HEIGHT as f32;
+	}
+}
+
+struct Section<'a> {
+	label: String,
+	value: String,
+	history: &'a VecDeque<u64>,
+	line: u32,
+	fill: u32,
+}
+
+impl Section<'_> {
+	fn draw(&self, canvas: &mut dyn Canvas, rect: Rect, state: &AppState) {
+		let (header, _) = rect.split_top(HEADER_HEIGHT);
+		let graph = graph_rect(rect);
+
+		let peak = self.history.iter().copied().max().unwrap_or(0);
+		let axis = Axis::new(state.graph_scale, peak, MIN_GRAPH_SCALE, state.units);
+		let graph_widget = AreaGraph {
+			history: self.history,
+			capacity: HISTORY_LEN,
+			axis: &axis,
+			units: state.units,
+			numbers: state.numbers,
+			line: self.line,
+			fill: Some(self.fill),
+			flipped: false,
+			theme: &state.theme,
+		};
+		graph_widget.draw(canvas, graph);
+
+		let readout = Readout {
+			label: &self.label,
+			value: &self.value,
+			compact: false,
+			theme: &state.theme,
+		};
+		readout.draw(canvas, header.inset(PADDING_X, PADDING_Y));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::i18n;
+	use crate::ui::golden::{ self, synthetic_state };
+	use crate::ui::raster::Raster;
+	use crate::core::settings::PopupSize;
+	use crate::core::state::GraphScale;
+	use crate::sys::netinfo::{ InterfaceDetails, Media };
+	use crate::ui::theme::{ Theme, PRESETS };
+	use std::time::Duration;
+
+	fn render(state: &AppState) -> Raster {
+		render_at(state, PopupSize::default())
+	}
+
+	fn render_at(state: &AppState, size: PopupSize) -> Raster {
+		let (width, height) = size.window(state.view_mode);
+		let mut raster = Raster::new(width, height + details_height(state)).unwrap();
+		draw(state, None, &mut raster);
+		raster
+	}
+
+	#[test]
+	fn popup_matches_golden_images() {
+		i18n::init(Some("en"));
+		let modes = [
+			("all", ViewMode::All),
+			("download", ViewMode::DownloadOnly),
+			("upload", ViewMode::UploadOnly),
+			("overlay", ViewMode::Overlay),
+		];
+		let cases = modes.iter().map(|&(name, mode)| {
+			(format!("popup-{}", name), render(&synthetic_state(mode)))
+		});
+		golden::assert_all(cases);
+	}
+
+	#[test]
+	fn themes_match_golden_images() {
+		i18n::init(Some("en"));
+		let cases = PRESETS.iter().filter(|&&name| name != "dark").map(|&name| {
+			let mut state = synthetic_state(ViewMode::All);
+			state.theme = Theme::preset(name).unwrap();
+			(format!("popup-theme-{}", name), render(&state))
+		});
+		golden::assert_all(cases);
+	}
+
+	#[test]
+	fn log_scale_matches_golden_image() {
+		i18n::init(Some("en"));
+		let mut state = synthetic_state(ViewMode::All);
+		state.graph_scale = GraphScale::Log;
+		golden::assert_all([("popup-log".to_string(), render(&state))]);
+	}
+
+	#[test]
+	fn resized_popups_match_golden_images() {
+		i18n::init(Some("en"));
+		let sizes = [
+			("narrow", PopupSize::MIN),
+			("wide", PopupSize { width: 520, section_height: 160 }),
+		];
+		let cases = sizes.iter().map(|&(name, size)| {
+			(format!("popup-{}", name), render_at(&synthetic_state(ViewMode::All), size))
+		});
+		golden::assert_all(cases);
+	}
+
+	fn synthetic_details() -> InterfaceDetails {
+		InterfaceDetails {
+			alias: "Ethernet".to_string(),
+			description: "Intel(R) Ethernet Controller I225-V".to_string(),
+			mac: Some("3C:7C:3F:1A:2B:4C".to_string()),
+			ipv4: vec!["192.168.1.23".parse().unwrap()],
+			ipv6: vec!["fe80::3e7c:3fff:fe1a:2b4c".parse().unwrap()],
+			gateways: vec!["192.168.1.1".parse().unwrap()],
+			dns: vec!["192.168.1.1".parse().unwrap(), "1.1.1.1".parse().unwrap()],
+			link_speed: Some(2_500_000_000),
+			media: Media::Ethernet,
+			mtu: Some(1500),
+			uptime: Some(Duration::from_secs(3 * 3600 + 25 * 60)),
+		}
+	}
+
+	#[test]
+	fn details_match_golden_images() {
+		i18n::init(Some("en"));
+		let mut state = synthetic_state(ViewMode::DownloadOnly);
+		state.interface_name = "Ethernet".to_string();
+		state.details = Some(synthetic_details());
+		let collapsed = render(&state);
+		state.details_expanded = true;
+		let expanded = render(&state);
+		state.details = None;
+		let unavailable = render(&state);
+		golden::assert_all([
+			("popup-details-collapsed".to_string(), collapsed),
+			("popup-details-expanded".to_string(), expanded),
+			("popup-details-unavailable".to_string(), unavailable),
+		]);
+	}
+
+	#[test]
+	fn details_toggle_is_the_row_under_the_graphs() {
+		let mut state = synthetic_state(ViewMode::All);
+		state.details = Some(synthetic_details());
+		let (width, height) = PopupSize::default().window(ViewMode::All);
+		let size = (width, height + details_height(&state));
+		let toggle = Some(Hit::DetailsToggle);
+		assert_eq!(hit_test(&state, size, (10.0, (height as f32) + 5.0)), toggle);
+		assert_eq!(hit_test(&state, size, (10.0, (height as f32) - 5.0)), None);
+
+		state.details_expanded = true;
+		// Interface, adapter, type, MAC, one IPv4, one IPv6, gateway, two
+		// DNS servers, speed, MTU and uptime.
+		assert_eq!(details_height(&state), DETAILS_TOGGLE_HEIGHT + 12 * DETAILS_LINE_HEIGHT + 12);
+		let size = (width, height + details_height(&state));
+		assert_eq!(hit_test(&state, size, (10.0, (height as f32) + 5.0)), toggle);
+		assert_eq!(hit_test(&state, size, (10.0, (height as f32) + 40.0)), None);
+	}
+
+	#[test]
+	fn pin_is_in_the_top_right_corner() {
+		let state = synthetic_state(ViewMode::All);
+		let (width, height) = PopupSize::default().window(ViewMode::All);
+		let size = (width, height + details_height(&state));
+		assert_eq!(hit_test(&state, size, ((width as f32) - 12.0, 12.0)), Some(Hit::Pin));
+		assert_eq!(hit_test(&state, size, ((width as f32) - 40.0, 12.0)), None);
+	}
+
+	#[test]
+	fn pin_and_widget_match_golden_images() {
+		i18n::init(Some("en"));
+		let mut state = synthetic_state(ViewMode::All);
+		state.pinned = true;
+		let pinned = render(&state);
+		let mut widget = Raster::new(WIDGET_SIZE.0, WIDGET_SIZE.1).unwrap();
+		draw_widget(&state, &mut widget);
+		state.theme = Theme::preset("light").unwrap();
+		let mut light = Raster::new(WIDGET_SIZE.0, WIDGET_SIZE.1).unwrap();
+		draw_widget(&state, &mut light);
+		golden::assert_all([
+			("popup-pinned".to_string(), pinned),
+			("widget".to_string(), widget),
+			("widget-light".to_string(), light),
+		]);
+	}
+
+	#[test]
+	fn window_size_round_trips_per_section() {
+		let size = PopupSize { width: 300, section_height: 150 };
+		assert_eq!(size.window(ViewMode::All), (300, 300));
+		assert_eq!(size.window(ViewMode::UploadOnly), (300, 150));
+		assert_eq!(PopupSize::from_window(ViewMode::All, 300, 300), size);
+		assert_eq!(PopupSize::from_window(ViewMode::DownloadOnly, 300, 150), size);
+		assert_eq!(size.window(ViewMode::Overlay), (300, 225));
+		assert_eq!(PopupSize::from_window(ViewMode::Overlay, 300, 225), size);
+		assert_eq!(PopupSize::from_window(ViewMode::All, 10, 10), PopupSize::MIN);
+	}
+
+	#[test]
+	fn hover_matches_golden_images() {
+		i18n::init(Some("en"));
+		let state = synthetic_state(ViewMode::All);
+		let (width, height) = PopupSize::default().window(ViewMode::All);
+		let height = height + details_height(&state);
+		// Over the third download burst, and near the right edge of upload.
+		let cases = [("burst", (172.0, 90.0)), ("edge", (236.0, 200.0))].map(|(name, point)| {
+			let mut raster = Raster::new(width, height).unwrap();
+			draw(&state, Some(point), &mut raster);
+			(format!("popup-hover-{}", name), raster)
+		});
+		golden::assert_all(cases);
+	}
+
+	#[test]
+	fn hover_finds_the_sample_under_the_cursor() {
+		let graph = Rect::new(0.0, 50.0, 480.0, 50.0);
+		assert_eq!(sample_at(graph, 0.0, 240), Some(0));
+		assert_eq!(sample_at(graph, 201.0, 240), Some(101));
+		assert_eq!(sample_at(graph, 479.0, 240), Some(239));
+		assert_eq!(sample_at(graph, 100.0, 30), None);
+		assert_eq!(sample_at(graph, -1.0, 240), None);
+	}
+}
@@ -0,0 +1,197 @@
+/// A rectangle on the desktop, in physical pixels.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ScreenRect {
+	pub x: i32,
+	pub y: i32,
+	pub width: i32,
+	pub height: i32,
+}
+
+impl ScreenRect {
+	pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+		Self { x, y, width, height }
+	}
+
+	pub fn right(&self) -> i32 {
+		self.x + self.width
+	}
+
+	pub fn bottom(&self) -> i32 {
+		self.y + self.height
+	}
+
+	pub fn center(&self) -> (i32, i32) {
+		(self.x + self.width / 2, self.y + self.height / 2)
+	}
+}
+
+/// A monitor's full bounds and the part of it not covered by taskbars.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Screen {
+	pub bounds: ScreenRect,
+	pub work_area: ScreenRect,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Edge {
+	Top,
+	Bottom,
+	Left,
+	Right,
+}
+
+/// The edge the taskbar is on: the side the work area gives up or, for a
+/// taskbar that auto-hides, the side nearest the tray icon. Bottom if
+/// neither tells.
+pub fn taskbar_edge(screen: Screen, anchor: Option<ScreenRect>) -> Edge {
+	let (bounds, work) = (screen.bounds, screen.work_area);
+	if work.y > bounds.y {
+		return Edge::Top;
+	}
+	if work.x > bounds.x {
+		return Edge::Left;
+	}
+	if work.right() < bounds.right() {
+		return Edge::Right;
+	}
+	if work.bottom() < bounds.bottom() {
+		return Edge::Bottom;
+	}
+	let Some(anchor) = anchor else {
+		return Edge::Bottom;
+	};
+	let (x, y) = anchor.center();
+	[
+		(Edge::Bottom, bounds.bottom() - y),
+		(Edge::Top, y - bounds.y),
+		(Edge::Left, x - bounds.x),
+		(Edge::Right, bounds.right() - x),
+	]
+		.into_iter()
+		.min_by_key(|&(_, distance)| distance)
+		.map_or(Edge::Bottom, |(edge, _)| edge)
+}
+
+/// Top-left corner for a window of `size` beside `anchor`, the tray icon,
+/// on the taskbar side of `screen`, `gap` pixels in from the work area and
+/// kept inside it. Without an anchor the window goes to the corner where
+/// the notification area usually sits.
+pub fn place(size: (i32, i32), anchor: Option<ScreenRect>, screen: Screen, gap: i32) -> (i32, i32) {
+	let (width, height) = size;
+	let work = screen.work_area;
+	let edge = taskbar_edge(screen, anchor);
+
+	let left = work.x + gap;
+	let right = work.right() - width - gap;
+	let top = work.y + gap;
+	let bottom = work.bottom() - height - gap;
+	let (x, y) = match (edge, anchor.map(|anchor| anchor.center())) {
+		(Edge::Bottom, Some((x, _))) => (x - width / 2, bottom),
+		(Edge::Top, Some((x, _))) => (x - width / 2, top),
+		(Edge::Left, Some((_, y))) => (left, y - height / 2),
+		(Edge::Right, Some((_, y))) => (right, y - height / 2),
+		(Edge::Top, None) => (right, top),
+		(Edge::Left, None) => (left, bottom),
+		(Edge::Bottom | Edge::Right, None) => (right, bottom),
+	};
+	(clamp(x, left, right), clamp(y, top, bottom))
+}
+
+/// Moves a window of `size` at `position` as little as possible to bring
+/// it inside `work_area`, e.g. after the monitor it was on went away.
+pub fn keep_inside(position: (i32, i32), size: (i32, i32), work_area: ScreenRect) -> (i32, i32) {
+	let (x, y) = position;
+	let (width, height) = size;
+	(
+		clamp(x, work_area.x, work_area.right() - width),
+		clamp(y, work_area.y, work_area.bottom() - height),
+	)
+}
+
+/// Like `i32::clamp`, but a window too big to fit starts at `min`.
+fn clamp(value: i32, min: i32, max: i32) -> i32 {
+	value.min(max).max(min)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SIZE: (i32, i32) = (240, 220);
+	const GAP: i32 = 12;
+	const MONITOR: ScreenRect = ScreenRect::new(0, 0, 1920, 1080);
+
+	fn screen(work_area: ScreenRect) -> Screen {
+		Screen { bounds: MONITOR, work_area }
+	}
+
+	#[test]
+	fn bottom_taskbar_centres_over_the_icon() {
+		let screen = screen(ScreenRect::new(0, 0, 1920, 1040));
+		let icon = ScreenRect::new(1700, 1044, 32, 32);
+		assert_eq!(taskbar_edge(screen, Some(icon)), Edge::Bottom);
+		assert_eq!(place(SIZE, Some(icon), screen, GAP), (1596, 808));
+		// Near the corner the popup stays inside the work area.
+		let icon = ScreenRect::new(1880, 1044, 32, 32);
+		assert_eq!(place(SIZE, Some(icon), screen, GAP), (1668, 808));
+	}
+
+	#[test]
+	fn top_and_side_taskbars_open_inward() {
+		let top = screen(ScreenRect::new(0, 40, 1920, 1040));
+		let icon = ScreenRect::new(1700, 4, 32, 32);
+		assert_eq!(taskbar_edge(top, Some(icon)), Edge::Top);
+		assert_eq!(place(SIZE, Some(icon), top, GAP), (1596, 52));
+
+		let left = screen(ScreenRect::new(62, 0, 1858, 1080));
+		let icon = ScreenRect::new(15, 1000, 32, 32);
+		assert_eq!(taskbar_edge(left, Some(icon)), Edge::Left);
+		assert_eq!(place(SIZE, Some(icon), left, GAP), (74, 848));
+
+		let right = screen(ScreenRect::new(0, 0, 1858, 1080));
+		let icon = ScreenRect::new(1873, 500, 32, 32);
+		assert_eq!(taskbar_edge(right, Some(icon)), Edge::Right);
+		assert_eq!(place(SIZE, Some(icon), right, GAP), (1606, 406));
+	}
+
+	#[test]
+	fn secondary_monitors_use_their_own_coordinates() {
+		// A monitor left of and above the primary one.
+		let screen = Screen {
+			bounds: ScreenRect::new(-2560, -360, 2560, 1440),
+			work_area: ScreenRect::new(-2560, -360, 2560, 1392),
+		};
+		let icon = ScreenRect::new(-200, 1036, 48, 48);
+		assert_eq!(place(SIZE, Some(icon), screen, GAP), (-296, 800));
+	}
+
+	#[test]
+	fn auto_hide_taskbar_is_found_from_the_icon() {
+		let screen = screen(MONITOR);
+		let icon = ScreenRect::new(1700, 2, 32, 32);
+		assert_eq!(taskbar_edge(screen, Some(icon)), Edge::Top);
+		assert_eq!(taskbar_edge(screen, None), Edge::Bottom);
+	}
+
+	#[test]
+	fn without_an_icon_the_notification_corner_is_used() {
+		assert_eq!(place(SIZE, None, screen(ScreenRect::new(0, 0, 1920, 1040)), GAP), (1668, 808));
+		assert_eq!(place(SIZE, None, screen(ScreenRect::new(0, 40, 1920, 1040)), GAP), (1668, 52));
+		assert_eq!(place(SIZE, None, screen(ScreenRect::new(62, 0, 1858, 1080)), GAP), (74, 848));
+	}
+
+	#[test]
+	fn dragged_windows_are_kept_on_screen() {
+		let work = ScreenRect::new(0, 0, 1920, 1040);
+		assert_eq!(keep_inside((300, 200), SIZE, work), (300, 200));
+		assert_eq!(keep_inside((1800, 1000), SIZE, work), (1680, 820));
+		assert_eq!(keep_inside((-2000, -50), SIZE, work), (0, 0));
+	}
+
+	#[test]
+	fn oversized_windows_start_at_the_work_area_corner() {
+		let screen = screen(ScreenRect::new(0, 0, 1920, 1040));
+		let icon = ScreenRect::new(1700, 1044, 32, 32);
+		assert_eq!(place((2000, 1200), Some(icon), screen, GAP), (12, 12));
+	}
+}
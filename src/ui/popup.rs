@@ -1,210 +1,242 @@
+use std::time::{ Duration, Instant };
+use winit::dpi::{ LogicalSize, PhysicalPosition, PhysicalSize };
 use winit::window::{ Window, WindowBuilder, WindowLevel };
 use winit::event_loop::EventLoopWindowTarget;
 use winit::platform::windows::WindowBuilderExtWindows;
-use windows::Win32::Foundation::{ HWND, RECT, POINT };
-use windows::Win32::Graphics::Gdi::{
-	GetDC,
-	ReleaseDC,
-	FillRect,
-	Polygon,
-	Polyline,
-	TextOutW,
-	SetBkMode,
-	SetTextColor,
-	TRANSPARENT,
-	PS_SOLID,
-	PS_NULL,
-	FW_SEMIBOLD,
-	FW_BOLD,
-};
-use windows::Win32::UI::WindowsAndMessaging::{
-	SystemParametersInfoW,
-	SPI_GETWORKAREA,
-	SPIF_SENDCHANGE,
-};
+use windows::Win32::Foundation::HWND;
 use raw_window_handle::{ HasWindowHandle, RawWindowHandle };
 
+use crate::core::settings::PopupSize;
 use crate::core::state::{ AppState, ViewMode };
-use crate::sys::gdi::{ create_solid_brush, create_pen, create_font, DcScope };
-use crate::ui::theme::*;
-use crate::utils::format::format_speed_full;
+use crate::ui::canvas::{ Canvas, Point };
+use crate::ui::gdi_canvas::GdiCanvas;
+use crate::sys::display;
+use crate::ui::panel::{ self, Hit };
+use crate::ui::placement::{ self, ScreenRect };
+
+/// Logical pixels between the popup and the taskbar or screen edge.
+pub const SCREEN_GAP: f64 = 12.0;
+/// Clicking the tray icon takes focus from the popup before the click
+/// arrives; a click this soon after hiding it is taken as the one that
+/// closed it.
+const REOPEN_GUARD: Duration = Duration::from_millis(300);
 
 pub struct Popup {
 	window: Window,
+	/// The size the user last resized to.
+	size: PopupSize,
+	/// Cursor position over the popup, for the graph crosshair.
+	hover: Option<Point>,
+	/// Where the tray icon is, to open next to it.
+	anchor: Option<ScreenRect>,
+	/// Where the pinned popup was dragged to.
+	pinned_position: Option<(i32, i32)>,
+	/// When losing focus last hid the popup.
+	auto_hidden: Option<Instant>,
 }
 
 impl Popup {
-	pub fn new<T>(event_loop: &EventLoopWindowTarget<T>) -> Self {
+	pub fn new<T>(event_loop: &EventLoopWindowTarget<T>, size: PopupSize) -> Self {
+		let (width, height) = size.window(ViewMode::All);
 		let window = WindowBuilder::new()
 			.with_title("NetFlux Popup")
 			.with_decorations(false)
 			.with_window_level(WindowLevel::AlwaysOnTop)
 			.with_skip_taskbar(true)
-			.with_resizable(false)
-			.with_inner_size(winit::dpi::LogicalSize::new(240.0, 220.0))
+			.with_resizable(true)
+			.with_inner_size(LogicalSize::new(width as f64, height as f64))
 			.with_visible(false)
 			.build(event_loop)
 			.expect("Failed to create popup window");
 
-		Self { window }
+		Self {
+			window,
+			size,
+			hover: None,
+			anchor: None,
+			pinned_position: None,
+			auto_hidden: None,
+		}
+	}
+
+	/// Whether the OS asks apps for dark colours, if it says.
+	pub fn system_dark(&self) -> Option<bool> {
+		self.window.theme().map(|theme| theme == winit::window::Theme::Dark)
 	}
 
-	pub fn toggle(&self, state: &AppState) {
-		if self.window.is_visible().unwrap_or(false) {
+	pub fn is_visible(&self) -> bool {
+		self.window.is_visible().unwrap_or(false)
+	}
+
+	pub fn toggle(&mut self, state: &AppState) {
+		let just_hidden = self.auto_hidden.take().is_some_and(|at| at.elapsed() < REOPEN_GUARD);
+		if self.is_visible() {
 			self.window.set_visible(false);
-		} else {
-			self.reposition(state);
-			self.window.set_visible(true);
-			self.window.request_redraw();
+		} else if !just_hidden {
+			self.show(state);
 		}
 	}
 
+	pub fn show(&self, state: &AppState) {
+		self.reposition(state, true);
+		self.window.set_visible(true);
+		self.window.focus_window();
+		self.window.request_redraw();
+	}
+
 	pub fn update(&self, state: &AppState) {
-		if self.window.is_visible().unwrap_or(false) {
-			self.reposition(state);
+		if self.is_visible() {
+			self.reposition(state, false);
 			self.window.request_redraw();
 		}
 	}
 
-	pub fn draw(&self, state: &AppState) {
-		let handle = self.window.window_handle().unwrap().as_raw();
-		if let RawWindowHandle::Win32(handle) = handle {
-			let hwnd = HWND(handle.hwnd.get() as _);
-			unsafe {
-				let hdc = GetDC(hwnd);
-				let mut scope = DcScope::new(hdc);
-
-				let width = 240;
-				let height = match state.view_mode {
-					ViewMode::All => 220,
-					_ => 110,
-				};
-
-				let rect = RECT { left: 0, top: 0, right: width, bottom: height };
-				let bg_brush = create_solid_brush(COLOR_BG);
-				let _ = FillRect(hdc, &rect, bg_brush.as_brush());
-
-				SetBkMode(hdc, TRANSPARENT);
-
-				let font_label = create_font(-12, FW_SEMIBOLD.0 as i32, FONT_FACE);
-				let font_val = create_font(-24, FW_BOLD.0 as i32, FONT_FACE);
-
-				if state.view_mode == ViewMode::All || state.view_mode == ViewMode::DownloadOnly {
-					let max_down = std::cmp::max(*state.down_history.iter().max().unwrap_or(&1), 1024 * 1024);
-					let baseline = 100;
-					let graph_h = 50.0;
-
-					let fill_brush = create_solid_brush(COLOR_DOWN_FILL);
-					let null_pen = create_pen(PS_NULL, 0, 0);
-					scope.select(&fill_brush);
-					scope.select(&null_pen);
-
-					let mut points = Vec::with_capacity(state.down_history.len() + 2);
-					points.push(POINT { x: 0, y: baseline });
-					for (i, &val) in state.down_history.iter().enumerate() {
-						let h = (((val as f64) / (max_down as f64)) * graph_h) as i32;
-						points.push(POINT { x: i as i32, y: baseline - h });
-					}
-					points.push(POINT { x: state.down_history.len() as i32, y: baseline });
-					let _ = Polygon(hdc, &points);
-
-					let line_pen = create_pen(PS_SOLID, 2, COLOR_DOWN_LINE);
-					scope.select(&line_pen);
-					let mut line_points = Vec::with_capacity(state.down_history.len());
-					for (i, &val) in state.down_history.iter().enumerate() {
-						let h = (((val as f64) / (max_down as f64)) * graph_h) as i32;
-						line_points.push(POINT { x: i as i32, y: baseline - h });
-					}
-					if !line_points.is_empty() {
-						let _ = Polyline(hdc, &line_points);
-					}
-
-					SetTextColor(hdc, windows::Win32::Foundation::COLORREF(COLOR_TEXT_GRAY));
-					scope.select(&font_label);
-					let label = wide_string("↓ DOWNLOAD");
-					let _ = TextOutW(hdc, 16, 12, &label);
-
-					SetTextColor(hdc, windows::Win32::Foundation::COLORREF(COLOR_TEXT_WHITE));
-					scope.select(&font_val);
-					let val = wide_string(&format_speed_full(state.down_bps));
-					let _ = TextOutW(hdc, 16, 30, &val);
-				}
-
-				if state.view_mode == ViewMode::All || state.view_mode == ViewMode::UploadOnly {
-					let max_up = std::cmp::max(*state.up_history.iter().max().unwrap_or(&1), 1024 * 1024);
-					let baseline = if state.view_mode == ViewMode::All { 210 } else { 100 };
-					let graph_h = 50.0;
-					let y_label = if state.view_mode == ViewMode::All { 120 } else { 12 };
-					let y_val = if state.view_mode == ViewMode::All { 138 } else { 30 };
-
-					let fill_brush = create_solid_brush(COLOR_UP_FILL);
-					let null_pen = create_pen(PS_NULL, 0, 0);
-					scope.select(&fill_brush);
-					scope.select(&null_pen);
-
-					let mut points = Vec::with_capacity(state.up_history.len() + 2);
-					points.push(POINT { x: 0, y: baseline });
-					for (i, &val) in state.up_history.iter().enumerate() {
-						let h = (((val as f64) / (max_up as f64)) * graph_h) as i32;
-						points.push(POINT { x: i as i32, y: baseline - h });
-					}
-					points.push(POINT { x: state.up_history.len() as i32, y: baseline });
-					let _ = Polygon(hdc, &points);
-
-					let line_pen = create_pen(PS_SOLID, 2, COLOR_UP_LINE);
-					scope.select(&line_pen);
-					let mut line_points = Vec::with_capacity(state.up_history.len());
-					for (i, &val) in state.up_history.iter().enumerate() {
-						let h = (((val as f64) / (max_up as f64)) * graph_h) as i32;
-						line_points.push(POINT { x: i as i32, y: baseline - h });
-					}
-					if !line_points.is_empty() {
-						let _ = Polyline(hdc, &line_points);
-					}
-
-					SetTextColor(hdc, windows::Win32::Foundation::COLORREF(COLOR_TEXT_GRAY));
-					scope.select(&font_label);
-					let label = wide_string("↑ UPLOAD");
-					let _ = TextOutW(hdc, 16, y_label, &label);
-
-					SetTextColor(hdc, windows::Win32::Foundation::COLORREF(COLOR_TEXT_WHITE));
-					scope.select(&font_val);
-					let val = wide_string(&format_speed_full(state.up_bps));
-					let _ = TextOutW(hdc, 16, y_val, &val);
-				}
-
-				ReleaseDC(hwnd, hdc);
-			}
+	/// Hides the popup when another window takes focus, unless it is pinned.
+	pub fn focus_lost(&mut self, state: &AppState) {
+		if self.is_visible() && !state.pinned {
+			self.window.set_visible(false);
+			self.auto_hidden = Some(Instant::now());
 		}
 	}
 
-	fn reposition(&self, state: &AppState) {
-		unsafe {
-			let mut rect = RECT::default();
-			if
-				SystemParametersInfoW(
-					SPI_GETWORKAREA,
-					0,
-					Some(&mut rect as *mut _ as *mut _),
-					SPIF_SENDCHANGE
-				).is_ok()
-			{
-				let width = 240;
-				let height = match state.view_mode {
-					ViewMode::All => 220,
-					_ => 110,
-				};
-				let x = rect.right - width - 12;
-				let y = rect.bottom - height - 12;
-				self.window.set_outer_position(winit::dpi::PhysicalPosition::new(x, y));
-				let _ = self.window.request_inner_size(
-					winit::dpi::LogicalSize::new(width as f64, height as f64)
-				);
-			}
+	/// Records a size the user dragged the popup to, returning it if it
+	/// differs from the one remembered.
+	pub fn resized(&mut self, state: &AppState, size: PhysicalSize<u32>) -> Option<PopupSize> {
+		if !self.is_visible() {
+			return None;
+		}
+		self.window.request_redraw();
+		let size = size.to_logical::<f64>(self.window.scale_factor());
+		let details = panel::details_height(state);
+		let size = PopupSize::from_window(
+			state.view_mode,
+			size.width.round() as u32,
+			(size.height.round() as u32).saturating_sub(details)
+		);
+		if size == self.size {
+			return None;
+		}
+		self.size = size;
+		Some(size)
+	}
+
+	/// Records where the user dragged the pinned popup, returning the
+	/// position if it is one to remember.
+	pub fn moved(&mut self, state: &AppState, position: PhysicalPosition<i32>) -> Option<(i32, i32)> {
+		let position = Some((position.x, position.y));
+		if !self.is_visible() || !state.pinned || self.pinned_position == position {
+			return None;
+		}
+		self.pinned_position = position;
+		position
+	}
+
+	/// Sets where the pinned popup opens, e.g. from settings.
+	pub fn set_pinned_position(&mut self, position: Option<(i32, i32)>) {
+		self.pinned_position = position;
+	}
+
+	/// Pins the popup where it is now, returning that position.
+	pub fn pin_here(&mut self) -> Option<(i32, i32)> {
+		let position = self.window.outer_position().ok()?;
+		self.pinned_position = Some((position.x, position.y));
+		self.pinned_position
+	}
+
+	/// Sets the tray icon rectangle the popup opens beside.
+	pub fn set_anchor(&mut self, anchor: Option<ScreenRect>) {
+		self.anchor = anchor;
+	}
+
+	pub fn anchor(&self) -> Option<ScreenRect> {
+		self.anchor
+	}
+
+	pub fn set_hover(&mut self, hover: Option<Point>) {
+		if hover != self.hover {
+			self.hover = hover;
+			self.window.request_redraw();
+		}
+	}
+
+	/// The control under the cursor, if any.
+	pub fn hit(&self, state: &AppState) -> Option<Hit> {
+		let PhysicalSize { width, height } = self.window.inner_size();
+		self.hover.and_then(|point| panel::hit_test(state, (width, height), point))
+	}
+
+	/// Lets the user move the pinned popup by dragging it.
+	pub fn drag(&self, state: &AppState) {
+		if state.pinned {
+			let _ = self.window.drag_window();
 		}
 	}
+
+	/// Has the popup's window, hidden or not, call `on_change` when the
+	/// display layout or DPI changes.
+	pub fn watch_displays(&self, on_change: impl Fn() + 'static) {
+		if let RawWindowHandle::Win32(handle) = self.window.window_handle().unwrap().as_raw() {
+			display::watch_displays(HWND(handle.hwnd.get() as _), on_change);
+		}
+	}
+
+	pub fn draw(&self, state: &AppState) {
+		paint(&self.window, &state.theme.font, |canvas| panel::draw(state, self.hover, canvas));
+	}
+
+	/// Sizes the window for the monitor it opens on. The popup goes beside
+	/// the tray icon, inside that monitor's work area; the pinned popup
+	/// returns to where it was dragged when `showing`, and otherwise stays
+	/// put.
+	fn reposition(&self, state: &AppState, showing: bool) {
+		let saved = if state.pinned { self.pinned_position } else { None };
+		let center = self.anchor.map(|anchor| anchor.center());
+		let Some((screen, scale)) = display::screen_at(saved.or(center)) else {
+			return;
+		};
+
+		let details = panel::details_height(state);
+		let (width, height) = self.size.window(state.view_mode);
+		let height = height + details;
+		let logical = LogicalSize::new(width as f64, height as f64);
+		let size: PhysicalSize<i32> = logical.to_physical(scale);
+		let size = (size.width, size.height);
+		let gap = (SCREEN_GAP * scale).round() as i32;
+		let position = match saved {
+			Some(position) if showing => Some(placement::keep_inside(position, size, screen.work_area)),
+			Some(_) => None,
+			None => Some(placement::place(size, self.anchor, screen, gap)),
+		};
+
+		// Moving first lets a DPI change settle before the size is set in
+		// the new monitor's pixels.
+		if let Some((x, y)) = position {
+			self.window.set_outer_position(PhysicalPosition::new(x, y));
+		}
+		let (min_width, min_height) = PopupSize::MIN.window(state.view_mode);
+		self.window.set_min_inner_size(
+			Some(LogicalSize::new(min_width as f64, (min_height + details) as f64))
+		);
+		let _ = self.window.request_inner_size(PhysicalSize::new(size.0, size.1));
+	}
 }
 
-fn wide_string(s: &str) -> Vec<u16> {
-	s.encode_utf16().chain(std::iter::once(0)).collect()
+/// Renders a frame of `window`'s size with `draw` off screen and copies it
+/// to the window in one go.
+pub fn paint(window: &Window, font_face: &str, draw: impl FnOnce(&mut dyn Canvas)) {
+	let handle = window.window_handle().unwrap().as_raw();
+	if let RawWindowHandle::Win32(handle) = handle {
+		let hwnd = HWND(handle.hwnd.get() as _);
+		let PhysicalSize { width, height } = window.inner_size();
+		if width == 0 || height == 0 {
+			return;
+		}
+		match GdiCanvas::new(width, height, font_face) {
+			Ok(mut canvas) => {
+				draw(&mut canvas);
+				let _ = canvas.present(hwnd);
+			}
+			Err(e) => eprintln!("Failed to render {}: {}", window.title(), e),
+		}
+	}
 }
@@ -0,0 +1,177 @@
+use tiny_skia::{
+	Color,
+	FillRule,
+	LineJoin,
+	Paint,
+	PathBuilder,
+	Pixmap,
+	Rect,
+	Stroke,
+	Transform,
+};
+
+use crate::ui::canvas::{ self, Canvas, Point, TextStyle };
+use crate::ui::font;
+
+/// Converts a GDI `COLORREF` (0x00BBGGRR) from `ui::theme` into an opaque colour.
+pub fn colorref(color: u32) -> Color {
+	Color::from_rgba8(color as u8, (color >> 8) as u8, (color >> 16) as u8, 0xff)
+}
+
+/// An antialiased RGBA drawing surface backed by tiny-skia.
+pub struct Raster {
+	pixmap: Pixmap,
+}
+
+impl Raster {
+	pub fn new(width: u32, height: u32) -> Result<Self, String> {
+		let pixmap = Pixmap::new(width, height).ok_or("Invalid raster size")?;
+		Ok(Self { pixmap })
+	}
+
+	pub fn width(&self) -> u32 {
+		self.pixmap.width()
+	}
+
+	pub fn height(&self) -> u32 {
+		self.pixmap.height()
+	}
+
+	pub fn fill_round_rect(&mut self, x: f32, y: f32, w: f32, h: f32, radius: f32, color: Color) {
+		let Some(rect) = Rect::from_xywh(x, y, w, h) else {
+			return;
+		};
+		let path = if radius > 0.0 {
+			round_rect(rect, radius)
+		} else {
+			Some(PathBuilder::from_rect(rect))
+		};
+		if let Some(path) = path {
+			self.pixmap.fill_path(
+				&path,
+				&paint(color),
+				FillRule::Winding,
+				Transform::identity(),
+				None
+			);
+		}
+	}
+
+	/// Fills the closed polygon through `points`.
+	pub fn fill_polygon(&mut self, points: &[(f32, f32)], color: Color) {
+		if let Some(path) = polyline_path(points, true) {
+			self.pixmap.fill_path(
+				&path,
+				&paint(color),
+				FillRule::Winding,
+				Transform::identity(),
+				None
+			);
+		}
+	}
+
+	pub fn stroke_polyline(&mut self, points: &[(f32, f32)], width: f32, color: Color) {
+		if let Some(path) = polyline_path(points, false) {
+			let stroke = Stroke {
+				width,
+				line_join: LineJoin::Round,
+				..Stroke::default()
+			};
+			self.pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+		}
+	}
+
+	pub fn draw_text(&mut self, text: &str, x: f32, baseline: f32, em: f32, color: Color) {
+		if let Some(path) = font::text_path(text, x, baseline, em) {
+			self.pixmap.fill_path(
+				&path,
+				&paint(color),
+				FillRule::Winding,
+				Transform::identity(),
+				None
+			);
+		}
+	}
+
+	pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+		self.pixmap.encode_png().map_err(|e| e.to_string())
+	}
+
+	/// Straight (non-premultiplied) RGBA bytes, as tray and window APIs expect.
+	pub fn to_rgba(&self) -> Vec<u8> {
+		let mut rgba = Vec::with_capacity(self.pixmap.data().len());
+		for pixel in self.pixmap.pixels() {
+			let c = pixel.demultiply();
+			rgba.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+		}
+		rgba
+	}
+}
+
+fn paint(color: Color) -> Paint<'static> {
+	let mut paint = Paint::default();
+	paint.set_color(color);
+	paint.anti_alias = true;
+	paint
+}
+
+fn polyline_path(points: &[(f32, f32)], close: bool) -> Option<tiny_skia::Path> {
+	let (&(x, y), rest) = points.split_first()?;
+	let mut pb = PathBuilder::new();
+	pb.move_to(x, y);
+	for &(x, y) in rest {
+		pb.line_to(x, y);
+	}
+	if close {
+		pb.close();
+	}
+	pb.finish()
+}
+
+fn round_rect(rect: Rect, radius: f32) -> Option<tiny_skia::Path> {
+	let r = radius.min(rect.width() / 2.0).min(rect.height() / 2.0);
+	let (l, t, rt, b) = (rect.left(), rect.top(), rect.right(), rect.bottom());
+	// Cubic approximation of a quarter circle.
+	let k = r * 0.552_284_8;
+
+	let mut pb = PathBuilder::new();
+	pb.move_to(l + r, t);
+	pb.line_to(rt - r, t);
+	pb.cubic_to(rt - r + k, t, rt, t + r - k, rt, t + r);
+	pb.line_to(rt, b - r);
+	pb.cubic_to(rt, b - r + k, rt - r + k, b, rt - r, b);
+	pb.line_to(l + r, b);
+	pb.cubic_to(l + r - k, b, l, b - r + k, l, b - r);
+	pb.line_to(l, t + r);
+	pb.cubic_to(l, t + r - k, l + r - k, t, l + r, t);
+	pb.close();
+	pb.finish()
+}
+
+/// The software backend. There is a single embedded face, so every weight
+/// draws bold.
+impl Canvas for Raster {
+	fn size(&self) -> (u32, u32) {
+		(self.width(), self.height())
+	}
+
+	fn fill_rect(&mut self, rect: canvas::Rect, color: u32) {
+		self.fill_round_rect(rect.x, rect.y, rect.w, rect.h, 0.0, colorref(color));
+	}
+
+	fn fill_polygon(&mut self, points: &[Point], color: u32) {
+		Raster::fill_polygon(self, points, colorref(color));
+	}
+
+	fn polyline(&mut self, points: &[Point], width: f32, color: u32) {
+		self.stroke_polyline(points, width, colorref(color));
+	}
+
+	fn text(&mut self, text: &str, x: f32, y: f32, style: TextStyle, color: u32) {
+		self.draw_text(text, x, y + font::ascent(style.size), style.size, colorref(color));
+	}
+
+	fn measure_text(&mut self, text: &str, style: TextStyle) -> (f32, f32) {
+		(font::text_width(text, style.size), font::line_height(style.size))
+	}
+}
@@ -0,0 +1,100 @@
+/// Range of the samples drawn in one pixel column.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Column {
+	pub min: f64,
+	pub max: f64,
+	/// Whether the last sample in the column is at least the first, so a
+	/// line can pass through min and max in order.
+	pub rising: bool,
+}
+
+impl Column {
+	fn flat(value: f64) -> Self {
+		Self { min: value, max: value, rising: true }
+	}
+}
+
+/// Maps `samples`, spaced so that `capacity` of them span `columns` pixels,
+/// onto pixel columns starting at the left. Columns holding several samples
+/// keep their min and max, so a one-second burst survives shrinking; columns
+/// between samples are linearly interpolated.
+pub fn resample(samples: &[u64], capacity: usize, columns: usize) -> Vec<Column> {
+	if samples.is_empty() || columns == 0 {
+		return Vec::new();
+	}
+	let per_column = (capacity.max(1) as f64) / (columns as f64);
+	let last = (samples.len() - 1) as f64;
+
+	(0..columns)
+		.map_while(|column| {
+			let start = (column as f64) * per_column;
+			if start > last {
+				return None;
+			}
+			let first = start.ceil() as usize;
+			let end = (((column + 1) as f64) * per_column).ceil().min(last + 1.0) as usize;
+			if first >= end {
+				return Some(Column::flat(interpolate(samples, start)));
+			}
+			let bucket = &samples[first..end];
+			let (min, max) = bucket
+				.iter()
+				.fold((u64::MAX, 0), |(min, max), &value| (min.min(value), max.max(value)));
+			Some(Column {
+				min: min as f64,
+				max: max as f64,
+				rising: bucket[bucket.len() - 1] >= bucket[0],
+			})
+		})
+		.collect()
+}
+
+fn interpolate(samples: &[u64], position: f64) -> f64 {
+	let index = position.floor() as usize;
+	let (a, b) = (samples[index] as f64, samples[(index + 1).min(samples.len() - 1)] as f64);
+	a + (b - a) * position.fract()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn maxima(columns: &[Column]) -> Vec<f64> {
+		columns.iter().map(|column| column.max).collect()
+	}
+
+	#[test]
+	fn one_sample_per_column_is_unchanged() {
+		let samples = [3, 1, 4, 1, 5];
+		let columns = resample(&samples, 5, 5);
+		assert_eq!(maxima(&columns), vec![3.0, 1.0, 4.0, 1.0, 5.0]);
+		assert!(columns.iter().all(|column| column.min == column.max));
+	}
+
+	#[test]
+	fn shrinking_keeps_bursts() {
+		let mut samples = vec![100; 240];
+		samples[77] = 9_000;
+		let columns = resample(&samples, 240, 60);
+		assert_eq!(columns.len(), 60);
+		assert_eq!(columns[19], Column { min: 100.0, max: 9_000.0, rising: true });
+		assert_eq!(columns.iter().filter(|column| column.max > 100.0).count(), 1);
+
+		let columns = resample(&[1, 8, 2, 0], 4, 2);
+		assert_eq!(columns[0], Column { min: 1.0, max: 8.0, rising: true });
+		assert_eq!(columns[1], Column { min: 0.0, max: 2.0, rising: false });
+	}
+
+	#[test]
+	fn stretching_interpolates() {
+		let columns = resample(&[0, 100, 50], 3, 6);
+		assert_eq!(maxima(&columns), vec![0.0, 50.0, 100.0, 75.0, 50.0]);
+	}
+
+	#[test]
+	fn partial_history_fills_from_the_left() {
+		let columns = resample(&[7; 30], 240, 480);
+		assert_eq!(columns.len(), 59);
+		assert!(resample(&[], 240, 480).is_empty());
+	}
+}
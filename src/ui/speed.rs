@@ -0,0 +1,51 @@
+use crate::ui::theme::Theme;
+
+/// Bars use a log scale from 1 KiB/s to 1 GiB/s.
+const BAR_MIN: f64 = 1024.0;
+const BAR_MAX: f64 = 1024.0 * 1024.0 * 1024.0;
+
+const BAND_ACTIVE: u64 = 100 * 1024;
+const BAND_BUSY: u64 = 5 * 1024 * 1024;
+
+/// How busy a link is, which picks the colour speeds are shown in.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SpeedBand {
+	Idle,
+	Active,
+	Busy,
+}
+
+impl SpeedBand {
+	pub fn of(speed: u64) -> Self {
+		if speed < BAND_ACTIVE {
+			Self::Idle
+		} else if speed < BAND_BUSY {
+			Self::Active
+		} else {
+			Self::Busy
+		}
+	}
+
+	/// Dim while idle, the download colour while active, `busy` beyond that.
+	pub fn color(self, theme: &Theme) -> u32 {
+		match self {
+			Self::Idle => theme.text_dim,
+			Self::Active => theme.download_line,
+			Self::Busy => theme.busy,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Idle => "idle",
+			Self::Active => "active",
+			Self::Busy => "busy",
+		}
+	}
+}
+
+/// Where `speed` sits on the bars' log scale, from 0 at 1 KiB/s to 1 at
+/// 1 GiB/s.
+pub fn speed_level(speed: u64) -> f64 {
+	(((speed as f64).max(BAR_MIN) / BAR_MIN).ln() / (BAR_MAX / BAR_MIN).ln()).min(1.0)
+}
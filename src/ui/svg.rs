@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use crate::ui::canvas::{ Canvas, FontWeight, Point, Rect, TextStyle };
+use crate::ui::font;
+use crate::ui::theme::to_hex;
+
+/// Fallbacks after the theme font, for viewers that lack it. Text is laid
+/// out with the embedded font's metrics, which these roughly match.
+const FALLBACK_FONTS: &str = "'DejaVu Sans Condensed', 'Segoe UI', sans-serif";
+
+/// A vector backend that records drawing calls as SVG elements.
+pub struct Svg {
+	width: u32,
+	height: u32,
+	font_face: String,
+	body: String,
+}
+
+impl Svg {
+	pub fn new(width: u32, height: u32, font_face: &str) -> Self {
+		Self { width, height, font_face: font_face.to_string(), body: String::new() }
+	}
+
+	/// The finished document.
+	pub fn finish(self) -> String {
+		let font = format!("{}, {}", escape(&quote(&self.font_face)), FALLBACK_FONTS);
+		format!(
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+			 viewBox=\"0 0 {w} {h}\" font-family=\"{font}\">\n{body}</svg>\n",
+			w = self.width,
+			h = self.height,
+			font = font,
+			body = self.body
+		)
+	}
+}
+
+impl Canvas for Svg {
+	fn size(&self) -> (u32, u32) {
+		(self.width, self.height)
+	}
+
+	fn fill_rect(&mut self, rect: Rect, color: u32) {
+		let _ = writeln!(
+			self.body,
+			"<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+			rect.x,
+			rect.y,
+			rect.w,
+			rect.h,
+			to_hex(color)
+		);
+	}
+
+	fn fill_polygon(&mut self, points: &[Point], color: u32) {
+		let _ = writeln!(self.body, "<polygon points=\"{}\" fill=\"{}\"/>", points_attr(points), to_hex(color));
+	}
+
+	fn polyline(&mut self, points: &[Point], width: f32, color: u32) {
+		let _ = writeln!(
+			self.body,
+			"<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+			 stroke-linejoin=\"round\"/>",
+			points_attr(points),
+			to_hex(color),
+			width
+		);
+	}
+
+	fn text(&mut self, text: &str, x: f32, y: f32, style: TextStyle, color: u32) {
+		let weight = match style.weight {
+			FontWeight::SemiBold => 600,
+			FontWeight::Bold => 700,
+		};
+		let _ = writeln!(
+			self.body,
+			"<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-weight=\"{}\" fill=\"{}\">{}</text>",
+			x,
+			y + font::ascent(style.size),
+			style.size,
+			weight,
+			to_hex(color),
+			escape(text)
+		);
+	}
+
+	fn measure_text(&mut self, text: &str, style: TextStyle) -> (f32, f32) {
+		(font::text_width(text, style.size), font::line_height(style.size))
+	}
+}
+
+fn points_attr(points: &[Point]) -> String {
+	points
+		.iter()
+		.map(|(x, y)| format!("{},{}", x, y))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+fn quote(family: &str) -> String {
+	format!("'{}'", family.replace('\'', ""))
+}
+
+fn escape(text: &str) -> String {
+	text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn records_shapes_and_escaped_text() {
+		let mut svg = Svg::new(20, 10, "Segoe UI");
+		svg.fill_rect(Rect::new(0.0, 0.0, 20.0, 10.0), 0x00_30_20_10);
+		svg.polyline(&[(0.0, 1.0), (2.5, 3.0)], 2.0, 0x00_ff_00_00);
+		svg.text("<A & B>", 1.0, 0.0, TextStyle::new(10.0, FontWeight::Bold), 0x00_ff_ff_ff);
+		let document = svg.finish();
+
+		assert!(document.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\""));
+		assert!(document.contains("font-family=\"'Segoe UI', 'DejaVu Sans Condensed'"));
+		assert!(document.contains("<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#102030\"/>"));
+		assert!(document.contains("points=\"0,1 2.5,3\" fill=\"none\" stroke=\"#0000ff\""));
+		assert!(document.contains(">&lt;A &amp; B&gt;</text>"));
+		assert!(document.trim_end().ends_with("</svg>"));
+	}
+}
@@ -1,11 +1,273 @@
-pub const COLOR_BG: u32 = 0x000e0b0b;
-pub const COLOR_TEXT_GRAY: u32 = 0x00aaaaaa;
-pub const COLOR_TEXT_WHITE: u32 = 0x00ffffff;
+use std::path::{ Path, PathBuf };
+use serde::{ Deserialize, Serialize };
 
-pub const COLOR_DOWN_LINE: u32 = 0x0080de4a;
-pub const COLOR_DOWN_FILL: u32 = 0x0014532d;
+use crate::sys::paths;
 
-pub const COLOR_UP_LINE: u32 = 0x00b672f4;
-pub const COLOR_UP_FILL: u32 = 0x00831843;
+/// Follows the OS light/dark setting instead of naming a theme.
+pub const SYSTEM: &str = "system";
+pub const DEFAULT_THEME: &str = "dark";
+pub const PRESETS: [&str; 4] = ["dark", "light", "high-contrast", "solarized"];
 
-pub const FONT_FACE: &str = "Segoe UI";
+/// Colours are `COLORREF` values (0x00BBGGRR); theme files write them as `#rrggbb`.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Theme {
+	#[serde(with = "hex")]
+	pub background: u32,
+	#[serde(with = "hex")]
+	pub text: u32,
+	/// Captions, units and idle tray numbers.
+	#[serde(with = "hex")]
+	pub text_dim: u32,
+	#[serde(with = "hex")]
+	pub download_line: u32,
+	#[serde(with = "hex")]
+	pub download_fill: u32,
+	#[serde(with = "hex")]
+	pub upload_line: u32,
+	#[serde(with = "hex")]
+	pub upload_fill: u32,
+	/// Tray colour for the busiest speed band.
+	#[serde(with = "hex")]
+	pub busy: u32,
+	/// Empty part of a meter, a step away from the background.
+	#[serde(with = "hex")]
+	pub track: u32,
+	/// Face for natively drawn text. The software renderer always uses the
+	/// bundled font.
+	pub font: String,
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> u32 {
+	(r as u32) | ((g as u32) << 8) | ((b as u32) << 16)
+}
+
+impl Default for Theme {
+	fn default() -> Self {
+		Self::dark()
+	}
+}
+
+impl Theme {
+	fn dark() -> Self {
+		Self {
+			background: rgb(0x0b, 0x0b, 0x0e),
+			text: rgb(0xff, 0xff, 0xff),
+			text_dim: rgb(0xaa, 0xaa, 0xaa),
+			download_line: rgb(0x4a, 0xde, 0x80),
+			download_fill: rgb(0x2d, 0x53, 0x14),
+			upload_line: rgb(0xf4, 0x72, 0xb6),
+			upload_fill: rgb(0x43, 0x18, 0x83),
+			busy: rgb(0xea, 0xb3, 0x08),
+			track: rgb(0x26, 0x26, 0x2a),
+			font: "Segoe UI".to_string(),
+		}
+	}
+
+	fn light() -> Self {
+		Self {
+			background: rgb(0xfa, 0xfa, 0xfa),
+			text: rgb(0x14, 0x14, 0x18),
+			text_dim: rgb(0x64, 0x64, 0x6e),
+			download_line: rgb(0x16, 0xa3, 0x4a),
+			download_fill: rgb(0xbb, 0xf7, 0xd0),
+			upload_line: rgb(0xdb, 0x27, 0x77),
+			upload_fill: rgb(0xfb, 0xcf, 0xe8),
+			busy: rgb(0xca, 0x8a, 0x04),
+			track: rgb(0xe4, 0xe4, 0xe7),
+			..Self::dark()
+		}
+	}
+
+	fn high_contrast() -> Self {
+		Self {
+			background: rgb(0x00, 0x00, 0x00),
+			text: rgb(0xff, 0xff, 0xff),
+			text_dim: rgb(0xe6, 0xe6, 0xe6),
+			download_line: rgb(0x00, 0xff, 0x00),
+			download_fill: rgb(0x00, 0x60, 0x00),
+			upload_line: rgb(0xff, 0x00, 0xff),
+			upload_fill: rgb(0x60, 0x00, 0x60),
+			busy: rgb(0xff, 0xff, 0x00),
+			track: rgb(0x40, 0x40, 0x40),
+			..Self::dark()
+		}
+	}
+
+	/// Solarized dark.
+	fn solarized() -> Self {
+		Self {
+			background: rgb(0x00, 0x2b, 0x36),
+			text: rgb(0xfd, 0xf6, 0xe3),
+			text_dim: rgb(0x93, 0xa1, 0xa1),
+			download_line: rgb(0x85, 0x99, 0x00),
+			download_fill: rgb(0x1f, 0x3a, 0x22),
+			upload_line: rgb(0xd3, 0x36, 0x82),
+			upload_fill: rgb(0x4a, 0x1d, 0x3a),
+			busy: rgb(0xb5, 0x89, 0x00),
+			track: rgb(0x07, 0x36, 0x42),
+			..Self::dark()
+		}
+	}
+
+	pub fn preset(name: &str) -> Option<Self> {
+		match name {
+			"dark" => Some(Self::dark()),
+			"light" => Some(Self::light()),
+			"high-contrast" => Some(Self::high_contrast()),
+			"solarized" => Some(Self::solarized()),
+			_ => None,
+		}
+	}
+
+	/// Parses a theme file. Keys it leaves out come from the preset named by
+	/// `base` (dark when unset), so a file can override a single colour.
+	pub fn parse(text: &str) -> Result<Self, String> {
+		let mut table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+		let base = match table.remove("base") {
+			Some(toml::Value::String(name)) => name,
+			Some(_) => return Err("`base` must be a preset name".to_string()),
+			None => DEFAULT_THEME.to_string(),
+		};
+		let base = Self::preset(&base).ok_or_else(|| format!("Unknown base theme `{}`", base))?;
+
+		let mut merged = toml::Table::try_from(&base).map_err(|e| e.to_string())?;
+		merged.extend(table);
+		toml::Value::Table(merged).try_into().map_err(|e: toml::de::Error| e.to_string())
+	}
+
+	pub fn load(path: &Path) -> Result<Self, String> {
+		let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+		Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+	}
+
+	/// Resolves a theme setting: `system`, a preset name, or the name of a
+	/// user theme file. `system_dark` is the OS preference, if known.
+	/// Fails for a name that is none of these or a file that does not load;
+	/// callers fall back to the default theme and say so.
+	pub fn resolve(name: &str, system_dark: Option<bool>) -> Result<Self, String> {
+		if name == SYSTEM {
+			return Ok(if system_dark == Some(false) { Self::light() } else { Self::dark() });
+		}
+		if let Some(theme) = Self::preset(name) {
+			return Ok(theme);
+		}
+		match user_theme_path(name) {
+			Some(path) if path.exists() => Self::load(&path),
+			_ => Err(format!("Unknown theme \"{}\"", name)),
+		}
+	}
+}
+
+pub fn themes_dir() -> Option<PathBuf> {
+	paths::app_config_dir().map(|dir| dir.join("themes"))
+}
+
+fn user_theme_path(name: &str) -> Option<PathBuf> {
+	themes_dir().map(|dir| dir.join(format!("{}.toml", name)))
+}
+
+/// Names of the `*.toml` files in the themes directory, sorted. Presets take
+/// precedence over files with the same name.
+pub fn user_themes() -> Vec<String> {
+	let Some(entries) = themes_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+		return Vec::new();
+	};
+	let mut names: Vec<String> = entries
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+		.filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+		.filter(|name| !PRESETS.contains(&name.as_str()) && name != SYSTEM)
+		.collect();
+	names.sort();
+	names
+}
+
+/// `#rrggbb` for a `COLORREF`, as theme files, SVG and status bars write it.
+pub fn to_hex(color: u32) -> String {
+	format!("#{:02x}{:02x}{:02x}", color & 0xff, (color >> 8) & 0xff, (color >> 16) & 0xff)
+}
+
+mod hex {
+	use serde::{ Deserialize, Deserializer, Serializer };
+
+	pub fn serialize<S: Serializer>(color: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&super::to_hex(*color))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+		let text = String::deserialize(deserializer)?;
+		parse(&text).ok_or_else(|| {
+			serde::de::Error::custom(format!("`{}` is not a #rrggbb colour", text))
+		})
+	}
+
+	fn parse(text: &str) -> Option<u32> {
+		let hex = text.strip_prefix('#')?;
+		if hex.len() != 6 {
+			return None;
+		}
+		let rgb = u32::from_str_radix(hex, 16).ok()?;
+		// #rrggbb to 0x00bbggrr
+		Some(((rgb >> 16) & 0xff) | (rgb & 0xff00) | ((rgb & 0xff) << 16))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dark_preset_keeps_the_original_colours() {
+		let dark = Theme::preset("dark").unwrap();
+		assert_eq!(dark.background, 0x000e0b0b);
+		assert_eq!(dark.download_line, 0x0080de4a);
+		assert_eq!(dark.upload_fill, 0x00831843);
+		assert_eq!(dark.busy, 0x0008b3ea);
+	}
+
+	#[test]
+	fn presets_round_trip_through_toml() {
+		for name in PRESETS {
+			let theme = Theme::preset(name).unwrap();
+			let text = toml::to_string(&theme).unwrap();
+			assert_eq!(Theme::parse(&text).unwrap(), theme, "{}", name);
+		}
+		let text = toml::to_string(&Theme::preset("dark").unwrap()).unwrap();
+		assert!(text.contains("download-line = \"#4ade80\""), "{}", text);
+	}
+
+	#[test]
+	fn partial_files_inherit_from_their_base() {
+		let theme = Theme::parse("base = \"light\"\nbusy = \"#FF0000\"\n").unwrap();
+		assert_eq!(theme.busy, 0x000000ff);
+		assert_eq!(theme.background, Theme::preset("light").unwrap().background);
+
+		let theme = Theme::parse("font = \"Consolas\"").unwrap();
+		assert_eq!(theme.font, "Consolas");
+		assert_eq!(theme.text, Theme::preset("dark").unwrap().text);
+	}
+
+	#[test]
+	fn bad_files_are_rejected() {
+		assert!(Theme::parse("base = \"neon\"").is_err());
+		assert!(Theme::parse("text = \"white\"").is_err());
+		assert!(Theme::parse("text = \"#12345\"").is_err());
+	}
+
+	#[test]
+	fn system_follows_the_os_preference() {
+		assert_eq!(Theme::resolve(SYSTEM, Some(false)), Ok(Theme::preset("light").unwrap()));
+		assert_eq!(Theme::resolve(SYSTEM, Some(true)), Ok(Theme::preset("dark").unwrap()));
+		assert_eq!(Theme::resolve(SYSTEM, None), Ok(Theme::preset("dark").unwrap()));
+		let solarized = Theme::preset("solarized").unwrap();
+		assert_eq!(Theme::resolve("solarized", Some(false)), Ok(solarized));
+	}
+
+	#[test]
+	fn unknown_themes_are_errors() {
+		let error = Theme::resolve("no-such-theme", None).unwrap_err();
+		assert!(error.contains("no-such-theme"), "{}", error);
+	}
+}
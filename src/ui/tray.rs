@@ -1,152 +1,399 @@
-use windows::Win32::Foundation::{ HWND, SIZE };
-use windows::Win32::Graphics::Gdi::{
-	GetDC,
-	CreateCompatibleDC,
-	CreateDIBSection,
-	SelectObject,
-	DeleteDC,
-	DeleteObject,
-	SetBkMode,
-	SetTextColor,
-	TextOutW,
-	GetTextExtentPoint32W,
-	RoundRect,
-	ReleaseDC,
-	TRANSPARENT,
-	BITMAPINFO,
-	BITMAPINFOHEADER,
-	BI_RGB,
-	DIB_RGB_COLORS,
-	FW_SEMIBOLD,
-};
-use std::ffi::c_void;
-use crate::sys::gdi::{ create_solid_brush, create_pen, create_font, DcScope };
-use crate::ui::theme::*;
+use crate::core::state::{ AppState, IconStyle };
+#[cfg(tray)]
+use crate::sys::display;
+use crate::ui::raster::{ colorref, Raster };
+use crate::ui::font;
+use crate::ui::speed::{ speed_level, SpeedBand };
 use crate::utils::format::format_speed_compact;
 
+/// Layout constants below are in pixels of a 32×32 icon and scale with the
+/// requested size.
+const DESIGN_SIZE: f32 = 32.0;
+/// Below this the design's unit line scales past legibility, so small icons
+/// switch to a layout in fixed pixel sizes.
+const MIN_UNIT_SIZE: u32 = 24;
+/// Font sizes in pixels for that layout: the unit starts at `SMALL_UNIT_PX`
+/// and neither line shrinks below `SMALL_MIN_PX`.
+const SMALL_UNIT_PX: f32 = 7.0;
+const SMALL_MIN_PX: f32 = 6.0;
+
+/// Logical tray icon size when Windows cannot tell us: notification icons
+/// are drawn at small-icon size.
+#[cfg(tray)]
+const LOGICAL_ICON_SIZE: f64 = 16.0;
+
+/// Horizontal room for the number, leaving a pixel of background on each side.
+const VALUE_MAX_WIDTH: f32 = 30.0;
+/// Smallest value font size in pixels before we stop shrinking.
+const MIN_VALUE_EM: f32 = 9.0;
+const UNIT_EM: f32 = 10.0;
+const UNIT_BASELINE: f32 = 29.0;
+/// Bottom of the area the number is centred in, above the unit line.
+const VALUE_AREA_BOTTOM: f32 = 19.0;
+
+/// Samples shown by the sparkline, one per pixel column.
+const SPARKLINE_SECONDS: usize = 30;
+/// Sparklines never scale below this, so an idle link draws flat.
+const SPARKLINE_MIN_SCALE: u64 = 16 * 1024;
+const SPLIT_EM: f32 = 14.0;
+
 pub struct TrayIconGenerator;
 
-impl TrayIconGenerator {
-	pub fn generate(speed: u64) -> Result<tray_icon::Icon, String> {
-		unsafe {
-			let width = 32;
-			let height = 32;
-
-			let bmi = BITMAPINFO {
-				bmiHeader: BITMAPINFOHEADER {
-					biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
-					biWidth: width,
-					biHeight: -height,
-					biPlanes: 1,
-					biBitCount: 32,
-					biCompression: BI_RGB.0,
-					..Default::default()
-				},
-				..Default::default()
-			};
-
-			let hdc_screen = GetDC(HWND(std::ptr::null_mut()));
-			let hdc_mem = CreateCompatibleDC(hdc_screen);
-			if hdc_mem.is_invalid() {
-				return Err("CreateCompatibleDC failed".to_string());
-			}
+/// Everything that decides what an icon looks like. Two states with the same
+/// key render identical icons, so the key can index a render cache and tell
+/// whether the tray needs updating at all.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct IconKey {
+	style: IconStyle,
+	size: u32,
+	/// The text shown, or bar and sparkline heights in quarter pixels.
+	content: String,
+	band: u32,
+}
 
-			let mut bits: *mut c_void = std::ptr::null_mut();
-			let hbitmap = CreateDIBSection(hdc_mem, &bmi, DIB_RGB_COLORS, &mut bits, None, 0).map_err(|e|
-				e.to_string()
-			)?;
+impl TrayIconGenerator {
+	/// The pixel size to render at: what the platform reports for the
+	/// monitor at `point`, where the tray icon is, otherwise the logical tray
+	/// size at `scale` (the display's DPI scale, 1.0 at 96 DPI).
+	#[cfg(tray)]
+	pub fn icon_size(point: Option<(i32, i32)>, scale: f64) -> u32 {
+		display::tray_icon_size(point)
+			.unwrap_or_else(|| (LOGICAL_ICON_SIZE * scale).round() as u32)
+			.max(16)
+	}
 
-			if bits.is_null() {
-				return Err("CreateDIBSection returned null bits".to_string());
+	pub fn key(state: &AppState, size: u32) -> IconKey {
+		let k = (size as f32) / DESIGN_SIZE;
+		let quarters = |heights: &mut dyn Iterator<Item = f32>| -> String {
+			heights
+				.map(|h| ((h * 4.0).round() as i32).to_string())
+				.collect::<Vec<_>>()
+				.join(",")
+		};
+		let (content, band) = match state.icon_style {
+			IconStyle::Number => {
+				let (value, unit) = format_speed_compact(state.down_bps, state.units, state.numbers);
+				(format!("{} {}", value, unit), SpeedBand::of(state.down_bps).color(&state.theme))
 			}
-
-			let old_bmp = SelectObject(hdc_mem, hbitmap);
-
-			{
-				let mut scope = DcScope::new(hdc_mem);
-
-				std::ptr::write_bytes(bits, 0, (width * height * 4) as usize);
-
-				let bg_brush = create_solid_brush(COLOR_BG);
-				let null_pen = create_pen(windows::Win32::Graphics::Gdi::PS_NULL, 0, 0);
-				scope.select(&bg_brush);
-				scope.select(&null_pen);
-				let _ = RoundRect(hdc_mem, 0, 0, width, height, 6, 6);
-
-				SetBkMode(hdc_mem, TRANSPARENT);
-
-				let (val_str, unit_str) = format_speed_compact(speed);
-
-				let color = if speed < 100 * 1024 {
-					COLOR_TEXT_GRAY
-				} else if speed < 5 * 1024 * 1024 {
-					COLOR_DOWN_LINE
-				} else {
-					0x0008b3ea
-				};
-
-				let _ = SetTextColor(hdc_mem, windows::Win32::Foundation::COLORREF(color));
-
-				let font_height = match val_str.len() {
-					1 => -22,
-					2 => -18,
-					_ => -14,
-				};
-
-				let font_val = create_font(font_height, FW_SEMIBOLD.0 as i32, FONT_FACE);
-				scope.select(&font_val);
-
-				let w_val = wide_string(&val_str);
-				let mut size_val = SIZE::default();
-				let _ = GetTextExtentPoint32W(hdc_mem, &w_val, &mut size_val);
-				let x_val = (width - size_val.cx) / 2;
-
-				let y_val = match val_str.len() {
-					1 => -5,
-					2 => -3,
-					_ => -1,
-				};
-				let _ = TextOutW(hdc_mem, x_val, y_val, &w_val);
-
-				let font_unit = create_font(-11, 0, FONT_FACE);
-				scope.select(&font_unit);
-				let w_unit = wide_string(&unit_str);
-				let mut size_unit = SIZE::default();
-				let _ = GetTextExtentPoint32W(hdc_mem, &w_unit, &mut size_unit);
-				let x_unit = (width - size_unit.cx) / 2;
-				let _ = TextOutW(hdc_mem, x_unit, 15, &w_unit);
+			IconStyle::Sparkline => {
+				let (down, up) = sparkline_points(state, k);
+				let down = quarters(&mut down.iter().map(|p| p.1));
+				let up = quarters(&mut up.iter().map(|p| p.1));
+				(format!("{};{}", down, up), 0)
 			}
-
-			let pixel_count = (width * height) as usize;
-			let pixels = std::slice::from_raw_parts_mut(bits as *mut u32, pixel_count);
-			for p in pixels.iter_mut() {
-				if *p != 0 {
-					*p |= 0xff000000;
-				}
+			IconStyle::Bars => {
+				let heights = [state.down_bps, state.up_bps].map(|speed| bar_height(speed, k));
+				(quarters(&mut heights.into_iter()), 0)
 			}
-
-			let mut rgba = Vec::with_capacity(pixel_count * 4);
-			for p in pixels.iter() {
-				let a = (*p >> 24) as u8;
-				let r = (*p >> 16) as u8;
-				let g = (*p >> 8) as u8;
-				let b = *p as u8;
-				rgba.push(r);
-				rgba.push(g);
-				rgba.push(b);
-				rgba.push(a);
+			IconStyle::Split => {
+				let down = split_label(state.down_bps, state);
+				(format!("{} {}", down, split_label(state.up_bps, state)), 0)
 			}
+			IconStyle::Dot => {
+				(String::new(), SpeedBand::of(state.down_bps.max(state.up_bps)).color(&state.theme))
+			}
+		};
+		IconKey { style: state.icon_style, size, content, band }
+	}
+
+	#[cfg(tray)]
+	pub fn generate(state: &AppState, size: u32) -> Result<tray_icon::Icon, String> {
+		let raster = Self::render(state, size)?;
+		tray_icon::Icon::from_rgba(raster.to_rgba(), size, size).map_err(|e| e.to_string())
+	}
 
-			let _ = SelectObject(hdc_mem, old_bmp);
-			let _ = DeleteObject(hbitmap);
-			let _ = DeleteDC(hdc_mem);
-			ReleaseDC(HWND(std::ptr::null_mut()), hdc_screen);
+	/// Draws the icon for `state.icon_style` at `size`×`size` pixels into an
+	/// RGBA raster without touching any platform API.
+	pub fn render(state: &AppState, size: u32) -> Result<Raster, String> {
+		let mut raster = Raster::new(size, size)?;
+		let k = (size as f32) / DESIGN_SIZE;
+		// The dot stands on its own; everything else gets the usual tile.
+		if state.icon_style != IconStyle::Dot {
+			let size = size as f32;
+			raster.fill_round_rect(0.0, 0.0, size, size, 3.0 * k, colorref(state.theme.background));
+		}
 
-			tray_icon::Icon::from_rgba(rgba, width as u32, height as u32).map_err(|e| e.to_string())
+		match state.icon_style {
+			IconStyle::Number if size < MIN_UNIT_SIZE => draw_number_small(&mut raster, state, k),
+			IconStyle::Number => draw_number(&mut raster, state, k),
+			IconStyle::Sparkline => draw_sparkline(&mut raster, state, k),
+			IconStyle::Bars => draw_bars(&mut raster, state, k),
+			IconStyle::Split => draw_split(&mut raster, state, k),
+			IconStyle::Dot => draw_dot(&mut raster, state, k),
 		}
+
+		Ok(raster)
+	}
+}
+
+/// Largest size from `em` down that fits `text` into `max_width`, and the
+/// width it measures at.
+fn fit_em(text: &str, mut em: f32, min_em: f32, max_width: f32) -> (f32, f32) {
+	let mut width = font::text_width(text, em);
+	while width > max_width && em > min_em {
+		em -= 0.5;
+		width = font::text_width(text, em);
+	}
+	(em, width)
+}
+
+fn draw_number(raster: &mut Raster, state: &AppState, k: f32) {
+	let size = DESIGN_SIZE * k;
+	let (val_str, unit_str) = format_speed_compact(state.down_bps, state.units, state.numbers);
+	let color = colorref(SpeedBand::of(state.down_bps).color(&state.theme));
+
+	// The formatter caps the number at three characters; start from a
+	// size tuned to its length and shrink until it measures inside the icon.
+	let em: f32 = match val_str.chars().count() {
+		1 => 22.0,
+		2 => 18.0,
+		_ => 14.0,
+	};
+	let (em, width) = fit_em(&val_str, em * k, MIN_VALUE_EM * k, VALUE_MAX_WIDTH * k);
+	let baseline = (VALUE_AREA_BOTTOM * k + font::digit_height(em)) / 2.0;
+	raster.draw_text(&val_str, (size - width) / 2.0, baseline.round(), em, color);
+
+	let unit_em = UNIT_EM * k;
+	let unit_width = font::text_width(&unit_str, unit_em);
+	raster.draw_text(
+		&unit_str,
+		((size - unit_width) / 2.0).round(),
+		(UNIT_BASELINE * k).round(),
+		unit_em,
+		colorref(state.theme.text_dim)
+	);
+}
+
+/// Small icons keep both lines but size them in pixels: the unit at the
+/// bottom in the smallest legible font, cut to its prefix letter if even
+/// that is too wide, and the number as large as fits above it.
+fn draw_number_small(raster: &mut Raster, state: &AppState, k: f32) {
+	let size = DESIGN_SIZE * k;
+	let (val_str, unit_str) = format_speed_compact(state.down_bps, state.units, state.numbers);
+
+	let (mut unit_em, mut unit_width) = fit_em(&unit_str, SMALL_UNIT_PX, SMALL_MIN_PX, size);
+	let mut unit_str = unit_str;
+	if unit_width > size {
+		unit_str = unit_str.chars().take(1).collect();
+		(unit_em, unit_width) = fit_em(&unit_str, SMALL_UNIT_PX, SMALL_MIN_PX, size);
+	}
+	let unit_baseline = size.round() - 1.0;
+	raster.draw_text(
+		&unit_str,
+		((size - unit_width) / 2.0).round(),
+		unit_baseline,
+		unit_em,
+		colorref(state.theme.text_dim)
+	);
+
+	// A pixel of background between the number and the unit.
+	let value_bottom = unit_baseline - font::digit_height(unit_em).ceil() - 1.0;
+	let em = (value_bottom - 1.0) / font::digit_height(1.0);
+	let (em, width) = fit_em(&val_str, em, SMALL_MIN_PX, size - 1.0);
+	let baseline = (value_bottom + font::digit_height(em)) / 2.0;
+	raster.draw_text(
+		&val_str,
+		(size - width) / 2.0,
+		baseline.round(),
+		em,
+		colorref(SpeedBand::of(state.down_bps).color(&state.theme))
+	);
+}
+
+type Points = Vec<(f32, f32)>;
+
+/// Down and up sparkline points, in pixels.
+fn sparkline_points(state: &AppState, k: f32) -> (Points, Points) {
+	let recent = |history: &std::collections::VecDeque<u64>| -> Vec<u64> {
+		history.iter().skip(history.len().saturating_sub(SPARKLINE_SECONDS)).copied().collect()
+	};
+	let down = recent(&state.down_history);
+	let up = recent(&state.up_history);
+	// Both lines share one scale so their heights compare.
+	let max = down.iter().chain(&up).copied().max().unwrap_or(0).max(SPARKLINE_MIN_SCALE);
+
+	let (top, bottom) = (3.0 * k, 29.0 * k);
+	let points = |samples: &[u64]| -> Points {
+		// Right-aligned, so a short history grows in from the right edge.
+		let start = 1 + SPARKLINE_SECONDS - samples.len();
+		samples
+			.iter()
+			.enumerate()
+			.map(|(i, &value)| {
+				let h = ((value as f64) / (max as f64)) as f32 * (bottom - top);
+				(((start + i) as f32) * k, bottom - h)
+			})
+			.collect()
+	};
+	(points(&down), points(&up))
+}
+
+fn draw_sparkline(raster: &mut Raster, state: &AppState, k: f32) {
+	let bottom = 29.0 * k;
+	let (down_points, up_points) = sparkline_points(state, k);
+	if let (Some(&(first, _)), Some(&(last, _))) = (down_points.first(), down_points.last()) {
+		let mut area = Vec::with_capacity(down_points.len() + 2);
+		area.push((first, bottom));
+		area.extend_from_slice(&down_points);
+		area.push((last, bottom));
+		raster.fill_polygon(&area, colorref(state.theme.download_fill));
+		raster.stroke_polyline(&down_points, 1.5 * k, colorref(state.theme.download_line));
 	}
+	raster.stroke_polyline(&up_points, 1.5 * k, colorref(state.theme.upload_line));
+}
+
+const BAR_TOP: f32 = 3.0;
+const BAR_BOTTOM: f32 = 29.0;
+
+fn bar_height(speed: u64, k: f32) -> f32 {
+	(speed_level(speed) as f32) * (BAR_BOTTOM - BAR_TOP) * k
+}
+
+fn draw_bars(raster: &mut Raster, state: &AppState, k: f32) {
+	let (top, bottom) = (BAR_TOP * k, BAR_BOTTOM * k);
+	let (width, radius) = (11.0 * k, 2.0 * k);
+	let bar = |raster: &mut Raster, x: f32, speed: u64, color: u32| {
+		let h = bar_height(speed, k);
+		raster.fill_round_rect(x, top, width, bottom - top, radius, colorref(state.theme.track));
+		if h > 0.0 {
+			raster.fill_round_rect(x, bottom - h, width, h, radius, colorref(color));
+		}
+	};
+	bar(raster, 4.0 * k, state.down_bps, state.theme.download_line);
+	bar(raster, 17.0 * k, state.up_bps, state.theme.upload_line);
+}
+
+/// Only the prefix letter fits next to the number, e.g. `1.9M` or `512B`.
+fn split_label(speed: u64, state: &AppState) -> String {
+	let (value, unit) = format_speed_compact(speed, state.units, state.numbers);
+	format!("{}{}", value, unit.chars().next().unwrap_or_default())
+}
+
+fn draw_split(raster: &mut Raster, state: &AppState, k: f32) {
+	let size = DESIGN_SIZE * k;
+	let max_width = VALUE_MAX_WIDTH * k;
+	let row = |raster: &mut Raster, speed: u64, baseline: f32, color: u32| {
+		let text = split_label(speed, state);
+		let (em, width) = fit_em(&text, SPLIT_EM * k, MIN_VALUE_EM * k, max_width);
+		raster.draw_text(
+			&text,
+			((size - width) / 2.0).round(),
+			(baseline * k).round(),
+			em,
+			colorref(color)
+		);
+	};
+	row(raster, state.down_bps, 14.0, state.theme.download_line);
+	row(raster, state.up_bps, 29.0, state.theme.upload_line);
+}
+
+fn draw_dot(raster: &mut Raster, state: &AppState, k: f32) {
+	let color = SpeedBand::of(state.down_bps.max(state.up_bps)).color(&state.theme);
+	raster.fill_round_rect(4.0 * k, 4.0 * k, 24.0 * k, 24.0 * k, 12.0 * k, colorref(state.theme.background));
+	raster.fill_round_rect(7.0 * k, 7.0 * k, 18.0 * k, 18.0 * k, 9.0 * k, colorref(color));
 }
 
-fn wide_string(s: &str) -> Vec<u16> {
-	s.encode_utf16().chain(std::iter::once(0)).collect()
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ui::golden;
+	use crate::ui::theme::{ Theme, PRESETS };
+	use crate::utils::format::{ DataUnit, NumberFormat, UnitPrefix, UnitSystem };
+
+	const SPEEDS: &[u64] = &[0, 512, 9_951, 150_000, 2_000_000, 12_000_000, 999_000_000];
+
+	fn state(style: IconStyle, units: UnitSystem, down: u64, up: u64) -> AppState {
+		let mut state = AppState::new();
+		state.icon_style = style;
+		state.units = units;
+		state.numbers = NumberFormat::POINT;
+		state.down_bps = down;
+		state.up_bps = up;
+		state
+	}
+
+	#[test]
+	fn icons_match_golden_images() {
+		let systems = [
+			("bytes", UnitSystem::new(DataUnit::Bytes, UnitPrefix::Binary)),
+			("bits", UnitSystem::new(DataUnit::Bits, UnitPrefix::Decimal)),
+		];
+		let cases = systems.iter().flat_map(|&(system, units)| {
+			SPEEDS.iter().map(move |&speed| {
+				let raster = TrayIconGenerator::render(
+					&state(IconStyle::Number, units, speed, 0),
+					32
+				).unwrap();
+				(format!("tray-{}-{}", system, speed), raster)
+			})
+		});
+		golden::assert_all(cases);
+	}
+
+	#[test]
+	fn styles_match_golden_images() {
+		let styles = [
+			("sparkline", IconStyle::Sparkline),
+			("bars", IconStyle::Bars),
+			("split", IconStyle::Split),
+			("dot", IconStyle::Dot),
+		];
+		// An upload-heavy moment, like a backup running.
+		let cases = styles.iter().map(|&(name, style)| {
+			let mut state = state(style, UnitSystem::default(), 40_000, 6_000_000);
+			for i in 0..40u64 {
+				state.down_history.push_back(20_000 + (i % 7) * 15_000);
+				state.up_history.push_back(1_000_000 + i * 150_000);
+			}
+			(format!("tray-style-{}", name), TrayIconGenerator::render(&state, 32).unwrap())
+		});
+		golden::assert_all(cases);
+	}
+
+	#[test]
+	fn key_follows_what_is_displayed() {
+		let units = UnitSystem::default();
+		let key = |style, down, up| TrayIconGenerator::key(&state(style, units, down, up), 32);
+
+		// 2.00 and 2.04 MiB/s both read "2.0 MiB" in the same colour.
+		assert_eq!(key(IconStyle::Number, 2_097_152, 0), key(IconStyle::Number, 2_139_095, 5));
+		assert_ne!(key(IconStyle::Number, 2_097_152, 0), key(IconStyle::Number, 2_202_009, 0));
+		// Same text, different colour band.
+		assert_ne!(key(IconStyle::Number, 99 * 1024, 0), key(IconStyle::Number, 100 * 1024, 0));
+		// The number style ignores upload; the split style does not.
+		assert_ne!(key(IconStyle::Split, 2_097_152, 0), key(IconStyle::Split, 2_097_152, 500_000));
+		assert_ne!(key(IconStyle::Number, 0, 0), key(IconStyle::Dot, 0, 0));
+		assert_ne!(
+			TrayIconGenerator::key(&state(IconStyle::Number, units, 0, 0), 16),
+			TrayIconGenerator::key(&state(IconStyle::Number, units, 0, 0), 32)
+		);
+	}
+
+	#[test]
+	fn sizes_match_golden_images() {
+		let cases = [16, 20, 22, 24, 48].iter().flat_map(|&size| {
+			[
+				("number", IconStyle::Number),
+				("split", IconStyle::Split),
+			].map(|(name, style)| {
+				let state = state(style, UnitSystem::default(), 2_000_000, 150_000);
+				let raster = TrayIconGenerator::render(&state, size).unwrap();
+				(format!("tray-size-{}-{}", size, name), raster)
+			})
+		});
+		golden::assert_all(cases);
+	}
+
+	#[test]
+	fn themes_match_golden_images() {
+		let cases = PRESETS.iter().flat_map(|&name| {
+			[0, 150_000, 12_000_000].map(|speed| {
+				let mut state = state(IconStyle::Number, UnitSystem::default(), speed, 0);
+				state.theme = Theme::preset(name).unwrap();
+				let raster = TrayIconGenerator::render(&state, 32).unwrap();
+				(format!("tray-theme-{}-{}", name, speed), raster)
+			})
+		});
+		golden::assert_all(cases);
+	}
 }
@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+
+use crate::ui::axis::Axis;
+use crate::ui::canvas::{ Canvas, FontWeight, Point, Rect, TextStyle };
+use crate::ui::resample::resample;
+use crate::ui::theme::Theme;
+use crate::utils::format::{ format_speed_tick, NumberFormat, UnitSystem };
+
+const LABEL_STYLE: TextStyle = TextStyle::new(12.0, FontWeight::SemiBold);
+const VALUE_STYLE: TextStyle = TextStyle::new(24.0, FontWeight::Bold);
+const COMPACT_VALUE_STYLE: TextStyle = TextStyle::new(16.0, FontWeight::Bold);
+/// Space between the label line and the value.
+const LINE_GAP: f32 = 4.0;
+const AXIS_STYLE: TextStyle = TextStyle::new(10.0, FontWeight::SemiBold);
+/// Gap between a gridline label and the right edge.
+const AXIS_LABEL_INSET: f32 = 4.0;
+/// Minimum space between two gridline labels.
+const AXIS_LABEL_GAP: f32 = 2.0;
+const TOOLTIP_STYLE: TextStyle = TextStyle::new(11.0, FontWeight::SemiBold);
+const TOOLTIP_PADDING: f32 = 6.0;
+/// Distance between a tooltip and the point it describes.
+const TOOLTIP_OFFSET: f32 = 8.0;
+const TIME_TICK_SECONDS: usize = 60;
+const TIME_TICK_LENGTH: f32 = 4.0;
+
+/// A small caption over a large value, e.g. `↓ DOWNLOAD` over `4.0 MiB/s`.
+pub struct Readout<'a> {
+	pub label: &'a str,
+	pub value: &'a str,
+	/// A smaller value, for two readouts side by side.
+	pub compact: bool,
+	pub theme: &'a Theme,
+}
+
+impl Readout<'_> {
+	pub fn draw(&self, canvas: &mut dyn Canvas, rect: Rect) {
+		canvas.text(self.label, rect.x, rect.y, LABEL_STYLE, self.theme.text_dim);
+		let (_, label_height) = canvas.measure_text(self.label, LABEL_STYLE);
+		let value_y = rect.y + label_height + LINE_GAP;
+		let style = if self.compact { COMPACT_VALUE_STYLE } else { VALUE_STYLE };
+		canvas.text(self.value, rect.x, value_y, style, self.theme.text);
+	}
+}
+
+/// Lines of text in a box beside a point, flipped to the left near the
+/// right edge and kept inside `bounds`.
+pub struct Tooltip<'a> {
+	pub lines: &'a [String],
+	pub theme: &'a Theme,
+}
+
+impl Tooltip<'_> {
+	pub fn draw(&self, canvas: &mut dyn Canvas, bounds: Rect, (x, y): Point) {
+		let sizes: Vec<(f32, f32)> = self.lines
+			.iter()
+			.map(|line| canvas.measure_text(line, TOOLTIP_STYLE))
+			.collect();
+		let width = sizes.iter().map(|&(w, _)| w).fold(0.0, f32::max) + 2.0 * TOOLTIP_PADDING;
+		let height = sizes.iter().map(|&(_, h)| h).sum::<f32>() + 2.0 * TOOLTIP_PADDING;
+
+		let mut left = x + TOOLTIP_OFFSET;
+		if left + width > bounds.right() {
+			left = x - TOOLTIP_OFFSET - width;
+		}
+		let left = left.max(bounds.x).round();
+		let top = (y - height / 2.0).min(bounds.bottom() - height).max(bounds.y).round();
+		canvas.fill_rect(Rect::new(left, top, width, height), self.theme.track);
+
+		let mut line_y = top + TOOLTIP_PADDING;
+		for (line, &(_, line_height)) in self.lines.iter().zip(&sizes) {
+			canvas.text(line, left + TOOLTIP_PADDING, line_y, TOOLTIP_STYLE, self.theme.text);
+			line_y += line_height;
+		}
+	}
+}
+
+/// A filled area graph of a rate history, newest sample on the right, over
+/// labelled gridlines with a tick mark every minute along the baseline.
+pub struct AreaGraph<'a> {
+	pub history: &'a VecDeque<u64>,
+	/// Number of samples that span the full width, one per second. The
+	/// history is resampled to however many pixels that is.
+	pub capacity: usize,
+	pub axis: &'a Axis,
+	pub units: UnitSystem,
+	pub numbers: NumberFormat,
+	pub line: u32,
+	/// Area colour; `None` draws just the line.
+	pub fill: Option<u32>,
+	/// Grows down from the top edge, for the lower half of a mirrored chart.
+	pub flipped: bool,
+	pub theme: &'a Theme,
+}
+
+impl AreaGraph<'_> {
+	pub fn draw(&self, canvas: &mut dyn Canvas, rect: Rect) {
+		let gridlines: Vec<(f64, f32)> = self.axis.ticks
+			.iter()
+			.map(|&tick| (tick, self.y(tick, rect)))
+			.collect();
+		for &(_, y) in &gridlines {
+			canvas.fill_rect(Rect::new(rect.x, y, rect.w, 1.0), self.theme.track);
+		}
+
+		self.draw_area(canvas, rect);
+
+		// Labels sit inside the graph on the baseline side of their line,
+		// from the outermost in, skipping any that would crowd the previous
+		// one or not fit.
+		let mut free = if self.flipped { rect.bottom() } else { rect.y };
+		for &(tick, y) in gridlines.iter().rev() {
+			let label = format_speed_tick(tick, self.units, self.numbers);
+			let (width, height) = canvas.measure_text(&label, AXIS_STYLE);
+			let top = if self.flipped { y - height } else { y + 1.0 };
+			let fits = if self.flipped {
+				top >= rect.y && y <= free
+			} else {
+				top >= free && top + height <= rect.bottom()
+			};
+			if !fits {
+				continue;
+			}
+			let x = rect.right() - width - AXIS_LABEL_INSET;
+			canvas.text(&label, x, top, AXIS_STYLE, self.theme.text_dim);
+			free = if self.flipped { top - AXIS_LABEL_GAP } else { top + height + AXIS_LABEL_GAP };
+		}
+	}
+
+	/// The edge the graph grows from and the direction it grows in.
+	fn baseline(&self, rect: Rect) -> (f32, f32) {
+		if self.flipped { (rect.y, 1.0) } else { (rect.bottom(), -1.0) }
+	}
+
+	/// Top of a one pixel line at `value`, kept inside the graph.
+	fn y(&self, value: f64, rect: Rect) -> f32 {
+		let (baseline, direction) = self.baseline(rect);
+		let y = baseline + direction * (self.axis.fraction(value) as f32) * rect.h;
+		y.round().clamp(rect.y, rect.bottom() - 1.0)
+	}
+
+	fn draw_area(&self, canvas: &mut dyn Canvas, rect: Rect) {
+		let samples: Vec<u64> = self.history.iter().copied().collect();
+		let columns = resample(&samples, self.capacity, rect.w.round() as usize);
+		if columns.is_empty() {
+			return;
+		}
+		let (baseline, direction) = self.baseline(rect);
+		let y = |value: f64| baseline + direction * (self.axis.fraction(value) as f32) * rect.h;
+
+		let mut area = Vec::with_capacity(columns.len() + 2);
+		let mut points = Vec::with_capacity(columns.len());
+		area.push((rect.x, baseline));
+		for (i, column) in columns.iter().enumerate() {
+			let x = rect.x + (i as f32);
+			area.push((x, y(column.max)));
+			if column.min == column.max {
+				points.push((x, y(column.max)));
+			} else {
+				let (from, to) = if column.rising {
+					(column.min, column.max)
+				} else {
+					(column.max, column.min)
+				};
+				points.push((x, y(from)));
+				points.push((x, y(to)));
+			}
+		}
+		area.push((rect.x + (columns.len() as f32), baseline));
+		if let Some(fill) = self.fill {
+			canvas.fill_polygon(&area, fill);
+		}
+		canvas.polyline(&points, 2.0, self.line);
+
+		// Minute marks, counted back from the newest sample.
+		let newest = rect.x + ((columns.len() - 1) as f32);
+		let interval = ((TIME_TICK_SECONDS as f32) * rect.w) / (self.capacity.max(1) as f32);
+		let top = if self.flipped { baseline } else { baseline - TIME_TICK_LENGTH };
+		let mut x = newest - interval;
+		while x >= rect.x {
+			canvas.fill_rect(Rect::new(x.round(), top, 1.0, TIME_TICK_LENGTH), self.theme.text_dim);
+			x -= interval;
+		}
+	}
+}
@@ -0,0 +1,138 @@
+use std::collections::{ HashMap, VecDeque };
+use std::hash::Hash;
+
+/// A small least-recently-used cache for rendered images, with hit counters.
+pub struct RenderCache<K, V> {
+	entries: HashMap<K, V>,
+	/// Keys from least to most recently used.
+	order: VecDeque<K>,
+	capacity: usize,
+	// Only read by the hit-rate report in debug builds.
+	#[cfg_attr(not(debug_assertions), allow(dead_code))]
+	hits: u64,
+	#[cfg_attr(not(debug_assertions), allow(dead_code))]
+	misses: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> RenderCache<K, V> {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			entries: HashMap::with_capacity(capacity),
+			order: VecDeque::with_capacity(capacity),
+			capacity: capacity.max(1),
+			hits: 0,
+			misses: 0,
+		}
+	}
+
+	/// Returns the cached value for `key`, rendering and storing it on a miss.
+	/// Failed renders are not cached.
+	pub fn get_or_render(
+		&mut self,
+		key: K,
+		render: impl FnOnce() -> Result<V, String>
+	) -> Result<&V, String> {
+		if self.entries.contains_key(&key) {
+			self.hits += 1;
+			if let Some(index) = self.order.iter().position(|k| *k == key) {
+				let key = self.order.remove(index).unwrap();
+				self.order.push_back(key);
+			}
+		} else {
+			self.misses += 1;
+			let value = render()?;
+			if self.entries.len() >= self.capacity {
+				if let Some(oldest) = self.order.pop_front() {
+					self.entries.remove(&oldest);
+				}
+			}
+			self.order.push_back(key.clone());
+			self.entries.insert(key.clone(), value);
+		}
+		Ok(&self.entries[&key])
+	}
+
+	#[cfg(tray)]
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.order.clear();
+	}
+}
+
+#[cfg(any(test, debug_assertions))]
+impl<K: Eq + Hash + Clone, V> RenderCache<K, V> {
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn hits(&self) -> u64 {
+		self.hits
+	}
+
+	pub fn misses(&self) -> u64 {
+		self.misses
+	}
+
+	/// Share of lookups served from the cache, 0.0 before the first lookup.
+	pub fn hit_rate(&self) -> f64 {
+		let lookups = self.hits + self.misses;
+		if lookups == 0 { 0.0 } else { (self.hits as f64) / (lookups as f64) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn renders_once_per_key() {
+		let mut cache = RenderCache::new(4);
+		let mut renders = 0;
+		for key in ["a", "b", "a", "a", "b"] {
+			cache
+				.get_or_render(key, || {
+					renders += 1;
+					Ok(key.to_uppercase())
+				})
+				.unwrap();
+		}
+		assert_eq!(renders, 2);
+		assert_eq!((cache.hits(), cache.misses()), (3, 2));
+		assert!((cache.hit_rate() - 0.6).abs() < 1e-9);
+	}
+
+	#[test]
+	fn evicts_least_recently_used() {
+		let mut cache = RenderCache::new(2);
+		cache.get_or_render(1, || Ok(1)).unwrap();
+		cache.get_or_render(2, || Ok(2)).unwrap();
+		// Touch 1 so that 2 becomes the oldest.
+		cache.get_or_render(1, || Ok(1)).unwrap();
+		cache.get_or_render(3, || Ok(3)).unwrap();
+		assert_eq!(cache.len(), 2);
+
+		let mut rendered = false;
+		cache
+			.get_or_render(1, || {
+				rendered = true;
+				Ok(1)
+			})
+			.unwrap();
+		assert!(!rendered);
+		cache
+			.get_or_render(2, || {
+				rendered = true;
+				Ok(2)
+			})
+			.unwrap();
+		assert!(rendered);
+	}
+
+	#[test]
+	fn failed_renders_are_not_cached() {
+		let mut cache: RenderCache<u32, u32> = RenderCache::new(2);
+		assert!(cache.get_or_render(1, || Err("boom".to_string())).is_err());
+		assert_eq!(cache.len(), 0);
+		assert_eq!(*cache.get_or_render(1, || Ok(7)).unwrap(), 7);
+	}
+}
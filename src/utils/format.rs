@@ -1,23 +1,603 @@
-pub fn format_speed_full(bytes_per_sec: u64) -> String {
-	if bytes_per_sec < 1024 {
-		format!("{} B/s", bytes_per_sec)
-	} else if bytes_per_sec < 1024 * 1024 {
-		format!("{:.1} KB/s", (bytes_per_sec as f64) / 1024.0)
-	} else if bytes_per_sec < 1024 * 1024 * 1024 {
-		format!("{:.1} MB/s", (bytes_per_sec as f64) / (1024.0 * 1024.0))
+use std::time::Duration;
+use fluent_bundle::FluentArgs;
+use serde::{ Deserialize, Serialize };
+
+use crate::i18n::I18n;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DataUnit {
+	#[default]
+	Bytes,
+	Bits,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitPrefix {
+	/// SI prefixes, k = 1000.
+	Decimal,
+	/// IEC prefixes, Ki = 1024.
+	#[default]
+	Binary,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+#[serde(default)]
+pub struct UnitSystem {
+	pub unit: DataUnit,
+	pub prefix: UnitPrefix,
+}
+
+const TIERS: usize = 7;
+
+const BYTE_DECIMAL: [&str; TIERS] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+const BYTE_BINARY: [&str; TIERS] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const BIT_DECIMAL: [&str; TIERS] = ["bit", "kbit", "Mbit", "Gbit", "Tbit", "Pbit", "Ebit"];
+const BIT_BINARY: [&str; TIERS] = ["bit", "Kibit", "Mibit", "Gibit", "Tibit", "Pibit", "Eibit"];
+
+const BIT_DECIMAL_COMPACT: [&str; TIERS] = ["b", "kb", "Mb", "Gb", "Tb", "Pb", "Eb"];
+const BIT_BINARY_COMPACT: [&str; TIERS] = ["b", "Kib", "Mib", "Gib", "Tib", "Pib", "Eib"];
+
+/// Separators used when printing numbers for the UI locale.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct NumberFormat {
+	pub decimal: char,
+}
+
+impl Default for NumberFormat {
+	fn default() -> Self {
+		Self::POINT
+	}
+}
+
+/// Languages whose conventional decimal separator is a comma.
+const COMMA_DECIMAL_LANGUAGES: &[&str] = &[
+	"be", "bg", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "it", "kk", "lt",
+	"lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr", "uk", "vi",
+];
+
+impl NumberFormat {
+	pub const POINT: Self = Self { decimal: '.' };
+	pub const COMMA: Self = Self { decimal: ',' };
+
+	/// Picks separators from a BCP 47 tag such as `de-DE` or `ru`.
+	pub fn for_locale(tag: &str) -> Self {
+		let language = tag.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+		if COMMA_DECIMAL_LANGUAGES.contains(&language.as_str()) {
+			Self::COMMA
+		} else {
+			Self::POINT
+		}
+	}
+
+	pub fn fixed(&self, value: f64, decimals: usize) -> String {
+		let text = format!("{:.*}", decimals, value);
+		if self.decimal == '.' {
+			text
+		} else {
+			text.replace('.', self.decimal.encode_utf8(&mut [0; 4]))
+		}
+	}
+}
+
+/// Width of the tray icon number. Three characters is enough for every
+/// `u64` rate once the prefix goes up to exa.
+pub const COMPACT_MAX_CHARS: usize = 3;
+
+impl UnitSystem {
+	#[cfg(any(tray, feature = "tui", test))]
+	pub const fn new(unit: DataUnit, prefix: UnitPrefix) -> Self {
+		Self { unit, prefix }
+	}
+
+	pub fn base(&self) -> f64 {
+		match self.prefix {
+			UnitPrefix::Decimal => 1000.0,
+			UnitPrefix::Binary => 1024.0,
+		}
+	}
+
+	/// Converts a byte count into the unit being displayed.
+	pub fn quantity(&self, bytes: u64) -> f64 {
+		match self.unit {
+			DataUnit::Bytes => bytes as f64,
+			DataUnit::Bits => (bytes as f64) * 8.0,
+		}
+	}
+
+	fn labels(&self) -> &'static [&'static str; TIERS] {
+		match (self.unit, self.prefix) {
+			(DataUnit::Bytes, UnitPrefix::Decimal) => &BYTE_DECIMAL,
+			(DataUnit::Bytes, UnitPrefix::Binary) => &BYTE_BINARY,
+			(DataUnit::Bits, UnitPrefix::Decimal) => &BIT_DECIMAL,
+			(DataUnit::Bits, UnitPrefix::Binary) => &BIT_BINARY,
+		}
+	}
+
+	fn compact_labels(&self) -> &'static [&'static str; TIERS] {
+		match (self.unit, self.prefix) {
+			(DataUnit::Bits, UnitPrefix::Decimal) => &BIT_DECIMAL_COMPACT,
+			(DataUnit::Bits, UnitPrefix::Binary) => &BIT_BINARY_COMPACT,
+			_ => self.labels(),
+		}
+	}
+
+	/// Picks the largest prefix that keeps the value, once rounded to
+	/// `decimals(tier)` places, below the next prefix step.
+	fn scale(&self, bytes: u64, decimals: impl Fn(usize) -> usize) -> (f64, usize) {
+		let base = self.base();
+		let mut value = self.quantity(bytes);
+		let mut tier = 0;
+		while tier < TIERS - 1 && round_to(value, decimals(tier)) >= base {
+			value /= base;
+			tier += 1;
+		}
+		(value, tier)
+	}
+}
+
+fn round_to(value: f64, decimals: usize) -> f64 {
+	let factor = (10f64).powi(decimals as i32);
+	(value * factor).round() / factor
+}
+
+/// A byte count such as a session total or quota, e.g. `1.5 GiB` or `12.0 Gbit`.
+pub fn format_bytes(bytes: u64, units: UnitSystem, numbers: NumberFormat) -> String {
+	let decimals = |tier: usize| if tier == 0 { 0 } else { 1 };
+	let (value, tier) = units.scale(bytes, decimals);
+	format!("{} {}", numbers.fixed(value, decimals(tier)), units.labels()[tier])
+}
+
+pub fn format_speed_full(bytes_per_sec: u64, units: UnitSystem, numbers: NumberFormat) -> String {
+	format!("{}/s", format_bytes(bytes_per_sec, units, numbers))
+}
+
+/// A graph axis label such as `500 KiB/s` or `2.5 Mbit/s`. Takes a float
+/// because a round number of bits need not be a whole number of bytes.
+#[cfg(any(tray, feature = "tui", test))]
+pub fn format_speed_tick(bytes_per_sec: f64, units: UnitSystem, numbers: NumberFormat) -> String {
+	let base = units.base();
+	let mut value = units.quantity(1) * bytes_per_sec;
+	let mut tier = 0;
+	while tier < TIERS - 1 && value >= base * (1.0 - 1e-9) {
+		value /= base;
+		tier += 1;
+	}
+	let decimals = if (value - value.round()).abs() < 0.005 { 0 } else { 1 };
+	format!("{} {}/s", numbers.fixed(value, decimals), units.labels()[tier])
+}
+
+/// Number and unit for the tray icon, never wider than `COMPACT_MAX_CHARS`.
+/// Walks up the prefixes and, within each, from one decimal down to none,
+/// returning the first number that fits.
+pub fn format_speed_compact(
+	bytes_per_sec: u64,
+	units: UnitSystem,
+	numbers: NumberFormat
+) -> (String, String) {
+	let base = units.base();
+	let mut value = units.quantity(bytes_per_sec);
+
+	for tier in 0..TIERS {
+		// Whole bits and bytes have no fractional part worth showing.
+		let max_decimals = if tier == 0 { 0 } else { 1 };
+		for decimals in (0..=max_decimals).rev() {
+			let text = numbers.fixed(value, decimals);
+			if text.chars().count() <= COMPACT_MAX_CHARS {
+				return (text, units.compact_labels()[tier].to_string());
+			}
+		}
+		if tier < TIERS - 1 {
+			value /= base;
+		}
+	}
+
+	(numbers.fixed(value, 0), units.compact_labels()[TIERS - 1].to_string())
+}
+
+/// The two largest components of a duration, e.g. `2 h 7 min` or `45 s`.
+pub fn format_duration(duration: Duration, i18n: &I18n) -> String {
+	let secs = duration.as_secs();
+	let (days, hours, minutes, seconds) = (secs / 86_400, (secs / 3600) % 24, (secs / 60) % 60, secs % 60);
+
+	let mut args = FluentArgs::new();
+	let id = if days > 0 {
+		args.set("days", days);
+		args.set("hours", hours);
+		"duration-days"
+	} else if hours > 0 {
+		args.set("hours", hours);
+		args.set("minutes", minutes);
+		"duration-hours"
+	} else if minutes > 0 {
+		args.set("minutes", minutes);
+		args.set("seconds", seconds);
+		"duration-minutes"
 	} else {
-		format!("{:.1} GB/s", (bytes_per_sec as f64) / (1024.0 * 1024.0 * 1024.0))
+		args.set("seconds", seconds);
+		"duration-seconds"
+	};
+	i18n.message(id, Some(&args))
+}
+
+/// Like `format_duration`, but never finer than a minute, e.g. `12 min` or
+/// `under 1 min`, for text that should not change every second.
+#[cfg(any(tray, test))]
+pub fn format_duration_minutes(duration: Duration, i18n: &I18n) -> String {
+	let minutes = duration.as_secs() / 60;
+	if minutes >= 60 {
+		return format_duration(duration, i18n);
 	}
+	if minutes == 0 {
+		return i18n.message("duration-under-minute", None);
+	}
+	let mut args = FluentArgs::new();
+	args.set("minutes", minutes);
+	i18n.message("duration-whole-minutes", Some(&args))
 }
 
-pub fn format_speed_compact(bytes_per_sec: u64) -> (String, String) {
-	if bytes_per_sec < 1024 {
-		(format!("{}", bytes_per_sec), "B".to_string())
-	} else if bytes_per_sec < 1024 * 1024 {
-		(format!("{:.0}", (bytes_per_sec as f64) / 1024.0), "KB".to_string())
-	} else if bytes_per_sec < 1024 * 1024 * 1024 {
-		(format!("{:.1}", (bytes_per_sec as f64) / (1024.0 * 1024.0)), "MB".to_string())
+/// How long ago something happened, e.g. `3 min ago`, in the largest
+/// whole unit.
+#[cfg(any(tray, test))]
+pub fn format_relative(elapsed: Duration, i18n: &I18n) -> String {
+	let secs = elapsed.as_secs();
+	let (id, count) = if secs < 5 {
+		("relative-now", 0)
+	} else if secs < 60 {
+		("relative-seconds", secs)
+	} else if secs < 3600 {
+		("relative-minutes", secs / 60)
+	} else if secs < 86_400 {
+		("relative-hours", secs / 3600)
 	} else {
-		(format!("{:.1}", (bytes_per_sec as f64) / (1024.0 * 1024.0 * 1024.0)), "GB".to_string())
+		("relative-days", secs / 86_400)
+	};
+
+	let mut args = FluentArgs::new();
+	args.set("count", count);
+	i18n.message(id, Some(&args))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const BYTES_SI: UnitSystem = UnitSystem::new(DataUnit::Bytes, UnitPrefix::Decimal);
+	const BYTES_IEC: UnitSystem = UnitSystem::new(DataUnit::Bytes, UnitPrefix::Binary);
+	const BITS_SI: UnitSystem = UnitSystem::new(DataUnit::Bits, UnitPrefix::Decimal);
+	const BITS_IEC: UnitSystem = UnitSystem::new(DataUnit::Bits, UnitPrefix::Binary);
+
+	const POINT: NumberFormat = NumberFormat::POINT;
+
+	const KIB: u64 = 1024;
+	const MIB: u64 = 1024 * KIB;
+	const GIB: u64 = 1024 * MIB;
+	const TIB: u64 = 1024 * GIB;
+
+	#[test]
+	fn full_bytes_decimal() {
+		let cases: &[(u64, &str)] = &[
+			(0, "0 B/s"),
+			(1, "1 B/s"),
+			(999, "999 B/s"),
+			(1000, "1.0 kB/s"),
+			(1049, "1.0 kB/s"),
+			(1050, "1.1 kB/s"),
+			(999_949, "999.9 kB/s"),
+			(999_950, "1.0 MB/s"),
+			(1_000_000, "1.0 MB/s"),
+			(999_949_999, "999.9 MB/s"),
+			(999_950_000, "1.0 GB/s"),
+			(1_000_000_000, "1.0 GB/s"),
+			(999_950_000_000, "1.0 TB/s"),
+			(999_949_999_999_999, "999.9 TB/s"),
+			(1_000_000_000_000_000, "1.0 PB/s"),
+			(1_000_000_000_000_000_000, "1.0 EB/s"),
+			(u64::MAX, "18.4 EB/s"),
+		];
+		for &(bytes, expected) in cases {
+			assert_eq!(format_speed_full(bytes, BYTES_SI, POINT), expected, "{} bytes", bytes);
+		}
+	}
+
+	#[test]
+	fn full_bytes_binary() {
+		let cases: &[(u64, &str)] = &[
+			(0, "0 B/s"),
+			(1023, "1023 B/s"),
+			(1024, "1.0 KiB/s"),
+			(1536, "1.5 KiB/s"),
+			(MIB - 52, "1023.9 KiB/s"),
+			(MIB - 51, "1.0 MiB/s"),
+			(MIB, "1.0 MiB/s"),
+			(100 * MIB, "100.0 MiB/s"),
+			(GIB - 1, "1.0 GiB/s"),
+			(GIB, "1.0 GiB/s"),
+			(TIB, "1.0 TiB/s"),
+			(1024 * TIB, "1.0 PiB/s"),
+			(u64::MAX, "16.0 EiB/s"),
+		];
+		for &(bytes, expected) in cases {
+			assert_eq!(format_speed_full(bytes, BYTES_IEC, POINT), expected, "{} bytes", bytes);
+		}
+	}
+
+	#[test]
+	fn full_bits_decimal() {
+		let cases: &[(u64, &str)] = &[
+			(0, "0 bit/s"),
+			(1, "8 bit/s"),
+			(124, "992 bit/s"),
+			(125, "1.0 kbit/s"),
+			(124_993, "999.9 kbit/s"),
+			(124_994, "1.0 Mbit/s"),
+			(125_000, "1.0 Mbit/s"),
+			(12_500_000, "100.0 Mbit/s"),
+			(125_000_000, "1.0 Gbit/s"),
+			(125_000_000_000, "1.0 Tbit/s"),
+		];
+		for &(bytes, expected) in cases {
+			assert_eq!(format_speed_full(bytes, BITS_SI, POINT), expected, "{} bytes", bytes);
+		}
+	}
+
+	#[test]
+	fn full_bits_binary() {
+		let cases: &[(u64, &str)] = &[
+			(0, "0 bit/s"),
+			(127, "1016 bit/s"),
+			(128, "1.0 Kibit/s"),
+			(MIB / 8 - 7, "1023.9 Kibit/s"),
+			(MIB / 8, "1.0 Mibit/s"),
+			(GIB / 8, "1.0 Gibit/s"),
+			(TIB / 8, "1.0 Tibit/s"),
+			(u64::MAX, "128.0 Eibit/s"),
+		];
+		for &(bytes, expected) in cases {
+			assert_eq!(format_speed_full(bytes, BITS_IEC, POINT), expected, "{} bytes", bytes);
+		}
+	}
+
+	#[test]
+	fn ticks_drop_the_decimal_when_round() {
+		let cases: &[(f64, UnitSystem, &str)] = &[
+			(0.0, BYTES_IEC, "0 B/s"),
+			((512 * KIB) as f64, BYTES_IEC, "512 KiB/s"),
+			(MIB as f64, BYTES_IEC, "1 MiB/s"),
+			(2.5 * (MIB as f64), BYTES_IEC, "2.5 MiB/s"),
+			(625_000.0, BITS_SI, "5 Mbit/s"),
+			(0.625, BITS_SI, "5 bit/s"),
+		];
+		for &(bytes, units, expected) in cases {
+			assert_eq!(format_speed_tick(bytes, units, POINT), expected, "{} bytes", bytes);
+		}
+		assert_eq!(format_speed_tick(2.5 * (MIB as f64), BYTES_IEC, NumberFormat::COMMA), "2,5 MiB/s");
+	}
+
+	#[test]
+	fn compact_bytes() {
+		let cases: &[(UnitSystem, u64, &str, &str)] = &[
+			(BYTES_SI, 0, "0", "B"),
+			(BYTES_SI, 999, "999", "B"),
+			(BYTES_SI, 1000, "1.0", "kB"),
+			(BYTES_SI, 1500, "1.5", "kB"),
+			(BYTES_SI, 9949, "9.9", "kB"),
+			(BYTES_SI, 9951, "10", "kB"),
+			(BYTES_SI, 999_499, "999", "kB"),
+			(BYTES_SI, 999_500, "1.0", "MB"),
+			(BYTES_SI, 123_456_789, "123", "MB"),
+			(BYTES_SI, 999_500_000, "1.0", "GB"),
+			(BYTES_IEC, 999, "999", "B"),
+			(BYTES_IEC, 1000, "1.0", "KiB"),
+			(BYTES_IEC, 1023, "1.0", "KiB"),
+			(BYTES_IEC, 1024, "1.0", "KiB"),
+			(BYTES_IEC, 1536, "1.5", "KiB"),
+			(BYTES_IEC, 999 * KIB + 511, "999", "KiB"),
+			(BYTES_IEC, 999 * KIB + 512, "1.0", "MiB"),
+			(BYTES_IEC, MIB - 1, "1.0", "MiB"),
+			(BYTES_IEC, GIB, "1.0", "GiB"),
+			(BYTES_IEC, u64::MAX, "16", "EiB"),
+		];
+		for &(units, bytes, value, unit) in cases {
+			assert_eq!(
+				format_speed_compact(bytes, units, POINT),
+				(value.to_string(), unit.to_string()),
+				"{} bytes in {:?}",
+				bytes,
+				units
+			);
+		}
+	}
+
+	#[test]
+	fn compact_bits() {
+		let cases: &[(UnitSystem, u64, &str, &str)] = &[
+			(BITS_SI, 0, "0", "b"),
+			(BITS_SI, 124, "992", "b"),
+			(BITS_SI, 125, "1.0", "kb"),
+			(BITS_SI, 124_937, "999", "kb"),
+			(BITS_SI, 124_938, "1.0", "Mb"),
+			(BITS_SI, 12_500_000, "100", "Mb"),
+			(BITS_SI, 125_000_000, "1.0", "Gb"),
+			(BITS_SI, u64::MAX, "148", "Eb"),
+			(BITS_IEC, 124, "992", "b"),
+			(BITS_IEC, 125, "1.0", "Kib"),
+			(BITS_IEC, MIB / 8, "1.0", "Mib"),
+			(BITS_IEC, GIB / 8, "1.0", "Gib"),
+			(BITS_IEC, u64::MAX, "128", "Eib"),
+		];
+		for &(units, bytes, value, unit) in cases {
+			assert_eq!(
+				format_speed_compact(bytes, units, POINT),
+				(value.to_string(), unit.to_string()),
+				"{} bytes in {:?}",
+				bytes,
+				units
+			);
+		}
+	}
+
+	/// Every power of two and ten (and their neighbours) plus a fine
+	/// geometric sweep from 1 to `u64::MAX`.
+	fn sweep() -> Vec<u64> {
+		let mut values = vec![0, u64::MAX];
+		for shift in 0..64 {
+			let p = 1u64 << shift;
+			values.extend([p - 1, p, p.saturating_add(1)]);
+		}
+		let mut p = 1u64;
+		while let Some(next) = p.checked_mul(10) {
+			values.extend([p - 1, p, p + 1]);
+			p = next;
+		}
+		let mut x = 1.0f64;
+		while x < (u64::MAX as f64) {
+			values.push(x as u64);
+			x *= 1.0007;
+		}
+		values.sort_unstable();
+		values.dedup();
+		values
+	}
+
+	#[test]
+	fn compact_always_fits_and_stays_accurate() {
+		for units in [BYTES_SI, BYTES_IEC, BITS_SI, BITS_IEC] {
+			let labels = units.compact_labels();
+			let mut previous = 0.0;
+			for bytes in sweep() {
+				let (value, unit) = format_speed_compact(bytes, units, POINT);
+				assert!(value.len() <= COMPACT_MAX_CHARS, "{} -> {}{}", bytes, value, unit);
+
+				let tier = labels.iter().position(|&label| label == unit).unwrap();
+				let shown = value.parse::<f64>().unwrap() * units.base().powi(tier as i32);
+				let actual = units.quantity(bytes);
+				if actual > 0.0 {
+					let error = (shown - actual).abs() / actual;
+					assert!(error <= 0.052, "{} -> {}{} is off by {:.3}", bytes, value, unit, error);
+				}
+				assert!(shown >= previous, "{} -> {}{} went backwards", bytes, value, unit);
+				previous = shown;
+			}
+		}
+	}
+
+	#[test]
+	fn default_matches_previous_1024_scaling() {
+		assert_eq!(UnitSystem::default(), BYTES_IEC);
+		assert_eq!(format_speed_full(5 * MIB, UnitSystem::default(), POINT), "5.0 MiB/s");
+	}
+
+	#[test]
+	fn number_format_follows_language() {
+		assert_eq!(NumberFormat::for_locale("en-US"), NumberFormat::POINT);
+		assert_eq!(NumberFormat::for_locale("ja"), NumberFormat::POINT);
+		assert_eq!(NumberFormat::for_locale("de-DE"), NumberFormat::COMMA);
+		assert_eq!(NumberFormat::for_locale("ru_RU"), NumberFormat::COMMA);
+		assert_eq!(NumberFormat::for_locale(""), NumberFormat::POINT);
+	}
+
+	#[test]
+	fn comma_locales_use_comma_everywhere() {
+		let comma = NumberFormat::COMMA;
+		assert_eq!(format_speed_full(1536, BYTES_IEC, comma), "1,5 KiB/s");
+		assert_eq!(format_speed_full(999, BYTES_IEC, comma), "999 B/s");
+		assert_eq!(format_speed_full(12_500_000, BITS_SI, comma), "100,0 Mbit/s");
+		assert_eq!(format_speed_compact(1536, BYTES_IEC, comma), ("1,5".to_string(), "KiB".to_string()));
+		assert_eq!(format_speed_compact(15_360, BYTES_IEC, comma), ("15".to_string(), "KiB".to_string()));
+	}
+
+	#[test]
+	fn bytes_share_speed_scaling() {
+		let cases: &[(UnitSystem, u64, &str)] = &[
+			(BYTES_IEC, 0, "0 B"),
+			(BYTES_IEC, 1023, "1023 B"),
+			(BYTES_IEC, 1024, "1.0 KiB"),
+			(BYTES_IEC, 3 * GIB / 2, "1.5 GiB"),
+			(BYTES_SI, 999_949, "999.9 kB"),
+			(BYTES_SI, 999_950, "1.0 MB"),
+			(BYTES_SI, 2_500_000_000_000, "2.5 TB"),
+			(BITS_SI, 1_500_000_000, "12.0 Gbit"),
+			(BITS_IEC, MIB, "8.0 Mibit"),
+			(BYTES_SI, u64::MAX, "18.4 EB"),
+		];
+		for &(units, bytes, expected) in cases {
+			assert_eq!(format_bytes(bytes, units, POINT), expected, "{} bytes in {:?}", bytes, units);
+			assert_eq!(format_speed_full(bytes, units, POINT), format!("{}/s", expected));
+		}
+		assert_eq!(format_bytes(3 * GIB / 2, BYTES_IEC, NumberFormat::COMMA), "1,5 GiB");
+	}
+
+	#[test]
+	fn durations_show_two_largest_components() {
+		let en = I18n::new(Some("en"));
+		let cases: &[(u64, &str)] = &[
+			(0, "0 s"),
+			(59, "59 s"),
+			(60, "1 min 0 s"),
+			(185, "3 min 5 s"),
+			(3599, "59 min 59 s"),
+			(3600, "1 h 0 min"),
+			(7620, "2 h 7 min"),
+			(86_399, "23 h 59 min"),
+			(86_400, "1 d 0 h"),
+			(3 * 86_400 + 4 * 3600 + 59, "3 d 4 h"),
+		];
+		for &(secs, expected) in cases {
+			assert_eq!(format_duration(Duration::from_secs(secs), &en), expected, "{} s", secs);
+		}
+	}
+
+	#[test]
+	fn minute_durations_drop_the_seconds() {
+		let en = I18n::new(Some("en"));
+		let cases: &[(u64, &str)] = &[
+			(0, "under 1 min"),
+			(59, "under 1 min"),
+			(60, "1 min"),
+			(185, "3 min"),
+			(3599, "59 min"),
+			(7620, "2 h 7 min"),
+			(7679, "2 h 7 min"),
+		];
+		for &(secs, expected) in cases {
+			let text = format_duration_minutes(Duration::from_secs(secs), &en);
+			assert_eq!(text, expected, "{} s", secs);
+		}
+		let de = I18n::new(Some("de"));
+		assert_eq!(format_duration_minutes(Duration::from_secs(185), &de), "3 Min.");
+	}
+
+	#[test]
+	fn relative_times_round_down_to_largest_unit() {
+		let en = I18n::new(Some("en"));
+		let cases: &[(u64, &str)] = &[
+			(0, "just now"),
+			(4, "just now"),
+			(5, "5 s ago"),
+			(59, "59 s ago"),
+			(60, "1 min ago"),
+			(199, "3 min ago"),
+			(3599, "59 min ago"),
+			(3600, "1 h ago"),
+			(86_399, "23 h ago"),
+			(86_400, "1 d ago"),
+			(10 * 86_400, "10 d ago"),
+		];
+		for &(secs, expected) in cases {
+			assert_eq!(format_relative(Duration::from_secs(secs), &en), expected, "{} s", secs);
+		}
+	}
+
+	#[test]
+	fn durations_and_relative_times_are_localized() {
+		let de = I18n::new(Some("de"));
+		assert_eq!(format_duration(Duration::from_secs(7620), &de), "2 Std. 7 Min.");
+		assert_eq!(format_relative(Duration::from_secs(180), &de), "vor 3 Min.");
+
+		let ru = I18n::new(Some("ru"));
+		assert_eq!(format_duration(Duration::from_secs(185), &ru), "3 мин 5 с");
+		assert_eq!(format_relative(Duration::from_secs(7200), &ru), "2 ч назад");
 	}
 }
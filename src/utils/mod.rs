@@ -1 +1,3 @@
+#[cfg(any(tray, test))]
+pub mod cache;
 pub mod format;
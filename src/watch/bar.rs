@@ -0,0 +1,153 @@
+use fluent_bundle::FluentArgs;
+use serde::Serialize;
+
+use crate::core::state::AppState;
+use crate::i18n::{ self, tr_args };
+use crate::ui::theme::to_hex;
+use crate::ui::speed::{ speed_level, SpeedBand };
+use crate::utils::format::{
+	format_bytes,
+	format_duration,
+	format_speed_compact,
+	format_speed_full,
+};
+
+/// Opens the i3bar protocol: a header, then an endless array of status lines.
+pub const I3BAR_HEADER: &str = "{\"version\":1}\n[";
+
+/// Waybar's `return-type: json` module output.
+#[derive(Serialize)]
+struct Waybar {
+	text: String,
+	tooltip: String,
+	class: &'static str,
+	percentage: u32,
+}
+
+/// One block of an i3bar status line.
+#[derive(Serialize)]
+struct I3barBlock<'a> {
+	name: &'static str,
+	instance: &'a str,
+	full_text: String,
+	short_text: String,
+	color: String,
+}
+
+/// Both rates as short as the tray icon shows them, e.g. `↓ 1.2 MiB/s ↑ 30 KiB/s`.
+fn text(state: &AppState) -> String {
+	format!("{} {}", download(state), upload(state))
+}
+
+fn download(state: &AppState) -> String {
+	let (value, unit) = format_speed_compact(state.down_bps, state.units, state.numbers);
+	format!("↓ {} {}/s", value, unit)
+}
+
+fn upload(state: &AppState) -> String {
+	let (value, unit) = format_speed_compact(state.up_bps, state.units, state.numbers);
+	format!("↑ {} {}/s", value, unit)
+}
+
+/// Coloured by the busier direction, as the tray's activity dot is.
+fn band(state: &AppState) -> SpeedBand {
+	SpeedBand::of(state.down_bps.max(state.up_bps))
+}
+
+/// The tray tooltip under the interface name.
+fn tooltip(state: &AppState) -> String {
+	let (units, numbers) = (state.units, state.numbers);
+	let mut args = FluentArgs::new();
+	args.set("down", format_speed_full(state.down_bps, units, numbers));
+	args.set("up", format_speed_full(state.up_bps, units, numbers));
+	let speeds = tr_args("tooltip-speeds", &args);
+
+	let mut args = FluentArgs::new();
+	args.set("down", format_bytes(state.total_down, units, numbers));
+	args.set("up", format_bytes(state.total_up, units, numbers));
+	args.set("duration", format_duration(state.session_duration(), i18n::current()));
+	format!("{}\n{}\n{}", state.interface_name, speeds, tr_args("tooltip-session", &args))
+}
+
+/// A Waybar custom module line; `class` is `idle`, `active` or `busy` and
+/// `percentage` follows the tray bars' log scale.
+pub fn waybar(state: &AppState) -> String {
+	let line = Waybar {
+		text: text(state),
+		tooltip: tooltip(state),
+		class: band(state).name(),
+		percentage: (speed_level(state.down_bps.max(state.up_bps)) * 100.0).round() as u32,
+	};
+	serde_json::to_string(&line).unwrap_or_default()
+}
+
+/// An i3bar status line; every one after the `first` continues the array.
+pub fn i3bar(state: &AppState, first: bool) -> String {
+	let block = I3barBlock {
+		name: "netflux",
+		instance: &state.interface_name,
+		full_text: text(state),
+		short_text: download(state),
+		color: to_hex(band(state).color(&state.theme)),
+	};
+	let line = serde_json::to_string(&[block]).unwrap_or_default();
+	if first { line } else { format!(",{}", line) }
+}
+
+/// A Polybar `tail = true` script line, coloured with format tags.
+pub fn polybar(state: &AppState) -> String {
+	format!("%{{F{}}}{}%{{F-}}", to_hex(band(state).color(&state.theme)), text(state))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::monitor::NetStats;
+	use crate::utils::format::NumberFormat;
+
+	fn state(down_bps: u64, up_bps: u64) -> AppState {
+		let mut state = AppState::new();
+		state.numbers = NumberFormat::POINT;
+		state.update(NetStats {
+			down_bps,
+			up_bps,
+			down_bytes: down_bps,
+			up_bytes: up_bps,
+			interface_name: "eth0".to_string(),
+		});
+		state
+	}
+
+	#[test]
+	fn waybar_lines_carry_class_and_percentage() {
+		i18n::init(Some("en"));
+		let parse = |line: String| serde_json::from_str::<serde_json::Value>(&line).unwrap();
+		let line = parse(waybar(&state(1_258_291, 30_720)));
+		assert_eq!(line["text"], "↓ 1.2 MiB/s ↑ 30 KiB/s");
+		assert_eq!(line["class"], "active");
+		assert_eq!(line["percentage"], 51);
+		let tooltip = line["tooltip"].as_str().unwrap();
+		let session = "eth0\nDown: 1.2 MiB/s | Up: 30.0 KiB/s\nSession: ↓ 1.2 MiB";
+		assert!(tooltip.starts_with(session), "{}", tooltip);
+
+		let idle = parse(waybar(&state(0, 0)));
+		assert_eq!((idle["class"].as_str(), idle["percentage"].as_u64()), (Some("idle"), Some(0)));
+	}
+
+	#[test]
+	fn i3bar_lines_continue_one_array() {
+		let busy = state(8 * 1024 * 1024, 0);
+		assert_eq!(
+			i3bar(&busy, true),
+			"[{\"name\":\"netflux\",\"instance\":\"eth0\",\
+			 \"full_text\":\"↓ 8.0 MiB/s ↑ 0 B/s\",\"short_text\":\"↓ 8.0 MiB/s\",\
+			 \"color\":\"#eab308\"}]"
+		);
+		assert!(i3bar(&busy, false).starts_with(",[{"));
+	}
+
+	#[test]
+	fn polybar_lines_use_color_tags() {
+		assert_eq!(polybar(&state(512, 0)), "%{F#aaaaaa}↓ 512 B/s ↑ 0 B/s%{F-}");
+	}
+}
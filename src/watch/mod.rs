@@ -0,0 +1,243 @@
+mod bar;
+
+use std::io::{ IsTerminal, Write };
+use std::thread;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+use serde::Serialize;
+
+use crate::core::monitor::{ NetMonitor, NetStats };
+use crate::core::settings::Settings;
+use crate::core::state::AppState;
+use crate::i18n;
+use crate::ui::theme::Theme;
+use crate::utils::format::format_speed_full;
+
+const DOWN_COLOR: &str = "\x1b[32m";
+const UP_COLOR: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+const TSV_HEADER: &str =
+	"time\tinterface\tdown_bytes_per_sec\tup_bytes_per_sec\tdown_bytes\tup_bytes";
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum WatchFormat {
+	/// `eth0  ↓ 12.3 MB/s  ↑ 1.1 MB/s`, in the units from the settings.
+	Human,
+	/// Tab-separated values under a header line, rates in bytes per second.
+	Tsv,
+	/// One JSON object per line.
+	Json,
+	/// A Waybar custom module with `return-type: json`.
+	Waybar,
+	/// The i3bar protocol, as an i3 `status_command`.
+	I3bar,
+	/// A Polybar script with `tail = true`.
+	Polybar,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct WatchOptions {
+	pub format: WatchFormat,
+	/// Lines to print before exiting; `None` runs until interrupted.
+	pub count: Option<u64>,
+	pub interval: Duration,
+	/// Only this interface instead of the busiest.
+	pub interface: Option<String>,
+	/// No colours. Implied when stdout is not a terminal or `NO_COLOR` is set.
+	pub plain: bool,
+}
+
+impl Default for WatchOptions {
+	fn default() -> Self {
+		Self {
+			format: WatchFormat::Human,
+			count: None,
+			interval: Duration::from_secs(1),
+			interface: None,
+			plain: false,
+		}
+	}
+}
+
+#[derive(Serialize)]
+struct Sample<'a> {
+	time: f64,
+	interface: &'a str,
+	down_bytes_per_sec: u64,
+	up_bytes_per_sec: u64,
+	down_bytes: u64,
+	up_bytes: u64,
+}
+
+/// Prints a line per `options.interval` until `options.count` lines are out
+/// or stdout is closed.
+pub fn run(options: WatchOptions) -> Result<(), String> {
+	let settings = Settings::load().unwrap_or_else(|e| {
+		eprintln!("netflux: {}", e);
+		Settings::default()
+	});
+	i18n::init(settings.language.as_deref());
+	let mut state = AppState::new();
+	state.set_units(settings.units);
+	state.set_numbers(i18n::numbers());
+	// A broken theme only costs the colours; the rates still print.
+	match Theme::resolve(&settings.theme, None) {
+		Ok(theme) => state.set_theme(theme),
+		Err(e) => eprintln!("netflux: {}", e),
+	}
+	let plain = options.plain ||
+		!std::io::stdout().is_terminal() ||
+		std::env::var_os("NO_COLOR").is_some();
+	let mut stdout = std::io::stdout().lock();
+	let mut write = |line: &str| writeln!(stdout, "{}", line).and_then(|_| stdout.flush()).is_ok();
+
+	let preamble = match options.format {
+		WatchFormat::Tsv => Some(TSV_HEADER),
+		WatchFormat::I3bar => Some(bar::I3BAR_HEADER),
+		_ => None,
+	};
+	if preamble.is_some_and(|preamble| !write(preamble)) {
+		return Ok(());
+	}
+	let mut monitor = NetMonitor::new();
+	monitor.tick();
+	let mut printed = 0;
+	let mut checked = false;
+	while options.count.is_none_or(|count| printed < count) {
+		thread::sleep(options.interval);
+		let busiest = monitor.tick();
+		let stats = match &options.interface {
+			Some(name) => {
+				let stats = monitor.interfaces().iter().find(|stats| &stats.interface_name == name);
+				if stats.is_none() && !checked {
+					let names: Vec<&str> = monitor
+						.interfaces()
+						.iter()
+						.map(|stats| stats.interface_name.as_str())
+						.collect();
+					let names = names.join(", ");
+					return Err(format!("No active interface '{}'; found: {}", name, names));
+				}
+				stats.cloned()
+			}
+			None => busiest,
+		};
+		checked = true;
+		// A tick without a reading, e.g. with every interface down, prints nothing.
+		let Some(stats) = stats else {
+			continue;
+		};
+		let time = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_or(0.0, |time| time.as_secs_f64());
+		state.update(stats.clone());
+		let line = format_line(&stats, &state, time, options.format, plain, printed == 0);
+		if !write(&line) {
+			// The reader went away, e.g. `netflux watch | head`.
+			return Ok(());
+		}
+		printed += 1;
+	}
+	Ok(())
+}
+
+/// The line for one tick: `stats` from the monitor, `state` with them
+/// recorded, `first` for the first line after the preamble.
+fn format_line(
+	stats: &NetStats,
+	state: &AppState,
+	time: f64,
+	format: WatchFormat,
+	plain: bool,
+	first: bool
+) -> String {
+	let (units, numbers) = (state.units, state.numbers);
+	match format {
+		WatchFormat::Human => {
+			let (down, up, reset) = if plain {
+				("", "", "")
+			} else {
+				(DOWN_COLOR, UP_COLOR, RESET)
+			};
+			format!(
+				"{}  {}↓{} {}  {}↑{} {}",
+				stats.interface_name,
+				down,
+				reset,
+				format_speed_full(stats.down_bps, units, numbers),
+				up,
+				reset,
+				format_speed_full(stats.up_bps, units, numbers)
+			)
+		}
+		WatchFormat::Tsv =>
+			format!(
+				"{:.3}\t{}\t{}\t{}\t{}\t{}",
+				time,
+				stats.interface_name.replace(['\t', '\n'], " "),
+				stats.down_bps,
+				stats.up_bps,
+				stats.down_bytes,
+				stats.up_bytes
+			),
+		WatchFormat::Json => {
+			let sample = Sample {
+				time: (time * 1000.0).round() / 1000.0,
+				interface: &stats.interface_name,
+				down_bytes_per_sec: stats.down_bps,
+				up_bytes_per_sec: stats.up_bps,
+				down_bytes: stats.down_bytes,
+				up_bytes: stats.up_bytes,
+			};
+			serde_json::to_string(&sample).unwrap_or_default()
+		}
+		WatchFormat::Waybar => bar::waybar(state),
+		WatchFormat::I3bar => bar::i3bar(state, first),
+		WatchFormat::Polybar => bar::polybar(state),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::format::{ DataUnit, NumberFormat, UnitPrefix, UnitSystem };
+
+	fn stats() -> NetStats {
+		NetStats {
+			down_bps: 12_300_000,
+			up_bps: 1_100_000,
+			down_bytes: 24_600_000,
+			up_bytes: 2_200_000,
+			interface_name: "eth0".to_string(),
+		}
+	}
+
+	fn line(format: WatchFormat, plain: bool) -> String {
+		let mut state = AppState::new();
+		state.set_units(UnitSystem::new(DataUnit::Bytes, UnitPrefix::Decimal));
+		state.set_numbers(NumberFormat::POINT);
+		state.update(stats());
+		format_line(&stats(), &state, 1_760_870_400.123_4, format, plain, true)
+	}
+
+	#[test]
+	fn human_lines_are_colored_only_on_terminals() {
+		assert_eq!(line(WatchFormat::Human, true), "eth0  ↓ 12.3 MB/s  ↑ 1.1 MB/s");
+		assert_eq!(
+			line(WatchFormat::Human, false),
+			"eth0  \x1b[32m↓\x1b[0m 12.3 MB/s  \x1b[35m↑\x1b[0m 1.1 MB/s"
+		);
+	}
+
+	#[test]
+	fn machine_formats_carry_raw_numbers() {
+		let tsv = line(WatchFormat::Tsv, false);
+		assert_eq!(tsv, "1760870400.123\teth0\t12300000\t1100000\t24600000\t2200000");
+		assert_eq!(TSV_HEADER.split('\t').count(), tsv.split('\t').count());
+		assert_eq!(
+			line(WatchFormat::Json, false),
+			"{\"time\":1760870400.123,\"interface\":\"eth0\",\"down_bytes_per_sec\":12300000,\
+			 \"up_bytes_per_sec\":1100000,\"down_bytes\":24600000,\"up_bytes\":2200000}"
+		);
+	}
+}
@@ -0,0 +1,84 @@
+//! Scrapes `netflux metrics` over HTTP as Prometheus would.
+
+use std::io::{ BufRead, BufReader, Read, Write };
+use std::net::TcpStream;
+use std::process::{ Child, Command, Stdio };
+use std::time::Duration;
+
+/// Stops the server even when an assertion fails.
+struct Server(Child);
+
+impl Drop for Server {
+	fn drop(&mut self) {
+		let _ = self.0.kill();
+		let _ = self.0.wait();
+	}
+}
+
+/// Starts the server on a free port and returns it with its address.
+fn start() -> (Server, String) {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_netflux"))
+		.args(["metrics", "--listen", "127.0.0.1:0"])
+		.stdout(Stdio::piped())
+		.spawn()
+		.expect("netflux should start");
+	let stdout = child.stdout.take().unwrap();
+	let server = Server(child);
+
+	let mut line = String::new();
+	BufReader::new(stdout).read_line(&mut line).unwrap();
+	let address = line
+		.trim()
+		.strip_prefix("Serving metrics on http://")
+		.and_then(|rest| rest.strip_suffix("/metrics"))
+		.unwrap_or_else(|| panic!("unexpected banner {:?}", line))
+		.to_string();
+	(server, address)
+}
+
+/// Sends `request` and splits the response into its head and body.
+fn send(address: &str, request: &str) -> (String, String) {
+	let mut stream = TcpStream::connect(address).unwrap();
+	stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+	stream.write_all(request.as_bytes()).unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).unwrap();
+	let (head, body) = response.split_once("\r\n\r\n").expect("a complete response");
+	(head.to_string(), body.to_string())
+}
+
+#[test]
+fn serves_openmetrics_counters_per_interface() {
+	let (_server, address) = start();
+	let request = "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n";
+	let (head, body) = send(&address, request);
+
+	assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head);
+	assert!(head.contains("Content-Type: application/openmetrics-text; version=1.0.0"), "{}", head);
+	assert!(head.contains(&format!("Content-Length: {}", body.len())), "{}", head);
+	for family in ["receive_bytes", "transmit_bytes", "receive_packets", "receive_errors"] {
+		assert!(body.contains(&format!("# TYPE netflux_{} counter\n", family)), "{}", body);
+	}
+	assert!(body.contains("# TYPE netflux_receive_bytes_per_second gauge\n"), "{}", body);
+	assert!(body.ends_with("# EOF\n"), "{}", body);
+
+	// The sandbox may have no interface up; every sample there is must be
+	// labelled and numeric.
+	for sample in body.lines().filter(|line| !line.starts_with('#')) {
+		let (series, value) = sample.rsplit_once(' ').unwrap();
+		assert!(series.starts_with("netflux_") && series.contains("{interface=\""), "{}", sample);
+		assert!(value.parse::<u64>().is_ok(), "{}", sample);
+	}
+}
+
+#[test]
+fn answers_other_paths_and_methods_with_errors() {
+	let (_server, address) = start();
+	let (head, _) = send(&address, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+	assert!(head.starts_with("HTTP/1.1 404 Not Found\r\n"), "{}", head);
+	let (head, _) = send(&address, "POST /metrics HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+	assert!(head.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"), "{}", head);
+	let (head, body) = send(&address, "HEAD /metrics HTTP/1.1\r\n\r\n");
+	assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head);
+	assert!(body.is_empty());
+}